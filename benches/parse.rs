@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use mimalloc::MiMalloc;
-use tmi::IrcMessageRef;
+use tmi::{IrcMessage, IrcMessageRef, ParseArena};
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
@@ -45,5 +45,170 @@ fn twitch(c: &mut Criterion) {
   run!(c, input, "all", input.len(), IrcMessageRef::parse);
 }
 
-criterion_group!(benches, twitch);
+fn parse_into(c: &mut Criterion) {
+  let input = read_input();
+  let input = &input[..100000.min(input.len())];
+
+  let mut group = c.benchmark_group("parse_into");
+  group.bench_function("parse", |b| {
+    b.iter(|| {
+      for line in input {
+        black_box(IrcMessageRef::parse(line).expect("failed to parse"));
+      }
+    });
+  });
+  group.bench_function("parse_into", |b| {
+    let mut arena = ParseArena::new();
+    b.iter(|| {
+      for line in input {
+        let msg = IrcMessageRef::parse_into(line, &mut arena).expect("failed to parse");
+        arena.reclaim(black_box(msg));
+      }
+    });
+  });
+  group.finish();
+}
+
+/// A `PRIVMSG` with 34 emotes, taken from `examples/with_whitelist.rs`.
+const EMOTE_HEAVY: &str = "@badge-info=subscriber/22;badges=subscriber/3012;color=#FFFF00;display-name=FELYP8;emote-only=1;emotes=521050:0-6,8-14,16-22,24-30,32-38,40-46,48-54,56-62,64-70,72-78,80-86,88-94,96-102,104-110,148-154,156-162,164-170,172-178,180-186,188-194,196-202,204-210,212-218,220-226,228-234,236-242,244-250,252-258,260-266/302827730:112-119/302827734:121-128/302827735:130-137/302827737:139-146;first-msg=0;flags=;id=1844235a-c24e-4e18-937b-805d6601aebe;mod=0;returning-chatter=0;room-id=22484632;subscriber=1;tmi-sent-ts=1685664001040;turbo=0;user-id=162760707;user-type= :felyp8!felyp8@felyp8.tmi.twitch.tv PRIVMSG #forsen :forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE1 forsenE2 forsenE3 forsenE4 forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE forsenE";
+
+/// A `PRIVMSG` with no emotes.
+const PLAIN: &str = "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam";
+
+#[cfg(feature = "message-types")]
+fn emotes(c: &mut Criterion) {
+  use tmi::{FromIrc, Privmsg};
+
+  let mut group = c.benchmark_group("emotes");
+  for (name, line) in [("emote_heavy", EMOTE_HEAVY), ("plain", PLAIN)] {
+    let msg = IrcMessageRef::parse(line).expect("failed to parse");
+    let msg = Privmsg::from_irc(msg).expect("failed to parse");
+    group.bench_with_input(BenchmarkId::from_parameter(name), &msg, |b, msg| {
+      b.iter(|| black_box(msg.emotes()));
+    });
+  }
+  group.finish();
+}
+
+#[cfg(not(feature = "message-types"))]
+fn emotes(_: &mut Criterion) {}
+
+fn into_owned(c: &mut Criterion) {
+  let mut group = c.benchmark_group("into_owned");
+  for (name, line) in [("emote_heavy", EMOTE_HEAVY), ("plain", PLAIN)] {
+    let msg = IrcMessageRef::parse(line).expect("failed to parse");
+    group.bench_with_input(BenchmarkId::from_parameter(name), &msg, |b, msg| {
+      b.iter(|| black_box(msg.clone().into_owned()));
+    });
+  }
+  group.finish();
+}
+
+fn tags(c: &mut Criterion) {
+  let mut group = c.benchmark_group("tags");
+  for (name, line) in [("emote_heavy", EMOTE_HEAVY), ("plain", PLAIN)] {
+    let msg = IrcMessageRef::parse(line).expect("failed to parse");
+    group.bench_with_input(BenchmarkId::new("tags", name), &msg, |b, msg| {
+      b.iter(|| {
+        for pair in msg.tags() {
+          black_box(pair);
+        }
+      });
+    });
+    group.bench_with_input(BenchmarkId::new("tags_raw", name), &msg, |b, msg| {
+      b.iter(|| {
+        for pair in msg.tags_raw() {
+          black_box(pair);
+        }
+      });
+    });
+  }
+  group.finish();
+}
+
+fn capacity(c: &mut Criterion) {
+  let mut group = c.benchmark_group("capacity");
+  for (name, line) in [("emote_heavy", EMOTE_HEAVY), ("plain", PLAIN)] {
+    group.bench_with_input(BenchmarkId::new("default", name), line, |b, line| {
+      b.iter(|| black_box(IrcMessageRef::parse(line)));
+    });
+    group.bench_with_input(BenchmarkId::new("32", name), line, |b, line| {
+      b.iter(|| black_box(IrcMessageRef::parse_with_capacity::<32>(line)));
+    });
+    group.bench_with_input(BenchmarkId::new("64", name), line, |b, line| {
+      b.iter(|| black_box(IrcMessageRef::parse_with_capacity::<64>(line)));
+    });
+  }
+  group.finish();
+}
+
+fn blacklist(c: &mut Criterion) {
+  let mut group = c.benchmark_group("blacklist");
+  for (name, line) in [("emote_heavy", EMOTE_HEAVY), ("plain", PLAIN)] {
+    group.bench_with_input(BenchmarkId::new("all", name), line, |b, line| {
+      b.iter(|| black_box(IrcMessageRef::parse(line)));
+    });
+    group.bench_with_input(BenchmarkId::new("no_emotes", name), line, |b, line| {
+      b.iter(|| {
+        black_box(IrcMessageRef::parse_with_whitelist(
+          line,
+          tmi::blacklist!(Emotes),
+        ))
+      });
+    });
+  }
+  group.finish();
+}
+
+fn peek_command(c: &mut Criterion) {
+  let input = read_input();
+  let input = &input[..100000.min(input.len())];
+
+  let mut group = c.benchmark_group("peek_command");
+  group.bench_function("parse", |b| {
+    b.iter(|| {
+      for line in input {
+        black_box(IrcMessageRef::parse(line).map(|msg| msg.command()));
+      }
+    });
+  });
+  group.bench_function("peek_command", |b| {
+    b.iter(|| {
+      for line in input {
+        black_box(IrcMessage::peek_command(line));
+      }
+    });
+  });
+  group.finish();
+}
+
+fn unescape(c: &mut Criterion) {
+  let mut group = c.benchmark_group("unescape");
+  for (name, value) in [("plain", "randers811"), ("escaped", "Riot\\sGames")] {
+    group.bench_with_input(BenchmarkId::new("unescape", name), value, |b, value| {
+      b.iter(|| black_box(tmi::unescape(value)));
+    });
+    group.bench_with_input(
+      BenchmarkId::new("maybe_unescape", name),
+      value,
+      |b, value| {
+        b.iter(|| black_box(tmi::common::maybe_unescape(value)));
+      },
+    );
+  }
+  group.finish();
+}
+
+criterion_group!(
+  benches,
+  twitch,
+  parse_into,
+  emotes,
+  into_owned,
+  tags,
+  capacity,
+  blacklist,
+  peek_command,
+  unescape
+);
 criterion_main!(benches);
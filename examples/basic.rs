@@ -21,7 +21,10 @@ struct Args {
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
-  tracing_subscriber::fmt::init();
+  // with `--features tracing`, this logs what the client is doing.
+  tracing_subscriber::fmt()
+    .with_max_level(tracing_subscriber::filter::LevelFilter::DEBUG)
+    .init();
 
   let args = Args::parse();
 
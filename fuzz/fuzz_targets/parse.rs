@@ -0,0 +1,29 @@
+//! Run with `cargo +nightly fuzz run parse`.
+//!
+//! Build with `--features tmi/force-scalar` to fuzz the scalar parser instead of the SIMD one.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tmi::{IrcMessage, IrcMessageRef};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(src) = std::str::from_utf8(data) else {
+    return;
+  };
+
+  let Some(msg) = IrcMessageRef::parse(src) else {
+    assert!(IrcMessage::parse(src).is_none());
+    return;
+  };
+
+  for (tag, value) in msg.tags() {
+    let _ = (tag.as_str(), tmi::unescape(value));
+  }
+  let _ = (msg.prefix(), msg.prefix_str(), msg.command().numeric());
+  let _ = (msg.channel(), msg.params(), msg.text());
+  let _ = msg.clone().as_typed();
+
+  let owned = msg.into_owned();
+  assert_eq!(owned.raw(), src);
+});
@@ -9,7 +9,7 @@
 //!
 //! If you wish to be able to send messages, you have to generate an oauth2 token,
 //! and then supply the [`Credentials`] to the client via [`Client::connect_with`],
-//! or [`Client::builder`] followed by [`ClientBuilder::credentials`].
+//! or [`Client::builder`] followed by [`ClientBuilder::login`] or [`ClientBuilder::credentials`].
 //!
 //! Generating an oauth2 token is out of scope for this library.
 //! Head over to the [official documentation](https://dev.twitch.tv/docs/irc/authenticate-bot/#getting-an-access-token)
@@ -17,13 +17,13 @@
 //!
 //! ⚠ Note: [`Client`] is a fairly low-level interface! It does not automatically handle:
 //! - Rate limiting (both for JOINs and PRIVMSGs)
-//! - Same message bypass
+//! - Same message bypass, unless configured via [`ClientBuilder::duplicate_strategy`]
 //! - `RECONNECT` commands
 //! - Rejoining channels
 //! - Latency measurement
 //!
 //! What it _does_ provide is:
-//! - Opening a TCP connection (with TLS) to Twitch.
+//! - Opening a TCP connection (with TLS, unless configured via [`ClientBuilder::transport`]) to Twitch.
 //! - Performing the handshake (authentication, capability negotiation)
 //! - Reconnect with backoff
 //! - A polling interface for receiving messages
@@ -32,25 +32,45 @@
 #[macro_use]
 mod macros;
 
+mod caps;
 pub mod conn;
+mod keepalive;
+mod metrics;
+mod oauth;
+mod queue;
+mod ratelimit;
 pub mod read;
 pub mod util;
 pub mod write;
 
+pub use caps::Capabilities;
+pub use conn::Transport;
+pub use metrics::Metrics;
+pub use oauth::{validate_oauth, OauthError};
+pub use queue::{MessageQueue, QueueError};
+pub use ratelimit::{MessageRate, RateLimit};
+pub use read::ClientReader;
+pub use write::ClientWriter;
+
 use self::conn::TlsConfig;
 use self::conn::{OpenStreamError, TlsConfigError};
+use self::keepalive::Keepalive;
 use self::read::ReadStream;
 use self::read::RecvError;
-use self::write::WriteStream;
-use crate::irc::Command;
+use self::write::{SendError, WriteStream};
+use crate::irc::{Command, Tag};
+#[cfg(feature = "message-types")]
+use crate::msg::{EmoteSetCache, EmoteSetsChanged, FromIrc, UserState};
 use crate::IrcMessage;
 use futures_util::StreamExt;
 use rand::{thread_rng, Rng};
 use std::fmt::{Display, Write};
 use std::future::Future;
 use std::io;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::ToSocketAddrs;
 use tokio_rustls::rustls::client::InvalidDnsNameError;
 use tokio_rustls::rustls::ServerName;
 use tokio_stream::wrappers::LinesStream;
@@ -82,6 +102,19 @@ impl Credentials {
     }
   }
 
+  /// Instantiate credentials from a `login` and an oauth2 `token`.
+  ///
+  /// The `token` may be given with or without the `oauth:` prefix,
+  /// and the `login` is converted to lowercase.
+  pub fn oauth(login: impl AsRef<str>, token: impl AsRef<str>) -> Self {
+    let token = token.as_ref();
+    let token = token.strip_prefix("oauth:").unwrap_or(token);
+    Self {
+      nick: login.as_ref().to_ascii_lowercase(),
+      pass: format!("oauth:{token}"),
+    }
+  }
+
   /// An anonymous login.
   ///
   /// Twitch allows logging in using any username in the form `justinfan?????`
@@ -123,21 +156,123 @@ impl std::fmt::Debug for Credentials {
 }
 
 /// Client configuration.
-///
-/// At the moment this only holds credentials.
 #[derive(Clone, Debug, Default)]
 pub struct Config {
   /// Credentials to use when logging in to Twitch IRC.
   pub credentials: Credentials,
+
+  /// How to handle Twitch rejecting identical consecutive messages.
+  pub duplicate_strategy: DuplicateStrategy,
+
+  /// The transport used to connect to Twitch IRC.
+  pub transport: Transport,
+
+  /// The capabilities to request during the handshake.
+  ///
+  /// The handshake fails with [`ConnectError::CapabilitiesRejected`]
+  /// if Twitch does not acknowledge them.
+  pub capabilities: Capabilities,
+
+  /// Whether to verify that the `twitch.tv/tags` capability was enabled.
+  ///
+  /// If this is `true`, then:
+  /// - The handshake fails with [`ConnectError::TagsNotEnabled`]
+  ///   if Twitch does not acknowledge the capability.
+  /// - [`Client::recv`] returns [`RecvError::TagsNotEnabled`] if the first
+  ///   received `PRIVMSG` or `ROOMSTATE` does not carry any tags.
+  pub verify_tags: bool,
+
+  /// Keep the connection alive, and detect when it is dead.
+  ///
+  /// If this is set, then [`Client::recv`]:
+  /// - Responds to every `PING` with a `PONG` echoing its token.
+  /// - Sends a `PING` if no message was received for this long.
+  /// - Returns [`RecvError::KeepaliveTimeout`] if no message was received
+  ///   for this long after sending the `PING`.
+  pub keepalive: Option<Duration>,
+
+  /// Fail a read if no message arrives for this long.
+  ///
+  /// If this is set, then [`Client::recv`] returns [`RecvError::Io`] with
+  /// [`ErrorKind::TimedOut`][std::io::ErrorKind::TimedOut] when it does not
+  /// receive a message within the timeout. The timeout restarts with every call.
+  /// This detects a connection which died silently, and is treated as a disconnect.
+  pub read_timeout: Option<Duration>,
+
+  /// The timeout for opening the connection and performing the handshake.
+  ///
+  /// This applies to [`ClientBuilder::connect`] and [`Client::reconnect`].
+  /// If this is not set, then the [`DEFAULT_TIMEOUT`] is used.
+  pub connect_timeout: Option<Duration>,
+
+  /// Whether to reconnect automatically.
+  ///
+  /// If this is `true`, then [`Client::recv`] transparently reconnects using the
+  /// [`DEFAULT_BACKOFF`] when the connection is lost, or when Twitch sends a `RECONNECT`.
+  /// Previously joined channels are joined again.
+  pub auto_reconnect: bool,
+
+  /// Limit the rate at which channels are joined.
+  ///
+  /// If this is set, then [`Client::join`] and [`Client::join_all`] wait
+  /// until the channels may be joined, instead of sending them all at once.
+  /// Channels are batched into a single `JOIN` command whenever possible.
+  ///
+  /// [`RateLimit::JOIN`] is the limit for normal accounts.
+  pub join_rate: Option<RateLimit>,
+
+  /// Limit the rate at which `PRIVMSG`s are sent.
+  ///
+  /// If this is set, then sending a [`Privmsg`][write::Privmsg] waits until
+  /// the message may be sent. The limit depends on whether the account is a
+  /// moderator or the broadcaster in the channel, see [`Client::set_moderator`].
+  ///
+  /// [`MessageRate::DEFAULT`] are the limits for normal accounts.
+  pub message_rate: Option<MessageRate>,
 }
 
 impl Config {
   /// Instantiate a config from some `credentials`.
   pub fn new(credentials: Credentials) -> Self {
-    Self { credentials }
+    Self {
+      credentials,
+      duplicate_strategy: DuplicateStrategy::default(),
+      transport: Transport::default(),
+      capabilities: Capabilities::default(),
+      verify_tags: false,
+      keepalive: None,
+      read_timeout: None,
+      connect_timeout: None,
+      auto_reconnect: false,
+      join_rate: None,
+      message_rate: None,
+    }
   }
 }
 
+/// How the client handles Twitch rejecting a message because it is identical
+/// to the previous one sent to the same channel within the last 30 seconds.
+///
+/// Twitch responds to such a message with a `NOTICE` which has its `msg-id`
+/// set to `msg_duplicate`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateStrategy {
+  /// Do nothing. The `msg_duplicate` notice is returned from [`Client::recv`]
+  /// like any other message.
+  #[default]
+  Ignore,
+
+  /// Append an invisible character to every other `PRIVMSG`,
+  /// so that consecutive messages are never identical.
+  ///
+  /// See [`SameMessageBypass`][write::SameMessageBypass].
+  Bypass,
+
+  /// Detect the `msg_duplicate` notice, and return it from [`Client::recv`]
+  /// as [`RecvError::Send`] with [`SendError::Duplicate`].
+  Detect,
+}
+
 /// Builder for a [`Client`].
 pub struct ClientBuilder {
   config: Config,
@@ -159,6 +294,35 @@ pub struct Backoff {
 
   /// The maximum delay to wait inbetween connection attempts.
   pub max_delay: Duration,
+
+  /// A random duration between zero and this value is added to each delay.
+  ///
+  /// This prevents many clients from reconnecting at the exact same time.
+  pub jitter: Duration,
+}
+
+impl Backoff {
+  /// The delays to wait before each reconnect attempt, without any jitter.
+  pub fn delays(&self) -> impl Iterator<Item = Duration> {
+    let max_delay = self.max_delay;
+    let multiplier = self.delay_multiplier;
+    let delays = std::iter::successors(Some(self.initial_delay), move |delay| {
+      Some(std::cmp::min(max_delay, *delay * multiplier))
+    });
+    delays.take(
+      self
+        .max_tries
+        .map(|tries| tries as usize)
+        .unwrap_or(usize::MAX),
+    )
+  }
+
+  fn with_jitter(&self, delay: Duration) -> Duration {
+    match self.jitter.is_zero() {
+      true => delay,
+      false => delay + thread_rng().gen_range(Duration::ZERO..=self.jitter),
+    }
+  }
 }
 
 /// The default reconnect backoff.
@@ -167,6 +331,7 @@ pub const DEFAULT_BACKOFF: Backoff = Backoff {
   initial_delay: Duration::from_secs(1),
   delay_multiplier: 3,
   max_delay: Duration::from_secs(12),
+  jitter: Duration::from_millis(500),
 };
 
 impl ClientBuilder {
@@ -176,11 +341,116 @@ impl ClientBuilder {
     self
   }
 
+  /// Log in as `login` using an oauth2 `token`.
+  ///
+  /// See [`Credentials::oauth`].
+  pub fn login(self, login: impl AsRef<str>, token: impl AsRef<str>) -> Self {
+    self.credentials(Credentials::oauth(login, token))
+  }
+
+  /// Log in anonymously.
+  ///
+  /// See [`Credentials::anon`].
+  pub fn anonymous(self) -> Self {
+    self.credentials(Credentials::anon())
+  }
+
+  /// Set the [`DuplicateStrategy`].
+  pub fn duplicate_strategy(mut self, strategy: DuplicateStrategy) -> Self {
+    self.config.duplicate_strategy = strategy;
+    self
+  }
+
+  /// Set the [`Transport`] used to connect.
+  ///
+  /// See [`Config::transport`].
+  pub fn transport(mut self, transport: Transport) -> Self {
+    self.config.transport = transport;
+    self
+  }
+
+  /// Whether to connect over TLS, which is the default.
+  ///
+  /// This is a shorthand for [`ClientBuilder::transport`] with either
+  /// [`Transport::Tls`] or [`Transport::Tcp`].
+  pub fn secure(self, secure: bool) -> Self {
+    self.transport(match secure {
+      true => Transport::Tls,
+      false => Transport::Tcp,
+    })
+  }
+
+  /// Set the [`Capabilities`] to request.
+  ///
+  /// See [`Config::capabilities`].
+  pub fn capabilities(mut self, capabilities: Capabilities) -> Self {
+    self.config.capabilities = capabilities;
+    self
+  }
+
+  /// Verify that the `twitch.tv/tags` capability was enabled.
+  ///
+  /// See [`Config::verify_tags`].
+  pub fn verify_tags(mut self, verify_tags: bool) -> Self {
+    self.config.verify_tags = verify_tags;
+    self
+  }
+
+  /// Keep the connection alive by sending a `PING` after `interval` without any messages.
+  ///
+  /// See [`Config::keepalive`].
+  pub fn keepalive(mut self, interval: Duration) -> Self {
+    self.config.keepalive = Some(interval);
+    self
+  }
+
+  /// Fail a read if no message arrives within `timeout`.
+  ///
+  /// See [`Config::read_timeout`].
+  pub fn read_timeout(mut self, timeout: Duration) -> Self {
+    self.config.read_timeout = Some(timeout);
+    self
+  }
+
+  /// Use `timeout` when connecting and reconnecting.
+  ///
+  /// See [`Config::connect_timeout`].
+  pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+    self.config.connect_timeout = Some(timeout);
+    self
+  }
+
+  /// Reconnect automatically when the connection is lost.
+  ///
+  /// See [`Config::auto_reconnect`].
+  pub fn auto_reconnect(mut self, auto_reconnect: bool) -> Self {
+    self.config.auto_reconnect = auto_reconnect;
+    self
+  }
+
+  /// Limit `JOIN`s to `count` channels `per` duration.
+  ///
+  /// See [`Config::join_rate`].
+  pub fn join_rate(mut self, count: u32, per: Duration) -> Self {
+    self.config.join_rate = Some(RateLimit { count, per });
+    self
+  }
+
+  /// Limit `PRIVMSG`s to the `normal` rate, or the `moderator` rate in channels
+  /// where the account is a moderator or the broadcaster.
+  ///
+  /// See [`Config::message_rate`].
+  pub fn message_rate(mut self, normal: RateLimit, moderator: RateLimit) -> Self {
+    self.config.message_rate = Some(MessageRate { normal, moderator });
+    self
+  }
+
   /// Attempts to connect to Twitch IRC using this configuration.
   ///
-  /// This uses the [`DEFAULT_TIMEOUT`].
+  /// This uses the [`Config::connect_timeout`], or the [`DEFAULT_TIMEOUT`] if it is not set.
   pub fn connect(self) -> impl Future<Output = Result<Client, ConnectError>> {
-    Client::connect_with(self.config, DEFAULT_TIMEOUT)
+    let timeout = self.config.connect_timeout.unwrap_or(DEFAULT_TIMEOUT);
+    Client::connect_with(self.config, timeout)
   }
 
   /// Attempts to connect to Twitch IRC using this configuration.
@@ -205,14 +475,20 @@ impl ClientBuilder {
 ///
 /// It is a low-level interface, which means it does not automatically handle:
 /// - Rate limiting
-/// - Same message bypass
-/// - Reconnects / rejoining channels
+/// - Same message bypass, unless configured via [`DuplicateStrategy`]
+/// - Reconnects / rejoining channels, unless configured via [`Config::auto_reconnect`]
 /// - Latency measurement
 pub struct Client {
-  reader: ReadStream,
-  writer: WriteStream,
+  reader: ClientReader,
+  writer: ClientWriter,
+  keepalive: Option<Keepalive>,
+  user_id: Option<String>,
+  display_name: Option<String>,
+  #[cfg(feature = "message-types")]
+  emote_sets: EmoteSetCache,
+  #[cfg(feature = "message-types")]
+  emote_sets_changed: Option<EmoteSetsChanged>,
 
-  scratch: String,
   tls: TlsConfig,
   config: Config,
 }
@@ -235,16 +511,30 @@ impl Client {
   }
 
   /// Attempts to connect with the provided `config` and `timeout`.
-  pub async fn connect_with(config: Config, timeout: Duration) -> Result<Client, ConnectError> {
+  ///
+  /// Unless the login is [anonymous][`Credentials::anon`], the token is checked
+  /// using [`validate_oauth`] before connecting.
+  #[cfg_attr(feature = "tracing", tracing::instrument(name = "connect", skip_all))]
+  pub async fn connect_with(mut config: Config, timeout: Duration) -> Result<Client, ConnectError> {
     trace!("connecting");
+    if !config.credentials.is_anon() {
+      config.credentials.pass = validate_oauth(&config.credentials.pass)?;
+    }
     let tls = TlsConfig::load(ServerName::try_from(conn::HOST)?)?;
     trace!("opening connection to twitch");
-    let stream = conn::open(tls.clone()).timeout(timeout).await??;
-    let (reader, writer) = split(stream);
+    let (reader, writer) = open(&tls, config.transport, config.transport.addr())
+      .timeout(timeout)
+      .await??;
     let mut chat = Client {
-      reader,
-      writer,
-      scratch: String::with_capacity(1024),
+      reader: ClientReader::new(reader, &config),
+      writer: ClientWriter::new(writer, &config),
+      keepalive: config.keepalive.map(Keepalive::new),
+      user_id: None,
+      display_name: None,
+      #[cfg(feature = "message-types")]
+      emote_sets: EmoteSetCache::new(),
+      #[cfg(feature = "message-types")]
+      emote_sets_changed: None,
       tls,
       config,
     };
@@ -254,14 +544,20 @@ impl Client {
 
   /// Attempt to reconnect to Twitch IRC.
   ///
-  /// This uses the [`DEFAULT_BACKOFF`] and [`DEFAULT_TIMEOUT`].
+  /// This uses the [`DEFAULT_BACKOFF`], and the [`Config::connect_timeout`]
+  /// or the [`DEFAULT_TIMEOUT`] if it is not set.
   pub fn reconnect(&mut self) -> impl Future<Output = Result<(), ReconnectError>> + '_ {
-    self.reconnect_with(DEFAULT_BACKOFF, DEFAULT_TIMEOUT)
+    let timeout = self.config.connect_timeout.unwrap_or(DEFAULT_TIMEOUT);
+    self.reconnect_with(DEFAULT_BACKOFF, timeout)
   }
 
   /// Attempt to reconnect to Twitch IRC.
   ///
   /// This uses the provided `backoff` and `timeout`.
+  ///
+  /// After reconnecting, all channels joined using [`Client::join`]
+  /// or [`Client::join_all`] are joined again.
+  #[cfg_attr(feature = "tracing", tracing::instrument(name = "reconnect", skip_all))]
   pub async fn reconnect_with(
     &mut self,
     backoff: Backoff,
@@ -269,27 +565,21 @@ impl Client {
   ) -> Result<(), ReconnectError> {
     trace!("reconnecting");
 
-    let mut tries = backoff.max_tries;
-    let mut delay = backoff.initial_delay;
     let mut cause = ConnectError::Timeout;
-    while matches!(tries, None | Some(1..)) {
-      tokio::time::sleep(delay).await;
-      if let Some(tries) = &mut tries {
-        *tries -= 1;
-      }
-      delay = std::cmp::min(backoff.max_delay, delay * backoff.delay_multiplier);
+    for delay in backoff.delays() {
+      tokio::time::sleep(backoff.with_jitter(delay)).await;
 
       trace!("opening connection to twitch");
-      let stream = match conn::open(self.tls.clone()).timeout(timeout).await? {
-        Ok(stream) => stream,
+      let transport = self.config.transport;
+      let streams = open(&self.tls, transport, transport.addr()).timeout(timeout);
+      (self.reader.stream, self.writer.stream) = match streams.await? {
+        Ok(streams) => streams,
         Err(e @ OpenStreamError::Io(_)) => {
           cause = e.into();
           continue;
         }
       };
 
-      (self.reader, self.writer) = split(stream);
-
       if let Err(e) = self.handshake().timeout(timeout).await? {
         if e.should_retry() {
           cause = e;
@@ -299,9 +589,17 @@ impl Client {
         }
       };
 
+      if let Err(e) = self.writer.rejoin().await {
+        cause = e.into();
+        continue;
+      }
+
+      self.reader.metrics.record_reconnect();
+      debug!("reconnected");
       return Ok(());
     }
 
+    warn!(%cause, "failed to reconnect");
     Err(ReconnectError { cause })
   }
 
@@ -309,53 +607,69 @@ impl Client {
     trace!("performing handshake");
 
     let credentials = &self.config.credentials;
-    const CAP: &str = "twitch.tv/commands twitch.tv/tags twitch.tv/membership";
-    trace!("CAP REQ {CAP:?}; NICK {:?}; PASS ***", credentials.nick);
-    write!(&mut self.scratch, "CAP REQ :{CAP}\r\n").unwrap();
-    write!(&mut self.scratch, "PASS {}\r\n", credentials.pass).unwrap();
-    write!(&mut self.scratch, "NICK {}\r\n", credentials.nick).unwrap();
-    self.writer.write_all(self.scratch.as_bytes()).await?;
-    self.writer.flush().await?;
-    self.scratch.clear();
-
-    trace!("waiting for CAP * ACK");
-    let message = self.recv().timeout(Duration::from_secs(5)).await??;
-    trace!(?message, "received message");
-
-    match message.command() {
-      Command::Capability => {
-        if message.params().is_some_and(|v| v.starts_with("* ACK")) {
-          trace!("received CAP * ACK")
-        } else {
+    let capabilities = self.config.capabilities;
+    trace!(
+      "CAP REQ {:?}; NICK {:?}; PASS ***",
+      capabilities.to_string(),
+      credentials.nick
+    );
+    let writer = &mut self.writer;
+    write_login(&mut writer.scratch, credentials, capabilities);
+    self.reader.tags_verified = false;
+    if let Some(keepalive) = &mut self.keepalive {
+      keepalive.reset();
+    }
+    writer.stream.write_all(writer.scratch.as_bytes()).await?;
+    writer.stream.flush().await?;
+    writer.scratch.clear();
+
+    if !capabilities.is_empty() {
+      trace!("waiting for CAP * ACK");
+      let message = self.reader.recv().timeout(Duration::from_secs(5)).await??;
+      trace!(?message, "received message");
+
+      // `CAP * ACK :twitch.tv/commands twitch.tv/tags`
+      let subcommand = match message.command() {
+        Command::Capability => message.param(1),
+        _ => None,
+      };
+      let capabilities = || message.text().unwrap_or_default().split_whitespace();
+      match subcommand {
+        Some("ACK") => {
+          debug!(capabilities = message.text(), "received CAP * ACK");
+          let acked: Capabilities = capabilities().collect();
+          if self.config.verify_tags && !acked.contains(Capabilities::TAGS) {
+            trace!("tags capability was not acknowledged");
+            return Err(ConnectError::TagsNotEnabled);
+          }
+        }
+        Some("NAK") => {
+          let rejected: Capabilities = capabilities().collect();
+          trace!(%rejected, "received CAP * NAK");
+          return Err(ConnectError::CapabilitiesRejected(rejected));
+        }
+        _ if message.command() == Command::Capability => {
           return Err(ConnectError::Auth);
         }
-      }
-      _ => {
-        trace!("unexpected message");
-        return Err(ConnectError::Welcome(message));
+        _ => {
+          trace!("unexpected message");
+          return Err(ConnectError::Welcome(message));
+        }
       }
     }
 
     trace!("waiting for NOTICE 001");
-    let message = self.recv().timeout(Duration::from_secs(5)).await??;
+    let message = self.reader.recv().timeout(Duration::from_secs(5)).await??;
     trace!(?message, "received message");
 
     match message.command() {
       Command::RplWelcome => {
-        trace!("connected");
+        debug!("connected");
       }
+      // invalid credentials are already reported by `recv` as `RecvError::AuthFailed`
       Command::Notice => {
-        if message
-          .params()
-          .map(|v| v.contains("authentication failed"))
-          .unwrap_or(false)
-        {
-          trace!("invalid credentials");
-          return Err(ConnectError::Auth);
-        } else {
-          trace!("unrecognized error");
-          return Err(ConnectError::Notice(message));
-        }
+        trace!("unrecognized error");
+        return Err(ConnectError::Notice(message));
       }
       _ => {
         trace!("first message not recognized");
@@ -367,6 +681,15 @@ impl Client {
   }
 }
 
+/// Write the lines sent to log in to Twitch IRC into `f`.
+fn write_login(f: &mut String, credentials: &Credentials, capabilities: Capabilities) {
+  if !capabilities.is_empty() {
+    let _ = write!(f, "CAP REQ :{capabilities}\r\n");
+  }
+  let _ = write!(f, "PASS {}\r\n", credentials.pass);
+  let _ = write!(f, "NICK {}\r\n", credentials.nick);
+}
+
 impl Client {
   #[inline]
   pub fn config(&self) -> &Config {
@@ -377,14 +700,117 @@ impl Client {
   pub fn credentials(&self) -> &Credentials {
     &self.config.credentials
   }
+
+  /// ID of the logged in user.
+  ///
+  /// Twitch sends it in the `GLOBALUSERSTATE` which follows a successful login,
+  /// so this is [`None`] until that is received by [`Client::recv`].
+  /// It is always [`None`] for [anonymous][`Credentials::anon`] logins.
+  #[inline]
+  pub fn user_id(&self) -> Option<&str> {
+    self.user_id.as_deref()
+  }
+
+  /// Display name of the logged in user.
+  ///
+  /// See [`Client::user_id`].
+  #[inline]
+  pub fn display_name(&self) -> Option<&str> {
+    self.display_name.as_deref()
+  }
+
+  /// The emote sets of the logged in user in each channel,
+  /// from the latest `USERSTATE` received by [`Client::recv`].
+  #[cfg(feature = "message-types")]
+  #[inline]
+  pub fn emote_sets(&self) -> &EmoteSetCache {
+    &self.emote_sets
+  }
+
+  /// How the message last received by [`Client::recv`] changed the [`Client::emote_sets`].
+  ///
+  /// This is [`None`] unless that message is a `USERSTATE` whose emote sets differ
+  /// from the previous `USERSTATE` in the same channel.
+  #[cfg(feature = "message-types")]
+  #[inline]
+  pub fn emote_sets_changed(&self) -> Option<&EmoteSetsChanged> {
+    self.emote_sets_changed.as_ref()
+  }
+
+  /// Counters for the messages read by this client.
+  ///
+  /// The counters keep counting across reconnects, and after [`Client::split`].
+  #[inline]
+  pub fn metrics(&self) -> Arc<Metrics> {
+    self.reader.metrics()
+  }
+
+  /// Update the client state from a received `message`.
+  fn observe(&mut self, message: &IrcMessage) {
+    if message.command() == Command::GlobalUserState {
+      if let Some(user_id) = message.tag(Tag::UserId) {
+        self.user_id = Some(user_id.to_owned());
+      }
+      if let Some(display_name) = message.tag(Tag::DisplayName) {
+        self.display_name = Some(crate::common::maybe_unescape(display_name).into_owned());
+      }
+    }
+    #[cfg(feature = "message-types")]
+    {
+      self.emote_sets_changed = match UserState::from_irc(message.as_ref()) {
+        Ok(state) => self.emote_sets.update(&state),
+        Err(_) => None,
+      };
+    }
+    self.writer.observe(message);
+  }
+
+  /// Split the client into independent reader and writer halves.
+  ///
+  /// This allows receiving messages in one task, and sending them in another.
+  ///
+  /// The halves can no longer be used to reconnect,
+  /// and do not perform the [`Config::keepalive`].
+  ///
+  /// ```rust,no_run
+  /// # async fn _test() -> anyhow::Result<()> {
+  /// let client = tmi::Client::connect().await?;
+  /// let (mut reader, mut writer) = client.split();
+  /// tokio::spawn(async move {
+  ///   while let Ok(msg) = reader.recv().await {
+  ///     println!("{}", msg.raw());
+  ///   }
+  /// });
+  /// writer.join(tmi::ChannelRef::parse("#forsen")?).await?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn split(self) -> (ClientReader, ClientWriter) {
+    (self.reader, self.writer)
+  }
+}
+
+async fn open(
+  tls: &TlsConfig,
+  transport: Transport,
+  addr: impl ToSocketAddrs,
+) -> Result<(ReadStream, WriteStream), OpenStreamError> {
+  Ok(match transport {
+    Transport::Tls => split(conn::open_to(tls.clone(), addr).await?),
+    Transport::Tcp => split(conn::open_plain(addr).await?),
+  })
 }
 
-fn split(stream: conn::Stream) -> (ReadStream, WriteStream) {
+fn split<S>(stream: S) -> (ReadStream, WriteStream)
+where
+  S: AsyncRead + AsyncWrite + Send + Sync + 'static,
+{
   let (reader, writer) = tokio::io::split(stream);
+  let reader: Box<dyn AsyncRead + Send + Sync + Unpin> = Box::new(reader);
 
   (
     LinesStream::new(BufReader::new(reader).lines()).fuse(),
-    writer,
+    Box::new(writer),
   )
 }
 
@@ -435,6 +861,11 @@ pub enum ConnectError {
   /// Failed to query DNS.
   Dns(InvalidDnsNameError),
 
+  /// The oauth2 token is not plausible.
+  ///
+  /// See [`validate_oauth`].
+  InvalidToken(OauthError),
+
   /// Failed to establish TLS connection.
   Tls(TlsConfigError),
 
@@ -452,6 +883,17 @@ pub enum ConnectError {
 
   /// Twitch sent a notice that we didn't expect during the handshake.
   Notice(IrcMessage),
+
+  /// Twitch did not acknowledge the `twitch.tv/tags` capability.
+  ///
+  /// This is only checked if [`Config::verify_tags`] is enabled.
+  TagsNotEnabled,
+
+  /// Twitch rejected some of the requested [`Config::capabilities`].
+  CapabilitiesRejected(Capabilities),
+
+  /// Failed to write to the stream.
+  Send(SendError),
 }
 
 impl ConnectError {
@@ -460,9 +902,18 @@ impl ConnectError {
   }
 }
 
+impl From<SendError> for ConnectError {
+  fn from(value: SendError) -> Self {
+    Self::Send(value)
+  }
+}
+
 impl From<RecvError> for ConnectError {
   fn from(value: RecvError) -> Self {
-    Self::Read(value)
+    match value {
+      RecvError::AuthFailed => Self::Auth,
+      value => Self::Read(value),
+    }
   }
 }
 
@@ -472,6 +923,12 @@ impl From<io::Error> for ConnectError {
   }
 }
 
+impl From<OauthError> for ConnectError {
+  fn from(value: OauthError) -> Self {
+    Self::InvalidToken(value)
+  }
+}
+
 impl From<InvalidDnsNameError> for ConnectError {
   fn from(value: InvalidDnsNameError) -> Self {
     Self::Dns(value)
@@ -502,6 +959,7 @@ impl Display for ConnectError {
       ConnectError::Read(e) => write!(f, "failed to connect: {e}"),
       ConnectError::Io(e) => write!(f, "failed to connect: {e}"),
       ConnectError::Dns(e) => write!(f, "failed to connect: {e}"),
+      ConnectError::InvalidToken(e) => write!(f, "failed to connect: {e}"),
       ConnectError::Tls(e) => write!(f, "failed to connect: {e}"),
       ConnectError::Open(e) => write!(f, "failed to connect: {e}"),
       ConnectError::Timeout => write!(f, "failed to connect: connection timed out"),
@@ -514,6 +972,15 @@ impl Display for ConnectError {
         f,
         "failed to connect: received unrecognized notice: {msg:?}"
       ),
+      ConnectError::TagsNotEnabled => write!(
+        f,
+        "failed to connect: the `twitch.tv/tags` capability was not acknowledged"
+      ),
+      ConnectError::CapabilitiesRejected(caps) => write!(
+        f,
+        "failed to connect: the requested capabilities were rejected: {caps}"
+      ),
+      ConnectError::Send(e) => write!(f, "failed to connect: {e}"),
     }
   }
 }
@@ -522,3 +989,432 @@ impl std::error::Error for ConnectError {}
 
 static_assert_send!(Client);
 static_assert_sync!(Client);
+static_assert_send!(ClientReader);
+static_assert_sync!(ClientReader);
+static_assert_send!(ClientWriter);
+static_assert_sync!(ClientWriter);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tokio::io::AsyncReadExt;
+
+  /// A client which talks to the other end of `stream` instead of Twitch.
+  pub(crate) fn mock<S>(stream: S, config: Config) -> Client
+  where
+    S: AsyncRead + AsyncWrite + Send + Sync + 'static,
+  {
+    mock_streams(split(stream), config)
+  }
+
+  fn mock_streams((reader, writer): (ReadStream, WriteStream), config: Config) -> Client {
+    Client {
+      reader: ClientReader::new(reader, &config),
+      writer: ClientWriter::new(writer, &config),
+      keepalive: config.keepalive.map(Keepalive::new),
+      user_id: None,
+      display_name: None,
+      #[cfg(feature = "message-types")]
+      emote_sets: EmoteSetCache::new(),
+      #[cfg(feature = "message-types")]
+      emote_sets_changed: None,
+      tls: TlsConfig::empty(),
+      config,
+    }
+  }
+
+  async fn handshake_with(caps: Capabilities, response: &[u8]) -> Result<String, ConnectError> {
+    let config = Config {
+      capabilities: caps,
+      ..Config::new(Credentials::new("forsen", "oauth:abc"))
+    };
+    handshake_with_config(config, response).await
+  }
+
+  async fn handshake_with_config(config: Config, response: &[u8]) -> Result<String, ConnectError> {
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let mut client = mock(stream, config);
+    server.write_all(response).await.unwrap();
+    client.handshake().await?;
+    let mut buf = [0u8; 256];
+    let n = server.read(&mut buf).await.unwrap();
+    Ok(String::from_utf8_lossy(&buf[..n]).into_owned())
+  }
+
+  #[tokio::test]
+  async fn request_capabilities() {
+    let sent = handshake_with(
+      Capabilities::TAGS | Capabilities::COMMANDS,
+      b":tmi.twitch.tv CAP * ACK :twitch.tv/commands twitch.tv/tags\r\n\
+        :tmi.twitch.tv 001 forsen :Welcome, GLHF!\r\n",
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+      sent,
+      "CAP REQ :twitch.tv/commands twitch.tv/tags\r\nPASS oauth:abc\r\nNICK forsen\r\n"
+    );
+
+    let sent = handshake_with(
+      Capabilities::NONE,
+      b":tmi.twitch.tv 001 forsen :Welcome, GLHF!\r\n",
+    )
+    .await
+    .unwrap();
+    assert_eq!(sent, "PASS oauth:abc\r\nNICK forsen\r\n");
+  }
+
+  #[tokio::test]
+  async fn invalid_credentials() {
+    let result = handshake_with(
+      Capabilities::ALL,
+      b":tmi.twitch.tv CAP * ACK :twitch.tv/commands twitch.tv/tags twitch.tv/membership\r\n\
+        :tmi.twitch.tv NOTICE * :Login authentication failed\r\n",
+    )
+    .await;
+    assert!(matches!(result, Err(ConnectError::Auth)));
+  }
+
+  #[tokio::test]
+  async fn rejected_capabilities() {
+    let result = handshake_with(
+      Capabilities::MEMBERSHIP,
+      b":tmi.twitch.tv CAP * NAK :twitch.tv/membership\r\n",
+    )
+    .await;
+    match result {
+      Err(ConnectError::CapabilitiesRejected(caps)) => {
+        assert_eq!(caps, Capabilities::MEMBERSHIP)
+      }
+      other => panic!("unexpected result: {other:?}"),
+    }
+  }
+
+  fn verify_tags_config() -> Config {
+    Config {
+      verify_tags: true,
+      ..Config::new(Credentials::new("forsen", "oauth:abc"))
+    }
+  }
+
+  #[tokio::test]
+  async fn verify_tags_nak() {
+    let result = handshake_with_config(
+      verify_tags_config(),
+      b":tmi.twitch.tv CAP * NAK :twitch.tv/commands twitch.tv/tags twitch.tv/membership\r\n",
+    )
+    .await;
+    match result {
+      Err(ConnectError::CapabilitiesRejected(caps)) => assert_eq!(caps, Capabilities::ALL),
+      other => panic!("unexpected result: {other:?}"),
+    }
+  }
+
+  #[tokio::test]
+  async fn verify_tags_ack_without_tags() {
+    let result = handshake_with_config(
+      verify_tags_config(),
+      b":tmi.twitch.tv CAP * ACK :twitch.tv/commands twitch.tv/membership\r\n",
+    )
+    .await;
+    assert!(matches!(result, Err(ConnectError::TagsNotEnabled)));
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn verify_tags_no_ack() {
+    let result = handshake_with_config(verify_tags_config(), b"").await;
+    assert!(matches!(result, Err(ConnectError::Timeout)));
+  }
+
+  #[tokio::test]
+  async fn capture_identity_from_globaluserstate() {
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let mut client = mock(stream, Config::default());
+    assert_eq!(client.user_id(), None);
+
+    server
+      .write_all(b"@badge-info=;badges=;color=;display-name=randers811;emote-sets=0;user-id=553170741;user-type= :tmi.twitch.tv GLOBALUSERSTATE\r\n")
+      .await
+      .unwrap();
+    let message = client.recv().await.unwrap();
+    assert_eq!(message.command(), Command::GlobalUserState);
+    assert_eq!(client.user_id(), Some("553170741"));
+    assert_eq!(client.display_name(), Some("randers811"));
+  }
+
+  #[cfg(feature = "message-types")]
+  #[tokio::test]
+  async fn emote_sets_changed_from_userstate() {
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let mut client = mock(stream, Config::default());
+
+    server
+      .write_all(b"@badge-info=;badges=;color=;display-name=randers811;emote-sets=0,300;mod=0;subscriber=0;user-type= :tmi.twitch.tv USERSTATE #forsen\r\n")
+      .await
+      .unwrap();
+    client.recv().await.unwrap();
+    assert_eq!(client.emote_sets_changed(), None);
+
+    server
+      .write_all(b"@badge-info=;badges=;color=;display-name=randers811;emote-sets=0,793;mod=0;subscriber=0;user-type= :tmi.twitch.tv USERSTATE #forsen\r\n")
+      .await
+      .unwrap();
+    client.recv().await.unwrap();
+    let changed = client.emote_sets_changed().unwrap();
+    assert_eq!(changed.channel().as_str(), "#forsen");
+    assert_eq!(changed.added(), ["793"]);
+    assert_eq!(changed.removed(), ["300"]);
+    let channel = crate::ChannelRef::parse("#forsen").unwrap();
+    assert_eq!(
+      client
+        .emote_sets()
+        .get(channel)
+        .unwrap()
+        .collect::<Vec<_>>(),
+      ["0", "793"]
+    );
+
+    server
+      .write_all(b":tmi.twitch.tv PING :tmi.twitch.tv\r\n")
+      .await
+      .unwrap();
+    client.recv().await.unwrap();
+    assert_eq!(client.emote_sets_changed(), None);
+  }
+
+  #[tokio::test]
+  async fn plain_tcp_stream() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+      let (mut server, _) = listener.accept().await.unwrap();
+      let mut buf = [0u8; 64];
+      let n = server.read(&mut buf).await.unwrap();
+      server
+        .write_all(b":tmi.twitch.tv 001 forsen :Welcome, GLHF!\r\n")
+        .await
+        .unwrap();
+      String::from_utf8_lossy(&buf[..n]).into_owned()
+    });
+
+    let config = Config {
+      capabilities: Capabilities::NONE,
+      ..Client::builder()
+        .login("forsen", "oauth:abc")
+        .transport(Transport::Tcp)
+        .config
+    };
+    let streams = open(&TlsConfig::empty(), config.transport, addr)
+      .await
+      .unwrap();
+    let mut client = mock_streams(streams, config);
+    client.handshake().await.unwrap();
+
+    assert_eq!(server.await.unwrap(), "PASS oauth:abc\r\nNICK forsen\r\n");
+  }
+
+  #[tokio::test]
+  async fn tls_stream() {
+    let cert = include_bytes!("client/testdata/cert.der");
+    let key = include_bytes!("client/testdata/key.der");
+    let server_config = tokio_rustls::rustls::ServerConfig::builder()
+      .with_safe_defaults()
+      .with_no_client_auth()
+      .with_single_cert(
+        vec![tokio_rustls::rustls::Certificate(cert.to_vec())],
+        tokio_rustls::rustls::PrivateKey(key.to_vec()),
+      )
+      .unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server_config));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+      let (stream, _) = listener.accept().await.unwrap();
+      let mut server = acceptor.accept(stream).await.unwrap();
+      let mut buf = [0u8; 64];
+      let n = server.read(&mut buf).await.unwrap();
+      server
+        .write_all(b":tmi.twitch.tv 001 forsen :Welcome, GLHF!\r\n")
+        .await
+        .unwrap();
+      String::from_utf8_lossy(&buf[..n]).into_owned()
+    });
+
+    let config = Config {
+      capabilities: Capabilities::NONE,
+      ..Client::builder()
+        .login("forsen", "oauth:abc")
+        .secure(true)
+        .config
+    };
+    assert_eq!(config.transport, Transport::Tls);
+    let streams = open(&TlsConfig::trusting(cert), config.transport, addr)
+      .await
+      .unwrap();
+    let mut client = mock_streams(streams, config);
+    client.handshake().await.unwrap();
+
+    assert_eq!(server.await.unwrap(), "PASS oauth:abc\r\nNICK forsen\r\n");
+  }
+
+  #[test]
+  fn secure_selects_transport() {
+    let builder = Client::builder().secure(false);
+    assert_eq!(builder.config.transport, Transport::Tcp);
+    let builder = builder.secure(true);
+    assert_eq!(builder.config.transport, Transport::Tls);
+  }
+
+  #[test]
+  fn login_sequence() {
+    let expected = "CAP REQ :twitch.tv/commands twitch.tv/tags twitch.tv/membership\r\n\
+      PASS oauth:abcdef\r\n\
+      NICK forsen\r\n";
+    for token in ["abcdef", "oauth:abcdef"] {
+      let builder = Client::builder().login("Forsen", token);
+      let mut f = String::new();
+      write_login(
+        &mut f,
+        &builder.config.credentials,
+        builder.config.capabilities,
+      );
+      assert_eq!(f, expected);
+    }
+
+    let builder = Client::builder().anonymous();
+    assert!(builder.config.credentials.is_anon());
+    let mut f = String::new();
+    write_login(
+      &mut f,
+      &builder.config.credentials,
+      builder.config.capabilities,
+    );
+    let nick = &builder.config.credentials.nick;
+    assert_eq!(
+      f,
+      format!(
+        "CAP REQ :twitch.tv/commands twitch.tv/tags twitch.tv/membership\r\n\
+        PASS just_a_lil_guy\r\n\
+        NICK {nick}\r\n"
+      )
+    );
+  }
+
+  #[tokio::test]
+  async fn split_halves_in_separate_tasks() {
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let config = Config::default();
+    let (reader, writer) = split(stream);
+    let mut reader = ClientReader::new(reader, &config);
+    let mut writer = ClientWriter::new(writer, &config);
+
+    let read_task = tokio::spawn(async move { reader.recv().await.unwrap() });
+    let write_task = tokio::spawn(async move {
+      let channel = crate::ChannelRef::parse("#forsen").unwrap();
+      writer.privmsg(channel, "yo").send().await.unwrap()
+    });
+
+    server
+      .write_all(b":tmi.twitch.tv PING :tmi.twitch.tv\r\n")
+      .await
+      .unwrap();
+    let message = read_task.await.unwrap();
+    assert_eq!(message.command(), Command::Ping);
+
+    write_task.await.unwrap();
+    let mut buf = [0u8; 128];
+    let n = server.read(&mut buf).await.unwrap();
+    assert_eq!(&buf[..n], b"PRIVMSG #forsen :yo\r\n");
+  }
+
+  #[test]
+  fn backoff_schedule() {
+    let secs = |v: &[u64]| {
+      v.iter()
+        .map(|&v| Duration::from_secs(v))
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(
+      DEFAULT_BACKOFF.delays().collect::<Vec<_>>(),
+      secs(&[1, 3, 9, 12, 12, 12, 12, 12])
+    );
+
+    let backoff = Backoff {
+      max_tries: None,
+      initial_delay: Duration::from_secs(1),
+      delay_multiplier: 2,
+      max_delay: Duration::from_secs(60),
+      jitter: Duration::from_secs(1),
+    };
+    assert_eq!(
+      backoff.delays().take(8).collect::<Vec<_>>(),
+      secs(&[1, 2, 4, 8, 16, 32, 60, 60])
+    );
+    for _ in 0..100 {
+      let delay = backoff.with_jitter(Duration::from_secs(4));
+      assert!(delay >= Duration::from_secs(4) && delay <= Duration::from_secs(5));
+    }
+  }
+
+  #[tokio::test]
+  async fn rejoin_joined_channels() {
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (_reader, writer) = split(stream);
+    let mut writer = ClientWriter::new(writer, &Config::default());
+
+    writer.rejoin().await.unwrap();
+    writer
+      .join(crate::ChannelRef::parse("#pajlada").unwrap())
+      .await
+      .unwrap();
+    writer
+      .join_all(["#forsen", "#xqcow"].map(|c| crate::ChannelRef::parse(c).unwrap()))
+      .await
+      .unwrap();
+    assert_eq!(writer.joined_channels().len(), 3);
+
+    writer.rejoin().await.unwrap();
+    assert_eq!(writer.joined_channels().len(), 3);
+
+    let mut buf = [0u8; 128];
+    let n = server.read(&mut buf).await.unwrap();
+    assert_eq!(
+      &buf[..n],
+      b"JOIN #pajlada\r\nJOIN #forsen,#xqcow\r\nJOIN #forsen,#pajlada,#xqcow\r\n"
+    );
+  }
+
+  #[tokio::test]
+  async fn recv_dedup_skips_seen_messages() {
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let config = Config {
+      keepalive: Some(Duration::from_secs(60)),
+      ..Config::default()
+    };
+    let mut client = mock(stream, config);
+    let mut filter = crate::DedupFilter::default();
+
+    server
+      .write_all(
+        b"@id=a :a!a@a.tmi.twitch.tv PRIVMSG #forsen :yo\r\n\
+          @id=a :a!a@a.tmi.twitch.tv PRIVMSG #forsen :yo\r\n\
+          PING :tmi.twitch.tv\r\n\
+          @id=b :a!a@a.tmi.twitch.tv PRIVMSG #forsen :yo\r\n",
+      )
+      .await
+      .unwrap();
+
+    let message = client.recv_dedup(&mut filter).await.unwrap();
+    assert_eq!(message.tag(crate::Tag::Id), Some("a"));
+    let message = client.recv_dedup(&mut filter).await.unwrap();
+    assert_eq!(message.command(), Command::Ping);
+    let message = client.recv_dedup(&mut filter).await.unwrap();
+    assert_eq!(message.tag(crate::Tag::Id), Some("b"));
+
+    // the keepalive still answers pings
+    let mut buf = [0u8; 64];
+    let n = server.read(&mut buf).await.unwrap();
+    assert_eq!(&buf[..n], b"PONG :tmi.twitch.tv\r\n");
+  }
+}
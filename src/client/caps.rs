@@ -0,0 +1,130 @@
+use std::fmt::Display;
+use std::ops::{BitOr, BitOrAssign};
+
+/// A set of Twitch IRC capabilities.
+///
+/// Capabilities are combined using `|`:
+///
+/// ```rust
+/// use tmi::client::Capabilities;
+///
+/// let caps = Capabilities::TAGS | Capabilities::COMMANDS;
+/// assert!(caps.contains(Capabilities::TAGS));
+/// assert!(!caps.contains(Capabilities::MEMBERSHIP));
+/// ```
+///
+/// The [`Default`] impl is [`Capabilities::ALL`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Capabilities(u8);
+
+impl Capabilities {
+  /// No capabilities.
+  pub const NONE: Capabilities = Capabilities(0);
+
+  /// `twitch.tv/tags`, which adds tags to messages.
+  pub const TAGS: Capabilities = Capabilities(1 << 0);
+
+  /// `twitch.tv/commands`, which enables Twitch-specific commands
+  /// such as `USERNOTICE`, `CLEARCHAT`, and `ROOMSTATE`.
+  pub const COMMANDS: Capabilities = Capabilities(1 << 1);
+
+  /// `twitch.tv/membership`, which enables `JOIN` and `PART` messages for other users.
+  pub const MEMBERSHIP: Capabilities = Capabilities(1 << 2);
+
+  /// All capabilities.
+  pub const ALL: Capabilities = Capabilities(Self::TAGS.0 | Self::COMMANDS.0 | Self::MEMBERSHIP.0);
+
+  const NAMES: [(Capabilities, &'static str); 3] = [
+    (Self::COMMANDS, "twitch.tv/commands"),
+    (Self::TAGS, "twitch.tv/tags"),
+    (Self::MEMBERSHIP, "twitch.tv/membership"),
+  ];
+
+  /// Returns `true` if all capabilities in `other` are also in `self`.
+  pub fn contains(&self, other: Capabilities) -> bool {
+    self.0 & other.0 == other.0
+  }
+
+  /// Returns `true` if there are no capabilities in `self`.
+  pub fn is_empty(&self) -> bool {
+    self.0 == 0
+  }
+
+  /// Parse a space-separated list of capability names.
+  ///
+  /// Unknown capabilities are ignored.
+  pub fn parse(names: &str) -> Capabilities {
+    names.split_whitespace().collect()
+  }
+
+  /// Iterator over the names of the capabilities in `self`.
+  pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+    Self::NAMES
+      .iter()
+      .filter(|(cap, _)| self.contains(*cap))
+      .map(|(_, name)| *name)
+  }
+}
+
+impl Default for Capabilities {
+  fn default() -> Self {
+    Self::ALL
+  }
+}
+
+impl BitOr for Capabilities {
+  type Output = Capabilities;
+
+  fn bitor(self, rhs: Self) -> Self::Output {
+    Capabilities(self.0 | rhs.0)
+  }
+}
+
+impl BitOrAssign for Capabilities {
+  fn bitor_assign(&mut self, rhs: Self) {
+    self.0 |= rhs.0;
+  }
+}
+
+/// Collects capability names, ignoring unknown capabilities.
+impl<'a> FromIterator<&'a str> for Capabilities {
+  fn from_iter<I: IntoIterator<Item = &'a str>>(names: I) -> Self {
+    names
+      .into_iter()
+      .filter_map(|name| Self::NAMES.iter().find(|(_, v)| *v == name))
+      .fold(Self::NONE, |caps, (cap, _)| caps | *cap)
+  }
+}
+
+/// Formats the capabilities as a space-separated list of names,
+/// as used in a `CAP REQ`.
+impl Display for Capabilities {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for (i, name) in self.names().enumerate() {
+      if i > 0 {
+        f.write_str(" ")?;
+      }
+      f.write_str(name)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn format_and_parse() {
+    assert_eq!(
+      Capabilities::ALL.to_string(),
+      "twitch.tv/commands twitch.tv/tags twitch.tv/membership"
+    );
+    assert_eq!(Capabilities::TAGS.to_string(), "twitch.tv/tags");
+    assert_eq!(Capabilities::NONE.to_string(), "");
+    assert_eq!(
+      Capabilities::parse("twitch.tv/membership twitch.tv/unknown twitch.tv/tags"),
+      Capabilities::TAGS | Capabilities::MEMBERSHIP
+    );
+  }
+}
@@ -1,28 +1,74 @@
 use std::fmt::Display;
 use std::io;
 use std::sync::Arc;
-use tokio::net::TcpStream;
+use tokio::net::{TcpStream, ToSocketAddrs};
 use tokio_rustls::client::TlsStream;
 use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerName};
 use tokio_rustls::{rustls, TlsConnector};
 
+/// The Twitch IRC host.
 pub const HOST: &str = "irc.chat.twitch.tv";
+
+/// The port on which Twitch IRC is served over TLS.
 pub const PORT: u16 = 6697;
 
+/// A TLS stream over TCP.
+///
+/// The client always connects securely. Twitch also serves IRC over
+/// WebSockets (`wss://irc-ws.chat.twitch.tv:443`), but the framing of
+/// messages is the same, so there is no benefit to using it here.
 pub type Stream = TlsStream<TcpStream>;
 
+/// The port on which Twitch IRC is served over plain TCP.
+pub const PLAIN_PORT: u16 = 6667;
+
+/// The transport used to connect to Twitch IRC.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Transport {
+  /// TLS over TCP, to [`HOST`]:[`PORT`].
+  #[default]
+  Tls,
+
+  /// Plain TCP, to [`HOST`]:[`PLAIN_PORT`].
+  ///
+  /// ⚠ The credentials are sent unencrypted.
+  Tcp,
+}
+
+impl Transport {
+  /// The address of Twitch IRC for this transport.
+  pub(crate) fn addr(self) -> (&'static str, u16) {
+    match self {
+      Transport::Tls => (HOST, PORT),
+      Transport::Tcp => (HOST, PLAIN_PORT),
+    }
+  }
+}
+
+/// Open a TLS stream to [`HOST`]:[`PORT`].
 pub async fn open(config: TlsConfig) -> Result<Stream, OpenStreamError> {
+  open_to(config, (HOST, PORT)).await
+}
+
+/// Open a TLS stream to `addr`, see [`open`].
+pub async fn open_to(
+  config: TlsConfig,
+  addr: impl ToSocketAddrs,
+) -> Result<Stream, OpenStreamError> {
   trace!(?config, "opening tls stream to twitch");
   Ok(
     TlsConnector::from(config.client())
-      .connect(
-        config.server_name(),
-        TcpStream::connect((HOST, PORT)).await?,
-      )
+      .connect(config.server_name(), TcpStream::connect(addr).await?)
       .await?,
   )
 }
 
+/// Open a plain TCP stream to `addr`, usually [`HOST`]:[`PLAIN_PORT`].
+pub async fn open_plain(addr: impl ToSocketAddrs) -> Result<TcpStream, OpenStreamError> {
+  trace!("opening tcp stream to twitch");
+  Ok(TcpStream::connect(addr).await?)
+}
+
 /// Failed to open a TLS stream.
 #[derive(Debug)]
 pub enum OpenStreamError {
@@ -70,6 +116,34 @@ impl TlsConfig {
     })
   }
 
+  /// A config without any root certificates, for clients which never open a stream.
+  #[cfg(test)]
+  pub(crate) fn empty() -> Self {
+    let config = rustls::ClientConfig::builder()
+      .with_safe_defaults()
+      .with_root_certificates(RootCertStore::empty())
+      .with_no_client_auth();
+    Self {
+      config: Arc::new(config),
+      server_name: ServerName::try_from(HOST).unwrap(),
+    }
+  }
+
+  /// A config which only trusts the DER-encoded `cert`, for clients of a local test server.
+  #[cfg(test)]
+  pub(crate) fn trusting(cert: &[u8]) -> Self {
+    let mut root_store = RootCertStore::empty();
+    root_store.add(&rustls::Certificate(cert.to_vec())).unwrap();
+    let config = rustls::ClientConfig::builder()
+      .with_safe_defaults()
+      .with_root_certificates(root_store)
+      .with_no_client_auth();
+    Self {
+      config: Arc::new(config),
+      server_name: ServerName::try_from(HOST).unwrap(),
+    }
+  }
+
   pub fn client(&self) -> Arc<ClientConfig> {
     self.config.clone()
   }
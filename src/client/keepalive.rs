@@ -0,0 +1,132 @@
+use super::read::{ClientReader, RecvError};
+use super::write::ClientWriter;
+use crate::irc::{Command, IrcMessage};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Keeps the connection alive, and detects when it is dead.
+///
+/// - Every `PING` sent by Twitch is answered with a `PONG`.
+/// - If no message is received for `interval`, a `PING` is sent.
+/// - If no message is received for another `interval` after that,
+///   the connection is considered dead.
+pub(super) struct Keepalive {
+  interval: Duration,
+  deadline: Instant,
+  ping_pending: bool,
+}
+
+impl Keepalive {
+  pub(super) fn new(interval: Duration) -> Self {
+    Self {
+      interval,
+      deadline: Instant::now() + interval,
+      ping_pending: false,
+    }
+  }
+
+  pub(super) fn reset(&mut self) {
+    self.deadline = Instant::now() + self.interval;
+    self.ping_pending = false;
+  }
+
+  pub(super) async fn recv(
+    &mut self,
+    reader: &mut ClientReader,
+    writer: &mut ClientWriter,
+  ) -> Result<IrcMessage, RecvError> {
+    loop {
+      match tokio::time::timeout_at(self.deadline, reader.recv()).await {
+        Ok(message) => {
+          let message = message?;
+          self.reset();
+          if message.command() == Command::Ping {
+            trace!("responding to PING");
+            writer
+              .pong_with(message.text())
+              .await
+              .map_err(RecvError::Send)?;
+          }
+          return Ok(message);
+        }
+        Err(_) if self.ping_pending => {
+          trace!("keepalive timed out");
+          return Err(RecvError::KeepaliveTimeout);
+        }
+        Err(_) => {
+          trace!("connection idle, sending PING");
+          writer
+            .ping("tmi.twitch.tv")
+            .await
+            .map_err(RecvError::Send)?;
+          self.deadline = Instant::now() + self.interval;
+          self.ping_pending = true;
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::client::{split, Config};
+  use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+  fn mock() -> (ClientReader, ClientWriter, DuplexStream) {
+    let (stream, server) = tokio::io::duplex(1024);
+    let config = Config::default();
+    let (reader, writer) = split(stream);
+    (
+      ClientReader::new(reader, &config),
+      ClientWriter::new(writer, &config),
+      server,
+    )
+  }
+
+  async fn read_line(server: &mut DuplexStream) -> String {
+    let mut buf = [0u8; 64];
+    let n = server.read(&mut buf).await.unwrap();
+    String::from_utf8_lossy(&buf[..n]).into_owned()
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn auto_reply_to_ping() {
+    let (mut reader, mut writer, mut server) = mock();
+    let mut keepalive = Keepalive::new(Duration::from_secs(60));
+
+    server.write_all(b"PING :tmi.twitch.tv\r\n").await.unwrap();
+    let message = keepalive.recv(&mut reader, &mut writer).await.unwrap();
+    assert_eq!(message.command(), Command::Ping);
+    assert_eq!(read_line(&mut server).await, "PONG :tmi.twitch.tv\r\n");
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn ping_when_idle() {
+    let (mut reader, mut writer, mut server) = mock();
+    let mut keepalive = Keepalive::new(Duration::from_secs(60));
+
+    let task = tokio::spawn(async move { keepalive.recv(&mut reader, &mut writer).await });
+    assert_eq!(read_line(&mut server).await, "PING :tmi.twitch.tv\r\n");
+    server
+      .write_all(b":tmi.twitch.tv PONG tmi.twitch.tv :tmi.twitch.tv\r\n")
+      .await
+      .unwrap();
+    let message = task.await.unwrap().unwrap();
+    assert_eq!(message.command(), Command::Pong);
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn idle_timeout() {
+    let (mut reader, mut writer, mut server) = mock();
+    let mut keepalive = Keepalive::new(Duration::from_secs(60));
+
+    let start = Instant::now();
+    let task = tokio::spawn(async move { keepalive.recv(&mut reader, &mut writer).await });
+    assert_eq!(read_line(&mut server).await, "PING :tmi.twitch.tv\r\n");
+    let error = task.await.unwrap().unwrap_err();
+    assert!(matches!(error, RecvError::KeepaliveTimeout));
+    assert!(error.is_disconnect());
+    assert!(start.elapsed() >= Duration::from_secs(120));
+  }
+}
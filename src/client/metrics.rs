@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters which are updated as a [`Client`][super::Client] reads messages.
+///
+/// The counters are shared, so they may be read from another task while the client is in use.
+///
+/// ```rust,no_run
+/// # async fn _test() -> anyhow::Result<()> {
+/// let mut client = tmi::Client::connect().await?;
+/// let metrics = client.metrics();
+/// tokio::spawn(async move {
+///   loop {
+///     tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+///     println!("read {} messages", metrics.messages_read());
+///   }
+/// });
+/// while let Ok(message) = client.recv().await {
+///   println!("{}", message.raw());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct Metrics {
+  messages_read: AtomicU64,
+  parse_failures: AtomicU64,
+  bytes_read: AtomicU64,
+  reconnects: AtomicU64,
+}
+
+impl Metrics {
+  /// Number of messages which were read and parsed successfully.
+  pub fn messages_read(&self) -> u64 {
+    self.messages_read.load(Ordering::Relaxed)
+  }
+
+  /// Number of lines which failed to parse, see [`RecvError::Parse`][super::read::RecvError::Parse].
+  pub fn parse_failures(&self) -> u64 {
+    self.parse_failures.load(Ordering::Relaxed)
+  }
+
+  /// Number of bytes read, excluding line terminators.
+  pub fn bytes_read(&self) -> u64 {
+    self.bytes_read.load(Ordering::Relaxed)
+  }
+
+  /// Number of successful reconnects, see [`Client::reconnect`][super::Client::reconnect].
+  pub fn reconnects(&self) -> u64 {
+    self.reconnects.load(Ordering::Relaxed)
+  }
+
+  pub(super) fn record_line(&self, line: &str, parsed: bool) {
+    self
+      .bytes_read
+      .fetch_add(line.len() as u64, Ordering::Relaxed);
+    match parsed {
+      true => self.messages_read.fetch_add(1, Ordering::Relaxed),
+      false => self.parse_failures.fetch_add(1, Ordering::Relaxed),
+    };
+  }
+
+  pub(super) fn record_reconnect(&self) {
+    self.reconnects.fetch_add(1, Ordering::Relaxed);
+  }
+}
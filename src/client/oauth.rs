@@ -0,0 +1,105 @@
+use std::fmt::Display;
+
+/// The range of plausible token lengths, excluding the `oauth:` prefix.
+///
+/// Twitch currently issues 30 character tokens.
+const TOKEN_LEN: std::ops::RangeInclusive<usize> = 20..=64;
+
+/// Check that `token` looks like a Twitch oauth2 token.
+///
+/// The token may be given with or without the `oauth:` prefix.
+/// Returns the token with the prefix, as sent in the `PASS` command.
+///
+/// This does not check whether the token is actually valid, only that it is plausible.
+/// It catches common mistakes such as passing a client secret, a token which includes
+/// the `Bearer` keyword, or an empty string.
+///
+/// ```rust
+/// use tmi::client::{validate_oauth, OauthError};
+///
+/// let token = "cfabdegwdoklmawdzdo98xt2fo512y";
+/// assert_eq!(validate_oauth(token).unwrap(), format!("oauth:{token}"));
+/// assert_eq!(validate_oauth("Bearer abc"), Err(OauthError::InvalidCharacter(' ')));
+/// ```
+pub fn validate_oauth(token: &str) -> Result<String, OauthError> {
+  let token = token.trim();
+  let token = token.strip_prefix("oauth:").unwrap_or(token);
+  if token.is_empty() {
+    return Err(OauthError::Empty);
+  }
+  if let Some(c) = token.chars().find(|c| !c.is_ascii_alphanumeric()) {
+    return Err(OauthError::InvalidCharacter(c));
+  }
+  if !TOKEN_LEN.contains(&token.len()) {
+    return Err(OauthError::InvalidLength(token.len()));
+  }
+  Ok(format!("oauth:{token}"))
+}
+
+/// The oauth2 token is not plausible.
+///
+/// See [`validate_oauth`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OauthError {
+  /// The token is empty.
+  Empty,
+
+  /// The token contains a character which is not ASCII alphanumeric.
+  InvalidCharacter(char),
+
+  /// The token is too short or too long.
+  InvalidLength(usize),
+}
+
+impl Display for OauthError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      OauthError::Empty => write!(f, "invalid oauth token: token is empty"),
+      OauthError::InvalidCharacter(c) => {
+        write!(f, "invalid oauth token: unexpected character {c:?}")
+      }
+      OauthError::InvalidLength(len) => write!(
+        f,
+        "invalid oauth token: expected {} to {} characters, got {len}",
+        TOKEN_LEN.start(),
+        TOKEN_LEN.end()
+      ),
+    }
+  }
+}
+
+impl std::error::Error for OauthError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn valid_token() {
+    let token = "oauth:cfabdegwdoklmawdzdo98xt2fo512y";
+    assert_eq!(validate_oauth(token).as_deref(), Ok(token));
+  }
+
+  #[test]
+  fn token_without_prefix_is_normalized() {
+    assert_eq!(
+      validate_oauth(" cfabdegwdoklmawdzdo98xt2fo512y\n").as_deref(),
+      Ok("oauth:cfabdegwdoklmawdzdo98xt2fo512y")
+    );
+  }
+
+  #[test]
+  fn invalid_tokens() {
+    assert_eq!(validate_oauth(""), Err(OauthError::Empty));
+    assert_eq!(validate_oauth("oauth:"), Err(OauthError::Empty));
+    assert_eq!(
+      validate_oauth("Bearer cfabdegwdoklmawdzdo98xt2fo512y"),
+      Err(OauthError::InvalidCharacter(' '))
+    );
+    assert_eq!(
+      validate_oauth("oauth:oauth:cfabdegwdoklmawdzdo98xt2fo512y"),
+      Err(OauthError::InvalidCharacter(':'))
+    );
+    assert_eq!(validate_oauth("abc"), Err(OauthError::InvalidLength(3)));
+  }
+}
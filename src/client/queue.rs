@@ -0,0 +1,374 @@
+use super::write::{ClientWriter, SendError};
+use super::Client;
+use crate::common::{Channel, ChannelRef};
+use std::fmt::Display;
+use std::io;
+use tokio::sync::mpsc;
+
+/// How many times a queued message is sent before it is dropped.
+const MAX_SEND_ATTEMPTS: u32 = 3;
+
+/// A bounded queue of `PRIVMSG`s, created by [`ClientWriter::queue`].
+///
+/// The queued messages are sent by [`ClientWriter::run_queue`], which respects the
+/// [`Config::message_rate`][super::Config::message_rate]. When messages are queued faster
+/// than they can be sent, the queue fills up instead of growing without bound.
+///
+/// The queue may be cloned to send messages from multiple tasks.
+///
+/// ```rust,no_run
+/// # async fn _test() -> anyhow::Result<()> {
+/// let client = tmi::Client::connect().await?;
+/// let (mut reader, mut writer) = client.split();
+/// let queue = writer.queue(64);
+/// tokio::spawn(async move { writer.run_queue().await });
+///
+/// let channel = tmi::ChannelRef::parse("#forsen")?;
+/// queue.send(channel, "yo").await?;
+///
+/// // the queue runs in its own task, so messages can be received at the same time
+/// loop {
+///   let message = reader.recv().await?;
+///   println!("{}", message.raw());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MessageQueue {
+  tx: mpsc::Sender<Queued>,
+}
+
+#[derive(Debug)]
+pub(super) struct Queued {
+  channel: Channel,
+  text: String,
+  /// How many times sending the message has failed.
+  failed: u32,
+}
+
+/// The receiving end of a [`MessageQueue`].
+#[derive(Debug)]
+pub(super) struct QueueReceiver {
+  rx: mpsc::Receiver<Queued>,
+  /// A message which failed to send, and is sent again before any other.
+  retry: Option<Queued>,
+}
+
+impl MessageQueue {
+  /// Queue a `PRIVMSG` to `channel`, failing with [`QueueError::Full`] if the queue is full.
+  pub fn try_send(
+    &self,
+    channel: impl AsRef<ChannelRef>,
+    text: impl Into<String>,
+  ) -> Result<(), QueueError> {
+    let message = Queued::new(channel.as_ref(), text.into())?;
+    self.tx.try_send(message).map_err(|e| match e {
+      mpsc::error::TrySendError::Full(_) => QueueError::Full,
+      mpsc::error::TrySendError::Closed(_) => QueueError::Closed,
+    })
+  }
+
+  /// Queue a `PRIVMSG` to `channel`, waiting until there is space in the queue.
+  pub async fn send(
+    &self,
+    channel: impl AsRef<ChannelRef>,
+    text: impl Into<String>,
+  ) -> Result<(), QueueError> {
+    let message = Queued::new(channel.as_ref(), text.into())?;
+    self.tx.send(message).await.map_err(|_| QueueError::Closed)
+  }
+
+  /// Number of messages which are waiting to be sent.
+  pub fn depth(&self) -> usize {
+    self.tx.max_capacity() - self.tx.capacity()
+  }
+
+  /// Maximum number of messages which may be waiting to be sent.
+  pub fn capacity(&self) -> usize {
+    self.tx.max_capacity()
+  }
+}
+
+impl Queued {
+  fn new(channel: &ChannelRef, text: String) -> Result<Self, QueueError> {
+    if text.contains(['\r', '\n']) {
+      return Err(QueueError::LineBreak);
+    }
+    Ok(Self {
+      channel: channel.to_owned(),
+      text,
+      failed: 0,
+    })
+  }
+}
+
+/// Whether sending a message again may succeed after it failed with `e`.
+///
+/// This is not the case once the connection is closed, or if the message is invalid.
+fn is_retryable(e: &SendError) -> bool {
+  match e {
+    SendError::Io(e) => !matches!(
+      e.kind(),
+      io::ErrorKind::BrokenPipe
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::NotConnected
+        | io::ErrorKind::UnexpectedEof
+        | io::ErrorKind::WriteZero
+    ),
+    _ => false,
+  }
+}
+
+impl ClientWriter {
+  /// Create a bounded queue of `PRIVMSG`s with room for `capacity` messages.
+  ///
+  /// The messages are only sent while [`ClientWriter::run_queue`] is running.
+  /// This replaces any previously created queue, which is then closed.
+  ///
+  /// # Panics
+  ///
+  /// If `capacity` is zero.
+  pub fn queue(&mut self, capacity: usize) -> MessageQueue {
+    let (tx, rx) = mpsc::channel(capacity);
+    self.queue = Some(QueueReceiver { rx, retry: None });
+    MessageQueue { tx }
+  }
+
+  /// Send the messages from the [`ClientWriter::queue`] as they arrive.
+  ///
+  /// This returns once every [`MessageQueue`] was dropped and the queue is empty,
+  /// or immediately if there is no queue. If sending fails, the error is returned.
+  ///
+  /// A message which failed to send because of a transient I/O error is sent again
+  /// by the next call, followed by the remaining messages. It is dropped after
+  /// failing 3 times, or right away if the connection was closed or the message is invalid.
+  pub async fn run_queue(&mut self) -> Result<(), SendError> {
+    while let Some(queue) = &mut self.queue {
+      let mut message = match queue.retry.take() {
+        Some(message) => message,
+        None => match queue.rx.recv().await {
+          Some(message) => message,
+          None => {
+            self.queue = None;
+            break;
+          }
+        },
+      };
+      if let Err(e) = self.privmsg(&message.channel, &message.text).send().await {
+        message.failed += 1;
+        if let Some(queue) = &mut self.queue {
+          if is_retryable(&e) && message.failed < MAX_SEND_ATTEMPTS {
+            queue.retry = Some(message);
+          }
+        }
+        return Err(e);
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Client {
+  /// Create a bounded queue of `PRIVMSG`s with room for `capacity` messages.
+  ///
+  /// See [`ClientWriter::queue`].
+  pub fn queue(&mut self, capacity: usize) -> MessageQueue {
+    self.writer.queue(capacity)
+  }
+
+  /// Send the messages from the [`Client::queue`] as they arrive.
+  ///
+  /// ⚠ This borrows the client until it returns, which is only once every [`MessageQueue`]
+  /// was dropped, so [`Client::recv`] can't be called in the meantime. To receive messages
+  /// while the queue is running, [split][`Client::split`] the client, and run the queue
+  /// on the [`ClientWriter`] in its own task, as shown in the [`MessageQueue`] example.
+  ///
+  /// See [`ClientWriter::run_queue`].
+  pub async fn run_queue(&mut self) -> Result<(), SendError> {
+    self.writer.run_queue().await
+  }
+}
+
+/// Failed to queue a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueError {
+  /// The queue is full.
+  Full,
+
+  /// The queue was replaced or the writer was dropped, so the message would never be sent.
+  Closed,
+
+  /// The message contains a `\r` or `\n`.
+  LineBreak,
+}
+
+impl Display for QueueError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      QueueError::Full => write!(f, "failed to queue message: queue is full"),
+      QueueError::Closed => write!(f, "failed to queue message: queue is closed"),
+      QueueError::LineBreak => write!(f, "failed to queue message: message contains a line break"),
+    }
+  }
+}
+
+impl std::error::Error for QueueError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::client::Config;
+  use tokio::io::AsyncReadExt;
+
+  #[tokio::test]
+  async fn try_send_when_full() {
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let mut writer = ClientWriter::new(writer, &Config::default());
+    let channel = ChannelRef::parse("#forsen").unwrap();
+
+    let queue = writer.queue(2);
+    assert_eq!(queue.capacity(), 2);
+    assert_eq!(queue.depth(), 0);
+    queue.try_send(channel, "a").unwrap();
+    queue.try_send(channel, "b").unwrap();
+    assert_eq!(queue.depth(), 2);
+    assert_eq!(queue.try_send(channel, "c"), Err(QueueError::Full));
+    assert_eq!(queue.depth(), 2);
+
+    // the full queue applies backpressure to `send`, until the writer makes room
+    let sender = tokio::spawn({
+      let queue = queue.clone();
+      async move { queue.send(channel.to_owned(), "c").await }
+    });
+    tokio::task::yield_now().await;
+    assert!(!sender.is_finished());
+
+    drop(queue);
+    let run = tokio::spawn(async move {
+      writer.run_queue().await.unwrap();
+      writer
+    });
+    sender.await.unwrap().unwrap();
+    let mut writer = run.await.unwrap();
+    writer.close().await.unwrap();
+
+    let mut buf = Vec::new();
+    server.read_to_end(&mut buf).await.unwrap();
+    let buf = String::from_utf8(buf).unwrap();
+    let texts = buf
+      .lines()
+      .filter_map(crate::IrcMessage::parse)
+      .filter(|message| message.command() == crate::Command::Privmsg)
+      .map(|message| message.text().unwrap().to_owned())
+      .collect::<Vec<_>>();
+    assert_eq!(texts, ["a", "b", "c"]);
+  }
+
+  #[tokio::test]
+  async fn closed_queue() {
+    let (stream, _server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let mut writer = ClientWriter::new(writer, &Config::default());
+    let channel = ChannelRef::parse("#forsen").unwrap();
+
+    let queue = writer.queue(1);
+    assert_eq!(
+      queue.try_send(channel, "a\r\nQUIT"),
+      Err(QueueError::LineBreak)
+    );
+    let _ = writer.queue(1);
+    assert_eq!(queue.try_send(channel, "a"), Err(QueueError::Closed));
+    assert_eq!(queue.send(channel, "a").await, Err(QueueError::Closed));
+
+    // without any queue handles left, there is nothing to send
+    writer.run_queue().await.unwrap();
+    writer.run_queue().await.unwrap();
+  }
+
+  /// A stream which fails every write with an error that isn't caused by a closed connection.
+  struct Failing;
+
+  impl tokio::io::AsyncWrite for Failing {
+    fn poll_write(
+      self: std::pin::Pin<&mut Self>,
+      _: &mut std::task::Context<'_>,
+      _: &[u8],
+    ) -> std::task::Poll<io::Result<usize>> {
+      std::task::Poll::Ready(Err(io::Error::other("failing")))
+    }
+
+    fn poll_flush(
+      self: std::pin::Pin<&mut Self>,
+      _: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+      std::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+      self: std::pin::Pin<&mut Self>,
+      _: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+      std::task::Poll::Ready(Ok(()))
+    }
+  }
+
+  fn retry(writer: &ClientWriter) -> Option<String> {
+    let queue = writer.queue.as_ref().unwrap();
+    queue.retry.as_ref().map(|message| message.text.clone())
+  }
+
+  #[tokio::test]
+  async fn retry_after_failed_send() {
+    let mut writer = ClientWriter::new(Box::new(Failing), &Config::default());
+    let channel = ChannelRef::parse("#forsen").unwrap();
+
+    let queue = writer.queue(2);
+    queue.try_send(channel, "a").unwrap();
+    queue.try_send(channel, "b").unwrap();
+
+    // the message which failed to send is kept, and sent again before the rest
+    assert!(writer.run_queue().await.is_err());
+    assert_eq!(retry(&writer).as_deref(), Some("a"));
+    assert!(writer.run_queue().await.is_err());
+    assert_eq!(retry(&writer).as_deref(), Some("a"));
+    assert_eq!(queue.depth(), 1);
+
+    // until it has failed too often
+    assert!(writer.run_queue().await.is_err());
+    assert_eq!(retry(&writer), None);
+    assert!(writer.run_queue().await.is_err());
+    assert_eq!(retry(&writer).as_deref(), Some("b"));
+    assert_eq!(queue.depth(), 0);
+  }
+
+  #[tokio::test]
+  async fn drop_after_closed_connection() {
+    let (stream, server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let mut writer = ClientWriter::new(writer, &Config::default());
+    let channel = ChannelRef::parse("#forsen").unwrap();
+
+    let queue = writer.queue(2);
+    queue.try_send(channel, "a").unwrap();
+    queue.try_send(channel, "b").unwrap();
+    drop(server);
+
+    // sending again can't succeed, so the message is not kept
+    assert!(writer.run_queue().await.is_err());
+    assert_eq!(retry(&writer), None);
+    assert_eq!(queue.depth(), 1);
+  }
+
+  #[test]
+  fn retryable_errors() {
+    let closed = io::Error::new(io::ErrorKind::BrokenPipe, "closed");
+    assert!(!is_retryable(&SendError::Io(closed)));
+    assert!(!is_retryable(&SendError::StreamClosed));
+    assert!(!is_retryable(&SendError::LineBreak));
+    let other = io::Error::other("other");
+    assert!(is_retryable(&SendError::Io(other)));
+  }
+}
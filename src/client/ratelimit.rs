@@ -0,0 +1,154 @@
+use crate::common::{Channel, ChannelRef};
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A rate limit of `count` operations `per` duration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+  /// Number of operations allowed within the duration.
+  pub count: u32,
+
+  /// The duration over which the operations are counted.
+  pub per: Duration,
+}
+
+impl RateLimit {
+  /// The limit on `JOIN`s for normal accounts, 20 channels per 10 seconds.
+  pub const JOIN: RateLimit = RateLimit {
+    count: 20,
+    per: Duration::from_secs(10),
+  };
+
+  /// The limit on `PRIVMSG`s for normal accounts, 20 messages per 30 seconds.
+  pub const PRIVMSG: RateLimit = RateLimit {
+    count: 20,
+    per: Duration::from_secs(30),
+  };
+
+  /// The limit on `PRIVMSG`s in channels where the account is a moderator
+  /// or the broadcaster, 100 messages per 30 seconds.
+  pub const PRIVMSG_MODERATOR: RateLimit = RateLimit {
+    count: 100,
+    per: Duration::from_secs(30),
+  };
+}
+
+/// Limits on the rate at which `PRIVMSG`s are sent.
+///
+/// Twitch allows sending more messages to channels in which the account
+/// is a moderator or the broadcaster, so those use a separate limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageRate {
+  /// The limit for channels in which the account is a regular user.
+  pub normal: RateLimit,
+
+  /// The limit for channels in which the account is a moderator or the broadcaster.
+  pub moderator: RateLimit,
+}
+
+impl MessageRate {
+  /// The limits for normal accounts.
+  pub const DEFAULT: MessageRate = MessageRate {
+    normal: RateLimit::PRIVMSG,
+    moderator: RateLimit::PRIVMSG_MODERATOR,
+  };
+}
+
+impl Default for MessageRate {
+  fn default() -> Self {
+    Self::DEFAULT
+  }
+}
+
+/// Token bucket which holds up to `limit.count` tokens,
+/// and refills at a rate of `limit.count` tokens per `limit.per`.
+#[derive(Debug)]
+pub(super) struct TokenBucket {
+  limit: RateLimit,
+  tokens: f64,
+  updated_at: Instant,
+}
+
+impl TokenBucket {
+  pub(super) fn new(limit: RateLimit) -> Self {
+    let limit = RateLimit {
+      count: limit.count.max(1),
+      per: limit.per,
+    };
+    Self {
+      limit,
+      tokens: limit.count as f64,
+      updated_at: Instant::now(),
+    }
+  }
+
+  fn refill(&mut self) {
+    let now = Instant::now();
+    let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+    let rate = self.limit.count as f64 / self.limit.per.as_secs_f64();
+    self.tokens = (self.tokens + elapsed * rate).min(self.limit.count as f64);
+    self.updated_at = now;
+  }
+
+  /// Wait until at least one token is available, and then take up to `max` tokens.
+  ///
+  /// Returns the number of tokens taken, and how long it had to wait for them.
+  pub(super) async fn acquire(&mut self, max: usize) -> (usize, Duration) {
+    self.refill();
+    let mut wait = Duration::ZERO;
+    if self.tokens < 1.0 {
+      let rate = self.limit.count as f64 / self.limit.per.as_secs_f64();
+      wait = Duration::from_secs_f64((1.0 - self.tokens) / rate);
+      trace!(?wait, "rate limited");
+      tokio::time::sleep(wait).await;
+      self.refill();
+    }
+    let taken = std::cmp::min(max, self.tokens.floor() as usize).max(1);
+    self.tokens -= taken as f64;
+    (taken, wait)
+  }
+}
+
+/// Rate limiter for `PRIVMSG`s, which picks the bucket
+/// based on the account's role in each channel.
+#[derive(Debug)]
+pub(super) struct MessageLimiter {
+  normal: TokenBucket,
+  moderator: TokenBucket,
+  moderator_in: HashSet<Channel>,
+}
+
+impl MessageLimiter {
+  pub(super) fn new(rate: MessageRate) -> Self {
+    Self {
+      normal: TokenBucket::new(rate.normal),
+      moderator: TokenBucket::new(rate.moderator),
+      moderator_in: HashSet::new(),
+    }
+  }
+
+  pub(super) fn set_moderator(&mut self, channel: &ChannelRef, moderator: bool) {
+    let channel = channel.normalize();
+    match moderator {
+      true => self.moderator_in.insert(channel),
+      false => self.moderator_in.remove(&channel),
+    };
+  }
+
+  pub(super) fn is_moderator(&self, channel: &ChannelRef) -> bool {
+    self.moderator_in.contains(&channel.normalize())
+  }
+
+  /// Wait until a message may be sent to `channel`.
+  ///
+  /// Returns how long it had to wait.
+  pub(super) async fn acquire(&mut self, channel: &ChannelRef) -> Duration {
+    let bucket = match self.is_moderator(channel) {
+      true => &mut self.moderator,
+      false => &mut self.normal,
+    };
+    let (_, wait) = bucket.acquire(1).await;
+    wait
+  }
+}
@@ -1,24 +1,242 @@
-use super::{conn, Client};
-use crate::irc::IrcMessage;
-use futures_util::stream::Fuse;
+use super::write::SendError;
+use super::{Client, Config, DuplicateStrategy, Metrics, ReconnectError};
+use crate::common::DedupFilter;
+use crate::irc::{Command, IrcMessage};
+use futures_util::stream::{self, Fuse, Stream};
 use std::fmt::Display;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::io;
-use tokio::io::{BufReader, ReadHalf};
+use tokio::io::{AsyncRead, BufReader};
 use tokio_stream::wrappers::LinesStream;
 use tokio_stream::StreamExt;
 
-pub type ReadStream = Fuse<LinesStream<BufReader<ReadHalf<conn::Stream>>>>;
+pub type ReadStream = Fuse<LinesStream<BufReader<Box<dyn AsyncRead + Send + Sync + Unpin>>>>;
+
+/// The reading half of a [`Client`], created by [`Client::split`].
+pub struct ClientReader {
+  pub(super) stream: ReadStream,
+  pub(super) tags_verified: bool,
+  duplicate_strategy: DuplicateStrategy,
+  verify_tags: bool,
+  read_timeout: Option<Duration>,
+  pub(super) metrics: Arc<Metrics>,
+}
+
+impl ClientReader {
+  pub(super) fn new(stream: ReadStream, config: &Config) -> Self {
+    Self {
+      stream,
+      tags_verified: false,
+      duplicate_strategy: config.duplicate_strategy,
+      verify_tags: config.verify_tags,
+      read_timeout: config.read_timeout,
+      metrics: Arc::default(),
+    }
+  }
+
+  /// Counters for the messages read by this reader.
+  ///
+  /// See [`Client::metrics`].
+  pub fn metrics(&self) -> Arc<Metrics> {
+    self.metrics.clone()
+  }
 
-impl Client {
   /// Read a single [`IrcMessage`] from the underlying stream.
+  ///
+  /// See [`Client::recv`].
   pub async fn recv(&mut self) -> Result<IrcMessage, RecvError> {
-    if let Some(message) = self.reader.next().await {
+    let next = match self.read_timeout {
+      Some(timeout) => tokio::time::timeout(timeout, self.stream.next())
+        .await
+        .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))?,
+      None => self.stream.next().await,
+    };
+    if let Some(message) = next {
       let message = message?;
-      Ok(IrcMessage::parse(&message).ok_or_else(|| RecvError::Parse(message))?)
+      let parsed = IrcMessage::parse(&message);
+      self.metrics.record_line(&message, parsed.is_some());
+      if parsed.is_none() {
+        warn!(line = %message, "failed to parse message");
+      }
+      let message = parsed.ok_or(RecvError::Parse(message))?;
+      if is_auth_failure(&message) {
+        return Err(RecvError::AuthFailed);
+      }
+      if self.duplicate_strategy == DuplicateStrategy::Detect {
+        if let Some(e) = SendError::from_notice(&message) {
+          return Err(RecvError::Send(e));
+        }
+      }
+      if self.verify_tags && !self.tags_verified {
+        match has_tags(&message) {
+          Some(true) => self.tags_verified = true,
+          Some(false) => return Err(RecvError::TagsNotEnabled),
+          None => {}
+        }
+      }
+      Ok(message)
     } else {
       Err(RecvError::StreamClosed)
     }
   }
+
+  /// Read a single [`IrcMessage`] which has not been seen by the `filter` yet.
+  ///
+  /// See [`Client::recv_dedup`].
+  pub async fn recv_dedup(&mut self, filter: &mut DedupFilter) -> Result<IrcMessage, RecvError> {
+    loop {
+      let message = self.recv().await?;
+      if !filter.seen_irc(&message.as_ref()) {
+        return Ok(message);
+      }
+      trace!(?message, "skipping duplicate message");
+    }
+  }
+}
+
+impl Client {
+  /// Read a single [`IrcMessage`] from the underlying stream.
+  ///
+  /// If the client was configured with [`DuplicateStrategy::Detect`],
+  /// then a `msg_duplicate` notice is returned as [`RecvError::Send`].
+  ///
+  /// If the client was configured with [`Config::verify_tags`],
+  /// then the first `PRIVMSG` or `ROOMSTATE` is checked for the presence of tags.
+  ///
+  /// If the client was configured with [`Config::read_timeout`], then this fails
+  /// with [`RecvError::Io`] when no message arrives in time.
+  ///
+  /// If the client was configured with [`Config::keepalive`], then this also
+  /// responds to `PING`s, and sends a `PING` when the connection is idle.
+  ///
+  /// If the client was configured with [`Config::auto_reconnect`], then this
+  /// reconnects when the connection is lost, or when Twitch sends a `RECONNECT`.
+  pub async fn recv(&mut self) -> Result<IrcMessage, RecvError> {
+    loop {
+      let result = match &mut self.keepalive {
+        Some(keepalive) => keepalive.recv(&mut self.reader, &mut self.writer).await,
+        None => self.reader.recv().await,
+      };
+      if let Ok(message) = &result {
+        self.observe(message);
+      }
+      if !self.config.auto_reconnect {
+        return result;
+      }
+      match result {
+        Ok(message) if message.command() == Command::Reconnect => {}
+        Err(e) if e.is_disconnect() => {}
+        result => return result,
+      }
+      trace!("reconnecting automatically");
+      self
+        .reconnect()
+        .await
+        .map_err(|e| RecvError::Reconnect(Box::new(e)))?;
+    }
+  }
+
+  /// Read a single [`IrcMessage`] which has not been seen by the `filter` yet.
+  ///
+  /// Messages with an `id` tag which was already seen are skipped.
+  /// The same `filter` may be shared between multiple clients to deduplicate
+  /// messages across all of their connections.
+  ///
+  /// Otherwise, this behaves like [`Client::recv`].
+  pub async fn recv_dedup(&mut self, filter: &mut DedupFilter) -> Result<IrcMessage, RecvError> {
+    loop {
+      let message = self.recv().await?;
+      if !filter.seen_irc(&message.as_ref()) {
+        return Ok(message);
+      }
+      trace!(?message, "skipping duplicate message");
+    }
+  }
+
+  /// Turn the client into a [`Stream`] of messages received using [`Client::recv`].
+  ///
+  /// The stream ends when the connection is closed. Any other error, such as a
+  /// message which failed to parse, is yielded as an `Err` item.
+  ///
+  /// See [`Client::typed_stream`] for a stream of typed messages.
+  ///
+  /// ```rust,no_run
+  /// # async fn _test() -> anyhow::Result<()> {
+  /// use futures_util::StreamExt;
+  ///
+  /// let client = tmi::Client::connect().await?;
+  /// let mut pings = std::pin::pin!(client
+  ///   .into_stream()
+  ///   .filter_map(|msg| async move { msg.ok() })
+  ///   .filter(|msg| std::future::ready(msg.command() == tmi::Command::Ping)));
+  /// while let Some(ping) = pings.next().await {
+  ///   println!("{}", ping.raw());
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn into_stream(self) -> impl Stream<Item = Result<IrcMessage, RecvError>> {
+    stream::unfold(self, |mut client| async move {
+      match client.recv().await {
+        Err(RecvError::StreamClosed) => None,
+        result => Some((result, client)),
+      }
+    })
+  }
+
+  /// Turn the client into a [`Stream`] of messages which have not been seen by the `filter` yet.
+  ///
+  /// This is [`Client::into_stream`], with duplicates skipped like [`Client::recv_dedup`] does.
+  ///
+  /// ```rust,no_run
+  /// # async fn _test() -> anyhow::Result<()> {
+  /// use futures_util::StreamExt;
+  ///
+  /// let client = tmi::Client::connect().await?;
+  /// let mut messages = std::pin::pin!(client.dedup(tmi::DedupFilter::default()));
+  /// while let Some(message) = messages.next().await {
+  ///   println!("{}", message?.raw());
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn dedup(self, mut filter: DedupFilter) -> impl Stream<Item = Result<IrcMessage, RecvError>> {
+    self.into_stream().filter(move |result| match result {
+      Ok(message) => !filter.seen_irc(&message.as_ref()),
+      Err(_) => true,
+    })
+  }
+
+  /// Turn the client into a [`Stream`] of [typed messages][crate::TypedMessage].
+  ///
+  /// Commands without a typed variant arrive as [`Message::Other`][crate::Message::Other].
+  /// A message which has a typed variant but fails to parse into it is yielded
+  /// as [`RecvError::Parse`]. Otherwise, this behaves like [`Client::into_stream`].
+  ///
+  /// ```rust,no_run
+  /// # async fn _test() -> anyhow::Result<()> {
+  /// use futures_util::StreamExt;
+  /// use tmi::Message;
+  ///
+  /// let client = tmi::Client::connect().await?;
+  /// let mut messages = std::pin::pin!(client.typed_stream());
+  /// while let Some(message) = messages.next().await {
+  ///   if let Message::Privmsg(privmsg) = message?.message() {
+  ///     println!("{}: {}", privmsg.sender().name(), privmsg.text());
+  ///   }
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  #[cfg(feature = "message-types")]
+  pub fn typed_stream(self) -> impl Stream<Item = Result<crate::TypedMessage, RecvError>> {
+    self.into_stream().map(|result| {
+      let message = result?;
+      let raw = message.raw().to_owned();
+      crate::TypedMessage::new(message).map_err(|_| RecvError::Parse(raw))
+    })
+  }
 }
 
 /// Failed to receive a message.
@@ -30,15 +248,54 @@ pub enum RecvError {
   /// Failed to parse the message.
   Parse(String),
 
+  /// Twitch rejected a previously sent message.
+  Send(SendError),
+
   /// The stream was closed.
   StreamClosed,
+
+  /// Received a message without tags, even though the `twitch.tv/tags` capability was requested.
+  TagsNotEnabled,
+
+  /// Twitch did not respond to a keepalive `PING` in time.
+  ///
+  /// See [`Config::keepalive`].
+  KeepaliveTimeout,
+
+  /// Failed to reconnect automatically.
+  ///
+  /// See [`Config::auto_reconnect`].
+  Reconnect(Box<ReconnectError>),
+
+  /// Twitch rejected the credentials, and is about to close the connection.
+  AuthFailed,
+}
+
+/// Returns `true` if `message` is the `NOTICE` which Twitch sends
+/// before closing the connection when the credentials are invalid.
+pub(super) fn is_auth_failure(message: &IrcMessage) -> bool {
+  if message.command() != Command::Notice || message.channel().is_some() {
+    return false;
+  }
+  message.text().is_some_and(|text| {
+    text.contains("authentication failed") || text.contains("Improperly formatted auth")
+  })
+}
+
+/// Returns whether `message` carries tags, or [`None`] if it is not
+/// a message which is always tagged when the `twitch.tv/tags` capability is enabled.
+fn has_tags(message: &IrcMessage) -> Option<bool> {
+  match message.command() {
+    Command::Privmsg | Command::RoomState => Some(message.tags().next().is_some()),
+    _ => None,
+  }
 }
 
 impl RecvError {
   /// Returns `true` if this `recv` failed due to a disconnect of some kind.
   pub fn is_disconnect(&self) -> bool {
     match self {
-      RecvError::StreamClosed => true,
+      RecvError::StreamClosed | RecvError::KeepaliveTimeout => true,
       RecvError::Io(e)
         if matches!(
           e.kind(),
@@ -63,9 +320,293 @@ impl Display for RecvError {
     match self {
       RecvError::Io(e) => write!(f, "failed to read message: {e}"),
       RecvError::Parse(s) => write!(f, "failed to read message: invalid message `{s}`"),
+      RecvError::Send(e) => write!(f, "failed to read message: {e}"),
       RecvError::StreamClosed => write!(f, "failed to read message: stream closed"),
+      RecvError::TagsNotEnabled => write!(
+        f,
+        "failed to read message: message has no tags, the `twitch.tv/tags` capability is not enabled"
+      ),
+      RecvError::KeepaliveTimeout => write!(
+        f,
+        "failed to read message: connection timed out, no response to keepalive ping"
+      ),
+      RecvError::Reconnect(e) => write!(f, "failed to read message: {e}"),
+      RecvError::AuthFailed => write!(f, "failed to read message: authentication failed"),
     }
   }
 }
 
 impl std::error::Error for RecvError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn auth_failure_notice() {
+    use tokio::io::AsyncWriteExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (reader, _writer) = crate::client::split(stream);
+    let mut reader = ClientReader::new(reader, &Config::default());
+
+    server
+      .write_all(
+        b"@msg-id=msg_banned :tmi.twitch.tv NOTICE #forsen :You are permanently banned from talking in forsen.\r\n\
+          :tmi.twitch.tv NOTICE * :Login authentication failed\r\n",
+      )
+      .await
+      .unwrap();
+    drop(server);
+
+    assert_eq!(reader.recv().await.unwrap().command(), Command::Notice);
+    assert!(matches!(reader.recv().await, Err(RecvError::AuthFailed)));
+  }
+
+  #[tokio::test]
+  async fn duplicate_notice_after_send() {
+    use crate::client::DuplicateStrategy;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    const NOTICE: &[u8] = b"@msg-id=msg_duplicate :tmi.twitch.tv NOTICE #forsen :Your message was not sent because it is identical to the previous one you sent, less than 30 seconds ago.\r\n";
+
+    for strategy in [DuplicateStrategy::Ignore, DuplicateStrategy::Detect] {
+      let (stream, server) = tokio::io::duplex(1024);
+      let mut server = BufReader::new(server);
+      let config = Config {
+        duplicate_strategy: strategy,
+        ..Config::default()
+      };
+      let mut client = crate::client::tests::mock(stream, config);
+      let channel = crate::ChannelRef::parse("#forsen").unwrap();
+
+      client.privmsg(channel, "yo").send().await.unwrap();
+      let mut line = String::new();
+      server.read_line(&mut line).await.unwrap();
+      assert!(line.ends_with("PRIVMSG #forsen :yo\r\n"), "{line:?}");
+      server.write_all(NOTICE).await.unwrap();
+
+      let result = client.recv().await;
+      match strategy {
+        DuplicateStrategy::Detect => {
+          assert!(matches!(result, Err(RecvError::Send(SendError::Duplicate))))
+        }
+        _ => assert_eq!(result.unwrap().command(), Command::Notice),
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn metrics() {
+    use tokio::io::AsyncWriteExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (reader, _writer) = crate::client::split(stream);
+    let mut reader = ClientReader::new(reader, &Config::default());
+    let metrics = reader.metrics();
+
+    server
+      .write_all(b"PING :tmi.twitch.tv\r\n \r\n:tmi.twitch.tv PONG\r\n")
+      .await
+      .unwrap();
+    drop(server);
+
+    assert_eq!(reader.recv().await.unwrap().command(), Command::Ping);
+    assert_eq!(metrics.parse_failures(), 0);
+    assert!(matches!(reader.recv().await, Err(RecvError::Parse(_))));
+    assert_eq!(metrics.parse_failures(), 1);
+    assert_eq!(reader.recv().await.unwrap().command(), Command::Pong);
+    assert!(matches!(reader.recv().await, Err(RecvError::StreamClosed)));
+
+    assert_eq!(metrics.messages_read(), 2);
+    assert_eq!(metrics.parse_failures(), 1);
+    assert_eq!(metrics.bytes_read(), 19 + 1 + 19);
+    assert_eq!(metrics.reconnects(), 0);
+  }
+
+  #[cfg(feature = "tracing")]
+  #[tokio::test]
+  async fn parse_error_event() {
+    use std::sync::Mutex;
+    use tokio::io::AsyncWriteExt;
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Output {
+      fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+      }
+
+      fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+      }
+    }
+
+    let output = Output::default();
+    let subscriber = tracing_subscriber::fmt()
+      .with_max_level(tracing::Level::WARN)
+      .with_ansi(false)
+      .with_writer({
+        let output = output.clone();
+        move || output.clone()
+      })
+      .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (reader, _writer) = crate::client::split(stream);
+    let mut reader = ClientReader::new(reader, &Config::default());
+
+    server.write_all(b"@id=1;mod=0\r\n").await.unwrap();
+    drop(server);
+    assert!(matches!(reader.recv().await, Err(RecvError::Parse(_))));
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("WARN"), "{output}");
+    assert!(output.contains("failed to parse message"), "{output}");
+    assert!(output.contains("line=@id=1;mod=0"), "{output}");
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn read_timeout() {
+    use tokio::io::AsyncWriteExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (reader, _writer) = crate::client::split(stream);
+    let config = Config {
+      read_timeout: Some(Duration::from_secs(10)),
+      ..Config::default()
+    };
+    let mut reader = ClientReader::new(reader, &config);
+
+    // the timeout restarts with every message, so these arrive in time
+    let server = tokio::spawn(async move {
+      for _ in 0..2 {
+        tokio::time::sleep(Duration::from_secs(6)).await;
+        server.write_all(b"PING :tmi.twitch.tv\r\n").await.unwrap();
+      }
+      server
+    });
+    assert_eq!(reader.recv().await.unwrap().command(), Command::Ping);
+    assert_eq!(reader.recv().await.unwrap().command(), Command::Ping);
+    let _server = server.await.unwrap();
+
+    // the stream is still open, but nothing arrives
+    let e = reader.recv().await.unwrap_err();
+    assert!(matches!(&e, RecvError::Io(e) if e.kind() == io::ErrorKind::TimedOut));
+    assert!(e.is_disconnect());
+  }
+
+  #[tokio::test]
+  async fn message_stream() {
+    use tokio::io::AsyncWriteExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let client = crate::client::tests::mock(stream, Config::default());
+
+    server
+      .write_all(
+        b"PING :tmi.twitch.tv\r\n\
+          :forsen!forsen@forsen.tmi.twitch.tv JOIN #forsen\r\n \r\n\
+          :tmi.twitch.tv RECONNECT\r\n",
+      )
+      .await
+      .unwrap();
+    drop(server);
+
+    let messages = client.into_stream().collect::<Vec<_>>().await;
+    assert_eq!(messages.len(), 4);
+    assert!(matches!(&messages[2], Err(RecvError::Parse(line)) if line == " "));
+    let commands = messages
+      .iter()
+      .filter_map(|message| message.as_ref().ok())
+      .map(|message| message.command())
+      .collect::<Vec<_>>();
+    assert_eq!(commands, [Command::Ping, Command::Join, Command::Reconnect]);
+  }
+
+  #[tokio::test]
+  async fn dedup_message_stream() {
+    use tokio::io::AsyncWriteExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let client = crate::client::tests::mock(stream, Config::default());
+
+    server
+      .write_all(
+        b"@id=a :a!a@a.tmi.twitch.tv PRIVMSG #forsen :yo\r\n\
+          @id=a :a!a@a.tmi.twitch.tv PRIVMSG #forsen :yo\r\n\
+          PING :tmi.twitch.tv\r\n\
+          @id=b :a!a@a.tmi.twitch.tv PRIVMSG #forsen :yo\r\n",
+      )
+      .await
+      .unwrap();
+    drop(server);
+
+    let messages = client
+      .dedup(DedupFilter::default())
+      .collect::<Result<Vec<_>, _>>()
+      .await
+      .unwrap();
+    let ids = messages
+      .iter()
+      .map(|message| message.tag(crate::Tag::Id))
+      .collect::<Vec<_>>();
+    assert_eq!(ids, [Some("a"), None, Some("b")]);
+  }
+
+  #[cfg(feature = "message-types")]
+  #[tokio::test]
+  async fn typed_message_stream() {
+    use crate::Message;
+    use tokio::io::AsyncWriteExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let client = crate::client::tests::mock(stream, Config::default());
+
+    server
+      .write_all(
+        b"@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam\r\n\
+          :tmi.twitch.tv 001 justinfan12345 :Welcome, GLHF!\r\n\
+          :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :no tags\r\n",
+      )
+      .await
+      .unwrap();
+    drop(server);
+
+    let messages = client.typed_stream().collect::<Vec<_>>().await;
+    assert_eq!(messages.len(), 3);
+    match messages[0].as_ref().unwrap().message() {
+      Message::Privmsg(privmsg) => assert_eq!(privmsg.text(), "dank cam"),
+      message => panic!("expected a privmsg, got {message:?}"),
+    }
+    assert!(matches!(
+      messages[1].as_ref().unwrap().message(),
+      Message::Other(_)
+    ));
+    assert!(
+      matches!(&messages[2], Err(RecvError::Parse(line)) if line.ends_with("PRIVMSG #pajlada :no tags"))
+    );
+  }
+
+  #[test]
+  fn untagged_messages() {
+    let privmsg =
+      IrcMessage::parse(":forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :test").unwrap();
+    assert_eq!(has_tags(&privmsg), Some(false));
+
+    let room_state = IrcMessage::parse(":tmi.twitch.tv ROOMSTATE #forsen").unwrap();
+    assert_eq!(has_tags(&room_state), Some(false));
+
+    let privmsg = IrcMessage::parse(
+      "@room-id=22484632 :forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :test",
+    )
+    .unwrap();
+    assert_eq!(has_tags(&privmsg), Some(true));
+
+    let join = IrcMessage::parse(":forsen!forsen@forsen.tmi.twitch.tv JOIN #forsen").unwrap();
+    assert_eq!(has_tags(&join), None);
+  }
+}
@@ -1,19 +1,185 @@
-use super::{conn, Client};
+use super::ratelimit::{MessageLimiter, TokenBucket};
+use super::{Client, Config, DuplicateStrategy};
 use crate::common::JoinIter;
-use crate::common::{ChannelRef, InvalidChannelName};
+use crate::common::{Channel, ChannelRef, InvalidChannelName};
+use crate::irc::{Command, IrcMessage, Tag as IrcTag};
+use rand::{thread_rng, Rng};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::convert::Infallible;
 use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::io;
-use tokio::io::{AsyncWriteExt, WriteHalf};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::oneshot;
 
-pub type WriteStream = WriteHalf<conn::Stream>;
+pub type WriteStream = Box<dyn AsyncWrite + Send + Sync + Unpin>;
+
+/// The writing half of a [`Client`], created by [`Client::split`].
+///
+/// It provides the same interface for sending messages as the [`Client`].
+pub struct ClientWriter {
+  pub(super) stream: WriteStream,
+  pub(super) scratch: String,
+  bypass: SameMessageBypass,
+  duplicate_strategy: DuplicateStrategy,
+  channels: BTreeSet<Channel>,
+  join_limiter: Option<TokenBucket>,
+  message_limiter: Option<MessageLimiter>,
+  privmsg_delay: Option<Duration>,
+  closed: bool,
+  unconfirmed: HashMap<String, oneshot::Sender<Result<(), SendError>>>,
+  nick: String,
+  pending_joins: BTreeMap<Channel, Vec<oneshot::Sender<Result<(), JoinError>>>>,
+  pub(super) queue: Option<super::queue::QueueReceiver>,
+}
+
+impl ClientWriter {
+  pub(super) fn new(stream: WriteStream, config: &Config) -> Self {
+    Self {
+      stream,
+      scratch: String::with_capacity(1024),
+      bypass: SameMessageBypass::default(),
+      duplicate_strategy: config.duplicate_strategy,
+      channels: BTreeSet::new(),
+      join_limiter: config.join_rate.map(TokenBucket::new),
+      message_limiter: config.message_rate.map(MessageLimiter::new),
+      privmsg_delay: None,
+      closed: false,
+      unconfirmed: HashMap::new(),
+      nick: config.credentials.nick.to_lowercase(),
+      pending_joins: BTreeMap::new(),
+      queue: None,
+    }
+  }
+
+  /// Channels which were joined using [`ClientWriter::join`] or [`ClientWriter::join_all`],
+  /// and not left using [`ClientWriter::part`].
+  ///
+  /// Channel names are [normalized][`ChannelRef::normalize`].
+  pub fn joined_channels(&self) -> impl ExactSizeIterator<Item = &ChannelRef> + '_ {
+    self.channels.iter().map(|channel| channel.as_ref())
+  }
+
+  /// Returns `true` if `channel` is one of the [joined channels][`ClientWriter::joined_channels`].
+  pub fn is_joined(&self, channel: impl AsRef<ChannelRef>) -> bool {
+    self.channels.contains(&channel.as_ref().normalize())
+  }
+
+  /// Set whether the account is a moderator or the broadcaster in `channel`.
+  ///
+  /// This determines which limit of the [`Config::message_rate`] applies to
+  /// `PRIVMSG`s sent to `channel`. [`Client::recv`] keeps it up to date
+  /// using the `mod` tag and `badges` of each `USERSTATE`.
+  pub fn set_moderator(&mut self, channel: impl AsRef<ChannelRef>, moderator: bool) {
+    if let Some(limiter) = &mut self.message_limiter {
+      limiter.set_moderator(channel.as_ref(), moderator);
+    }
+  }
+
+  /// How long the last `PRIVMSG` was delayed by the [`Config::message_rate`].
+  ///
+  /// Returns [`None`] if it was sent immediately.
+  pub fn privmsg_delay(&self) -> Option<Duration> {
+    self.privmsg_delay
+  }
+
+  /// Update the account's role in a channel from a `USERSTATE`,
+  /// and resolve the [`Confirmation`] of the `PRIVMSG` which caused it.
+  pub(super) fn observe(&mut self, message: &IrcMessage) {
+    self.confirm(message);
+    self.confirm_join(message);
+    if message.command() != Command::UserState {
+      return;
+    }
+    let Some(Ok(channel)) = message.channel().map(ChannelRef::parse) else {
+      return;
+    };
+    let moderator = message.tag_bool(IrcTag::Mod) == Some(true)
+      || message
+        .tag(IrcTag::Badges)
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|badge| badge.split_once('/'))
+        .any(|(name, _)| name == "moderator" || name == "broadcaster");
+    self.set_moderator(channel, moderator);
+  }
+
+  /// Resolve the [`Confirmation`] of a `PRIVMSG` from the response to it,
+  /// which carries the same `client-nonce`.
+  fn confirm(&mut self, message: &IrcMessage) {
+    if self.unconfirmed.is_empty() {
+      return;
+    }
+    let Some(nonce) = message.tag(IrcTag::ClientNonce) else {
+      return;
+    };
+    let result = match message.command() {
+      Command::UserState | Command::Privmsg => Ok(()),
+      Command::Notice => Err(SendError::from_notice(message).unwrap_or_else(|| {
+        SendError::Rejected(message.tag(IrcTag::MsgId).unwrap_or_default().to_owned())
+      })),
+      _ => return,
+    };
+    if let Some(tx) = self.unconfirmed.remove(nonce) {
+      let _ = tx.send(result);
+    }
+  }
+
+  /// Resolve the [`JoinConfirmation`] of a channel from the response to the `JOIN`.
+  ///
+  /// Twitch echoes the `JOIN` back and sends a `ROOMSTATE` once the channel was joined,
+  /// or a `NOTICE` if the channel is suspended or does not exist.
+  fn confirm_join(&mut self, message: &IrcMessage) {
+    if self.pending_joins.is_empty() {
+      return;
+    }
+    let available = match message.command() {
+      Command::RoomState => true,
+      Command::Join if message.prefix().and_then(|prefix| prefix.nick) == Some(&self.nick) => true,
+      Command::Notice => match message.tag(IrcTag::MsgId) {
+        Some("msg_channel_suspended" | "tos_ban" | "msg_room_not_found") => false,
+        _ => return,
+      },
+      _ => return,
+    };
+    let Some(Ok(channel)) = message.channel().map(ChannelRef::parse) else {
+      return;
+    };
+    let channel = channel.normalize();
+    if !available {
+      // don't join the channel again when reconnecting
+      self.channels.remove(&channel);
+    }
+    for tx in self.pending_joins.remove(&channel).unwrap_or_default() {
+      let _ = tx.send(match available {
+        true => Ok(()),
+        false => Err(JoinError::ChannelUnavailable),
+      });
+    }
+  }
+
+  /// Join all previously [joined channels][`ClientWriter::joined_channels`] again.
+  pub(super) async fn rejoin(&mut self) -> Result<(), SendError> {
+    if self.channels.is_empty() {
+      return Ok(());
+    }
+    let channels = std::mem::take(&mut self.channels);
+    let result = self.join_all(&channels).await;
+    self.channels.extend(channels);
+    result
+  }
+}
 
 pub struct Privmsg<'a> {
-  client: &'a mut Client,
+  client: &'a mut ClientWriter,
   channel: &'a ChannelRef,
   text: &'a str,
   reply_parent_msg_id: Option<&'a str>,
   client_nonce: Option<&'a str>,
+  action: bool,
 }
 
 struct Tag<'a> {
@@ -24,8 +190,7 @@ struct Tag<'a> {
 impl<'a> std::fmt::Display for Tag<'a> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let Self { key, value } = self;
-    // TODO: handle escaping
-    write!(f, "{key}={value}")
+    write!(f, "{key}={}", crate::irc::escape(value))
   }
 }
 
@@ -40,39 +205,169 @@ impl<'a> Privmsg<'a> {
     self
   }
 
+  /// Send the message.
   pub async fn send(self) -> Result<(), SendError> {
+    self.send_inner(false).await.map(|_| ())
+  }
+
+  /// Send the message, and return the `client-nonce` sent along with it.
+  ///
+  /// If one was not provided using [`Privmsg::client_nonce`], then a random one is generated.
+  ///
+  /// Twitch echoes the nonce back in the `client-nonce` tag of the `PRIVMSG` (if the
+  /// `echo-message` capability is enabled), and of any `NOTICE` sent in response to it,
+  /// so it may be used to match them with the sent message.
+  pub async fn send_with_nonce(self) -> Result<String, SendError> {
+    let (client_nonce, _) = self.send_inner(true).await?;
+    Ok(client_nonce.unwrap_or_default())
+  }
+
+  async fn send_inner(
+    self,
+    generate_client_nonce: bool,
+  ) -> Result<(Option<String>, &'a mut ClientWriter), SendError> {
     let Self {
       client,
       channel,
       text,
       reply_parent_msg_id,
       client_nonce,
+      action,
     } = self;
 
+    no_line_breaks(text)?;
+    no_line_breaks(reply_parent_msg_id.unwrap_or_default())?;
+    no_line_breaks(client_nonce.unwrap_or_default())?;
+
+    let client_nonce = match client_nonce {
+      Some(client_nonce) => Some(client_nonce.to_owned()),
+      None => generate_client_nonce.then(generate_nonce),
+    };
+
+    client.privmsg_delay = match &mut client.message_limiter {
+      Some(limiter) => Some(limiter.acquire(channel).await).filter(|wait| !wait.is_zero()),
+      None => None,
+    };
+
     with_scratch!(client, |f| {
-      let has_tags = reply_parent_msg_id.is_some() || client_nonce.is_some();
-      if has_tags {
-        let reply_parent_msg_id = reply_parent_msg_id.map(|value| Tag {
-          key: "reply-parent-msg-id",
-          value,
-        });
-        let client_nonce = client_nonce.map(|value| Tag {
-          key: "client-nonce",
-          value,
-        });
+      let reply_parent_msg_id = reply_parent_msg_id.map(|value| Tag {
+        key: "reply-parent-msg-id",
+        value,
+      });
+      let client_nonce = client_nonce.as_deref().map(|value| Tag {
+        key: "client-nonce",
+        value,
+      });
+      if reply_parent_msg_id.is_some() || client_nonce.is_some() {
         let tags = reply_parent_msg_id
           .iter()
           .chain(client_nonce.iter())
           .join(';');
         let _ = write!(f, "@{tags} ");
       }
-      let _ = write!(f, "PRIVMSG {channel} :{text}\r\n");
+      let bypass = match client.duplicate_strategy {
+        DuplicateStrategy::Bypass => client.bypass.get(),
+        _ => "",
+      };
+      let _ = match action {
+        true => write!(
+          f,
+          "PRIVMSG {channel} :\u{0001}ACTION {text}{bypass}\u{0001}\r\n"
+        ),
+        false => write!(f, "PRIVMSG {channel} :{text}{bypass}\r\n"),
+      };
       client.send_raw(f.as_str()).await
-    })
+    })?;
+
+    Ok((client_nonce, client))
+  }
+
+  /// Send the message, and return a [`Confirmation`] which resolves
+  /// once Twitch has responded to it.
+  ///
+  /// Twitch responds to a `PRIVMSG` with a `USERSTATE` for the channel once it was
+  /// accepted, or with a `NOTICE` if it was rejected. Both carry the `client-nonce`
+  /// sent along with the message, which is used to match them with the message.
+  ///
+  /// ⚠ Responses are only observed by [`Client::recv`], so the confirmation
+  /// never resolves if the client was [split][`Client::split`].
+  ///
+  /// ```rust,no_run
+  /// # async fn _test() -> anyhow::Result<()> {
+  /// # let mut client: tmi::Client = todo!();
+  /// let channel = tmi::ChannelRef::parse("#forsen")?;
+  /// let confirmation = client.privmsg(channel, "yo").send_confirmed().await?;
+  /// let mut confirmation = std::pin::pin!(confirmation);
+  /// loop {
+  ///   tokio::select! {
+  ///     result = &mut confirmation => break result?,
+  ///     message = client.recv() => println!("{}", message?.raw()),
+  ///   }
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub async fn send_confirmed(self) -> Result<Confirmation, SendError> {
+    let (nonce, client) = self.send_inner(true).await?;
+    let nonce = nonce.unwrap_or_default();
+
+    // forget about messages whose confirmation was dropped
+    client.unconfirmed.retain(|_, tx| !tx.is_closed());
+    let (tx, rx) = oneshot::channel();
+    client.unconfirmed.insert(nonce.clone(), tx);
+
+    Ok(Confirmation { nonce, rx })
   }
 }
 
-impl Client {
+/// Resolves once Twitch has responded to a `PRIVMSG`,
+/// see [`Privmsg::send_confirmed`].
+///
+/// The output is `Ok(())` if the message was accepted,
+/// or a [`SendError::Rejected`] or [`SendError::Duplicate`] if it was rejected.
+#[must_use = "the confirmation does nothing unless awaited"]
+pub struct Confirmation {
+  nonce: String,
+  rx: oneshot::Receiver<Result<(), SendError>>,
+}
+
+impl Confirmation {
+  /// The `client-nonce` sent along with the message.
+  pub fn nonce(&self) -> &str {
+    &self.nonce
+  }
+}
+
+impl Future for Confirmation {
+  type Output = Result<(), SendError>;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    // the sender is dropped along with the writer
+    Pin::new(&mut self.rx)
+      .poll(cx)
+      .map(|result| result.unwrap_or(Err(SendError::StreamClosed)))
+  }
+}
+
+/// Resolves once Twitch has responded to a `JOIN`,
+/// see [`ClientWriter::join_confirmed`].
+#[must_use = "the confirmation does nothing unless awaited"]
+pub struct JoinConfirmation {
+  rx: oneshot::Receiver<Result<(), JoinError>>,
+}
+
+impl Future for JoinConfirmation {
+  type Output = Result<(), JoinError>;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    // the sender is dropped along with the writer
+    Pin::new(&mut self.rx)
+      .poll(cx)
+      .map(|result| result.unwrap_or(Err(JoinError::Send(SendError::StreamClosed))))
+  }
+}
+
+impl ClientWriter {
   /// Send a raw string through the TCP socket.
   ///
   /// ⚠ This call is not rate limited in any way.
@@ -85,7 +380,7 @@ impl Client {
   {
     let RawMessage { data } = s.try_into()?;
     trace!(data, "sending message");
-    self.writer.write_all(data.as_bytes()).await?;
+    self.stream.write_all(data.as_bytes()).await?;
     Ok(())
   }
 
@@ -94,7 +389,7 @@ impl Client {
   /// ```rust,no_run
   /// # async fn _test() -> anyhow::Result<()> {
   /// # let msg: tmi::Privmsg<'_> = todo!();
-  /// # let mut client: tmi::Client = todo!();
+  /// # let mut client: tmi::client::ClientWriter = todo!();
   /// client
   ///   .privmsg(msg.channel(), "yo")
   ///   .reply_to(msg.message_id())
@@ -104,9 +399,15 @@ impl Client {
   /// # }
   /// ```
   ///
+  /// If the client was configured with [`Config::message_rate`], then sending waits
+  /// until the message may be sent. See [`ClientWriter::privmsg_delay`].
+  ///
   /// You can specify additional properties using the builder methods:
   /// - `reply_to`: to specify a `reply-parent-msg-id` tag, which makes this privmsg a reply to another message.
   /// - `client_nonce`: to identify the message in the `Notice` which Twitch may send as a response to this message.
+  ///
+  /// Sending fails with [`SendError::LineBreak`] if `text` or any of the properties contain
+  /// a `\r` or `\n`, as that would allow injecting arbitrary commands into the connection.
   pub fn privmsg<'a, C>(&'a mut self, channel: &'a C, text: &'a str) -> Privmsg<'a>
   where
     C: AsRef<ChannelRef> + ?Sized + 'a,
@@ -117,9 +418,53 @@ impl Client {
       text,
       reply_parent_msg_id: None,
       client_nonce: None,
+      action: false,
+    }
+  }
+
+  /// Create a `privmsg` from a `channel` and `text`, which is sent as an action,
+  /// like the `/me` command.
+  ///
+  /// The `text` is wrapped in `\x01ACTION ` and `\x01`, and the message is received
+  /// with [`Privmsg::is_action`][crate::Privmsg::is_action] set.
+  ///
+  /// See [`ClientWriter::privmsg`].
+  pub fn action<'a, C>(&'a mut self, channel: &'a C, text: &'a str) -> Privmsg<'a>
+  where
+    C: AsRef<ChannelRef> + ?Sized + 'a,
+  {
+    Privmsg {
+      action: true,
+      ..self.privmsg(channel, text)
     }
   }
 
+  /// Create a `privmsg` which replies to `message`, in the same channel.
+  ///
+  /// ```rust,no_run
+  /// # async fn _test() -> anyhow::Result<()> {
+  /// # let msg: tmi::Privmsg<'_> = todo!();
+  /// # let mut client: tmi::client::ClientWriter = todo!();
+  /// client.reply_to(&msg, "yo").send().await?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  ///
+  /// The `reply-parent-msg-id` is always the ID of `message`. If `message` is itself
+  /// a reply, Twitch places the new message in the same thread.
+  ///
+  /// See [`ClientWriter::privmsg`].
+  #[cfg(feature = "message-types")]
+  pub fn reply_to<'a>(
+    &'a mut self,
+    message: &'a crate::msg::Privmsg<'_>,
+    text: &'a str,
+  ) -> Privmsg<'a> {
+    self
+      .privmsg(message.channel(), text)
+      .reply_to(message.message_id())
+  }
+
   /// Send a `PING` command with an optional `nonce` argument.
   pub async fn ping(&mut self, nonce: &str) -> Result<(), SendError> {
     with_scratch!(self, |f| {
@@ -129,9 +474,14 @@ impl Client {
   }
 
   /// Send a `PONG` command in response to a `PING`.
+  #[cfg(feature = "message-types")]
   pub async fn pong(&mut self, ping: &crate::Ping<'_>) -> Result<(), SendError> {
+    self.pong_with(ping.nonce()).await
+  }
+
+  pub(super) async fn pong_with(&mut self, nonce: Option<&str>) -> Result<(), SendError> {
     with_scratch!(self, |f| {
-      if let Some(nonce) = ping.nonce() {
+      if let Some(nonce) = nonce {
         let _ = write!(f, "PONG :{nonce}\r\n");
       } else {
         let _ = write!(f, "PONG\r\n");
@@ -142,20 +492,70 @@ impl Client {
 
   /// Send a `JOIN` command.
   ///
-  /// ⚠ This call is not rate limited in any way.
+  /// Nothing is sent if the channel is already [joined][`ClientWriter::joined_channels`].
+  ///
+  /// ⚠ This call is not rate limited, unless configured via [`Config::join_rate`].
   ///
   /// ⚠ `channel` MUST be a valid channel name prefixed by `#`.
   pub async fn join(&mut self, channel: impl AsRef<ChannelRef>) -> Result<(), SendError> {
-    with_scratch!(self, |f| {
-      let channel = channel.as_ref();
-      let _ = write!(f, "JOIN {channel}\r\n");
-      Ok(self.send_raw(f.as_str()).await?)
-    })
+    self.join_all([channel]).await
+  }
+
+  /// Send a `JOIN` command, and return a [`JoinConfirmation`] which resolves
+  /// once Twitch has responded to it.
+  ///
+  /// The confirmation resolves to [`JoinError::ChannelUnavailable`] if Twitch responds
+  /// with a `NOTICE` saying that the channel is suspended or does not exist,
+  /// in which case it is also removed from the [joined channels][`ClientWriter::joined_channels`].
+  /// If the channel is already joined, it resolves immediately.
+  ///
+  /// ⚠ Responses are only observed by [`Client::recv`], so the confirmation
+  /// never resolves if the client was [split][`Client::split`].
+  ///
+  /// ```rust,no_run
+  /// # async fn _test() -> anyhow::Result<()> {
+  /// # let mut client: tmi::Client = todo!();
+  /// let channel = tmi::ChannelRef::parse("#forsen")?;
+  /// let confirmation = client.join_confirmed(channel).await?;
+  /// let mut confirmation = std::pin::pin!(confirmation);
+  /// loop {
+  ///   tokio::select! {
+  ///     result = &mut confirmation => break result?,
+  ///     message = client.recv() => println!("{}", message?.raw()),
+  ///   }
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub async fn join_confirmed(
+    &mut self,
+    channel: impl AsRef<ChannelRef>,
+  ) -> Result<JoinConfirmation, SendError> {
+    let channel = ChannelRef::parse(channel.as_ref())?.normalize();
+    let (tx, rx) = oneshot::channel();
+    if self.channels.contains(&channel) && !self.pending_joins.contains_key(&channel) {
+      let _ = tx.send(Ok(()));
+      return Ok(JoinConfirmation { rx });
+    }
+
+    self.join(&channel).await?;
+    // forget about joins whose confirmation was dropped
+    self.pending_joins.retain(|_, txs| {
+      txs.retain(|tx| !tx.is_closed());
+      !txs.is_empty()
+    });
+    self.pending_joins.entry(channel).or_default().push(tx);
+
+    Ok(JoinConfirmation { rx })
   }
 
   /// Send a `JOIN` command.
   ///
-  /// ⚠ This call is not rate limited in any way.
+  /// Channels which are already [joined][`ClientWriter::joined_channels`] are skipped,
+  /// and nothing is sent if all of them are.
+  ///
+  /// ⚠ This call is not rate limited, unless configured via [`Config::join_rate`].
+  /// If it is, then the channels are sent in batches as allowed by the limit.
   ///
   /// ⚠ Each channel in `channels` MUST be a valid channel name
   /// prefixed by `#`.
@@ -164,20 +564,211 @@ impl Client {
     I: IntoIterator<Item = C>,
     C: AsRef<ChannelRef>,
   {
-    with_scratch!(self, |f| {
-      let _ = f.write_str("JOIN ");
-      let mut channels = channels.into_iter();
-      if let Some(channel) = channels.next() {
-        let channel = ChannelRef::parse(channel.as_ref())?;
-        let _ = write!(f, "{channel}");
+    let mut joined = BTreeSet::new();
+    for channel in channels {
+      let channel = ChannelRef::parse(channel.as_ref())?.normalize();
+      if !self.channels.contains(&channel) {
+        joined.insert(channel);
       }
-      for channel in channels {
-        let channel = ChannelRef::parse(channel.as_ref())?;
-        let _ = write!(f, ",{channel}");
+    }
+    let mut joined = joined.into_iter().peekable();
+    while joined.peek().is_some() {
+      let count = match &mut self.join_limiter {
+        Some(limiter) => limiter.acquire(joined.len()).await.0,
+        None => usize::MAX,
+      };
+      let batch: Vec<_> = joined.by_ref().take(count).collect();
+      debug!(channels = %batch.iter().join(','), "joining channels");
+      with_scratch!(self, |f| {
+        let _ = write!(f, "JOIN {}\r\n", batch.iter().join(','));
+        self.send_raw(f.as_str()).await
+      })?;
+      self.channels.extend(batch);
+    }
+    Ok(())
+  }
+
+  /// Send a `PART` command.
+  ///
+  /// The channel is removed from the [joined channels][`ClientWriter::joined_channels`].
+  ///
+  /// ⚠ `channel` MUST be a valid channel name prefixed by `#`.
+  pub async fn part(&mut self, channel: impl AsRef<ChannelRef>) -> Result<(), SendError> {
+    let channel = channel.as_ref().normalize();
+    with_scratch!(self, |f| {
+      let _ = write!(f, "PART {channel}\r\n");
+      self.send_raw(f.as_str()).await
+    })?;
+    self.channels.remove(&channel);
+    Ok(())
+  }
+
+  /// Leave all [joined channels][`ClientWriter::joined_channels`], send a `QUIT` command,
+  /// and close the connection.
+  ///
+  /// Closing the connection shuts down the underlying stream,
+  /// which for TLS also sends a `close_notify` alert.
+  ///
+  /// Calling this again after it succeeded does nothing.
+  pub async fn close(&mut self) -> Result<(), SendError> {
+    if self.closed {
+      return Ok(());
+    }
+    trace!("closing connection");
+
+    let channels = std::mem::take(&mut self.channels);
+    with_scratch!(self, |f| {
+      for channel in &channels {
+        let _ = write!(f, "PART {channel}\r\n");
       }
-      let _ = f.write_str("\r\n");
+      f.push_str("QUIT\r\n");
       self.send_raw(f.as_str()).await
-    })
+    })?;
+    self.stream.flush().await?;
+    self.stream.shutdown().await?;
+    self.closed = true;
+
+    Ok(())
+  }
+}
+
+impl Client {
+  /// Channels which were joined using [`Client::join`] or [`Client::join_all`],
+  /// and not left using [`Client::part`].
+  ///
+  /// These are joined again after a reconnect.
+  pub fn joined_channels(&self) -> impl ExactSizeIterator<Item = &ChannelRef> + '_ {
+    self.writer.joined_channels()
+  }
+
+  /// Returns `true` if `channel` is one of the [joined channels][`Client::joined_channels`].
+  pub fn is_joined(&self, channel: impl AsRef<ChannelRef>) -> bool {
+    self.writer.is_joined(channel)
+  }
+
+  /// Set whether the account is a moderator or the broadcaster in `channel`.
+  ///
+  /// See [`ClientWriter::set_moderator`].
+  pub fn set_moderator(&mut self, channel: impl AsRef<ChannelRef>, moderator: bool) {
+    self.writer.set_moderator(channel, moderator)
+  }
+
+  /// How long the last `PRIVMSG` was delayed by the [`Config::message_rate`].
+  ///
+  /// Returns [`None`] if it was sent immediately.
+  pub fn privmsg_delay(&self) -> Option<Duration> {
+    self.writer.privmsg_delay()
+  }
+
+  /// Send a raw string through the TCP socket.
+  ///
+  /// See [`ClientWriter::send_raw`].
+  pub async fn send_raw<'a, S>(&mut self, s: S) -> Result<(), SendError>
+  where
+    S: TryInto<RawMessage<'a>>,
+    SendError: From<S::Error>,
+  {
+    self.writer.send_raw(s).await
+  }
+
+  /// Create a `privmsg` from a `channel` and `text`.
+  ///
+  /// ```rust,no_run
+  /// # async fn _test() -> anyhow::Result<()> {
+  /// # let msg: tmi::Privmsg<'_> = todo!();
+  /// # let mut client: tmi::Client = todo!();
+  /// client
+  ///   .privmsg(msg.channel(), "yo")
+  ///   .reply_to(msg.message_id())
+  ///   .send()
+  ///   .await?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  ///
+  /// See [`ClientWriter::privmsg`].
+  pub fn privmsg<'a, C>(&'a mut self, channel: &'a C, text: &'a str) -> Privmsg<'a>
+  where
+    C: AsRef<ChannelRef> + ?Sized + 'a,
+  {
+    self.writer.privmsg(channel, text)
+  }
+
+  /// Create a `privmsg` from a `channel` and `text`, which is sent as an action,
+  /// like the `/me` command.
+  ///
+  /// See [`ClientWriter::action`].
+  pub fn action<'a, C>(&'a mut self, channel: &'a C, text: &'a str) -> Privmsg<'a>
+  where
+    C: AsRef<ChannelRef> + ?Sized + 'a,
+  {
+    self.writer.action(channel, text)
+  }
+
+  /// Create a `privmsg` which replies to `message`, in the same channel.
+  ///
+  /// See [`ClientWriter::reply_to`].
+  #[cfg(feature = "message-types")]
+  pub fn reply_to<'a>(
+    &'a mut self,
+    message: &'a crate::msg::Privmsg<'_>,
+    text: &'a str,
+  ) -> Privmsg<'a> {
+    self.writer.reply_to(message, text)
+  }
+
+  /// Send a `PING` command with an optional `nonce` argument.
+  pub async fn ping(&mut self, nonce: &str) -> Result<(), SendError> {
+    self.writer.ping(nonce).await
+  }
+
+  /// Send a `PONG` command in response to a `PING`.
+  #[cfg(feature = "message-types")]
+  pub async fn pong(&mut self, ping: &crate::Ping<'_>) -> Result<(), SendError> {
+    self.writer.pong(ping).await
+  }
+
+  /// Send a `JOIN` command.
+  ///
+  /// See [`ClientWriter::join`].
+  pub async fn join(&mut self, channel: impl AsRef<ChannelRef>) -> Result<(), SendError> {
+    self.writer.join(channel).await
+  }
+
+  /// Send a `JOIN` command, and return a [`JoinConfirmation`] which resolves
+  /// once Twitch has responded to it.
+  ///
+  /// See [`ClientWriter::join_confirmed`].
+  pub async fn join_confirmed(
+    &mut self,
+    channel: impl AsRef<ChannelRef>,
+  ) -> Result<JoinConfirmation, SendError> {
+    self.writer.join_confirmed(channel).await
+  }
+
+  /// Send a `JOIN` command.
+  ///
+  /// See [`ClientWriter::join_all`].
+  pub async fn join_all<I, C>(&mut self, channels: I) -> Result<(), SendError>
+  where
+    I: IntoIterator<Item = C>,
+    C: AsRef<ChannelRef>,
+  {
+    self.writer.join_all(channels).await
+  }
+
+  /// Send a `PART` command.
+  ///
+  /// See [`ClientWriter::part`].
+  pub async fn part(&mut self, channel: impl AsRef<ChannelRef>) -> Result<(), SendError> {
+    self.writer.part(channel).await
+  }
+
+  /// Leave all joined channels, send a `QUIT` command, and close the connection.
+  ///
+  /// See [`ClientWriter::close`].
+  pub async fn close(mut self) -> Result<(), SendError> {
+    self.writer.close().await
   }
 }
 
@@ -195,6 +786,35 @@ pub enum SendError {
 
   /// Attempted to send a message to a channel with an invalid name.
   InvalidChannelName(InvalidChannelName),
+
+  /// Attempted to send a message which contains a `\r` or `\n`.
+  LineBreak,
+
+  /// Twitch rejected the message, because it was identical to the previous one.
+  ///
+  /// See [`DuplicateStrategy`].
+  Duplicate,
+
+  /// Twitch rejected the message with a `NOTICE`, which has this `msg-id`.
+  ///
+  /// See [`Privmsg::send_confirmed`].
+  Rejected(String),
+}
+
+impl SendError {
+  /// Classify a `NOTICE` sent by Twitch in response to a message.
+  ///
+  /// Returns [`None`] if `message` does not indicate that a message was rejected.
+  pub fn from_notice(message: &IrcMessage) -> Option<Self> {
+    if message.command() != Command::Notice {
+      return None;
+    }
+
+    match message.tag(IrcTag::MsgId)? {
+      "msg_duplicate" => Some(Self::Duplicate),
+      _ => None,
+    }
+  }
 }
 
 impl From<io::Error> for SendError {
@@ -234,13 +854,69 @@ impl Display for SendError {
         f,
         "failed to write message: message was incorrectly formatted, {inner}"
       ),
+      SendError::LineBreak => write!(f, "failed to write message: message contains a line break"),
+      SendError::Duplicate => write!(
+        f,
+        "failed to write message: message was identical to the previous one"
+      ),
+      SendError::Rejected(msg_id) => write!(
+        f,
+        "failed to write message: message was rejected by twitch ({msg_id})"
+      ),
     }
   }
 }
 
 impl std::error::Error for SendError {}
 
+/// Failed to join a channel.
+///
+/// See [`ClientWriter::join_confirmed`].
+#[derive(Debug)]
+pub enum JoinError {
+  /// Failed to send the `JOIN`, or the connection was closed before Twitch responded.
+  Send(SendError),
+
+  /// The channel is suspended or does not exist.
+  ChannelUnavailable,
+}
+
+impl From<SendError> for JoinError {
+  fn from(value: SendError) -> Self {
+    Self::Send(value)
+  }
+}
+
+impl Display for JoinError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      JoinError::Send(e) => write!(f, "failed to join channel: {e}"),
+      JoinError::ChannelUnavailable => write!(
+        f,
+        "failed to join channel: channel is suspended or does not exist"
+      ),
+    }
+  }
+}
+
+impl std::error::Error for JoinError {}
+
+/// Generate a random `client-nonce`, in the same format as the Twitch web client.
+fn generate_nonce() -> String {
+  format!("{:032x}", thread_rng().gen::<u128>())
+}
+
+fn no_line_breaks(s: &str) -> Result<(), SendError> {
+  match s.contains(['\r', '\n']) {
+    true => Err(SendError::LineBreak),
+    false => Ok(()),
+  }
+}
+
 /// Bypass the same-message slow mode requirement.
+///
+/// Each call to [`SameMessageBypass::get`] alternates between an empty string
+/// and an invisible character, which makes consecutive messages distinct.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct SameMessageBypass {
   append: bool,
@@ -293,3 +969,456 @@ impl<'a> TryFrom<&'a str> for RawMessage<'a> {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test(start_paused = true)]
+  async fn join_rate_limit() {
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::time::Instant;
+
+    let (stream, server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let config = Config {
+      join_rate: Some(crate::client::RateLimit {
+        count: 2,
+        per: Duration::from_secs(10),
+      }),
+      ..Default::default()
+    };
+    let mut writer = ClientWriter::new(writer, &config);
+
+    let start = Instant::now();
+    let task = tokio::spawn(async move {
+      let channels = ["#a", "#b", "#c", "#d", "#e"].map(|c| ChannelRef::parse(c).unwrap());
+      writer.join_all(channels).await.unwrap();
+      writer.join(ChannelRef::parse("#f").unwrap()).await.unwrap();
+    });
+
+    let mut lines = BufReader::new(server).lines();
+    let mut received = vec![];
+    for _ in 0..5 {
+      let line = lines.next_line().await.unwrap().unwrap();
+      received.push((start.elapsed().as_secs(), line));
+    }
+    task.await.unwrap();
+
+    assert_eq!(
+      received,
+      [
+        (0, "JOIN #a,#b".into()),
+        (5, "JOIN #c".into()),
+        (10, "JOIN #d".into()),
+        (15, "JOIN #e".into()),
+        (20, "JOIN #f".into()),
+      ]
+    );
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn message_rate_limit() {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let (stream, server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let config = Config {
+      message_rate: Some(crate::client::MessageRate {
+        normal: crate::client::RateLimit {
+          count: 2,
+          per: Duration::from_secs(10),
+        },
+        moderator: crate::client::RateLimit {
+          count: 3,
+          per: Duration::from_secs(10),
+        },
+      }),
+      ..Default::default()
+    };
+    let mut writer = ClientWriter::new(writer, &config);
+    let normal = ChannelRef::parse("#forsen").unwrap();
+    let moderator = ChannelRef::parse("#pajlada").unwrap();
+
+    let userstate =
+      IrcMessage::parse("@badges=moderator/1,subscriber/12 :tmi.twitch.tv USERSTATE #Pajlada")
+        .unwrap();
+    writer.observe(&userstate);
+
+    let mut delays = vec![];
+    for channel in [normal, normal, normal, moderator, moderator, moderator] {
+      writer.privmsg(channel, "yo").send().await.unwrap();
+      delays.push(writer.privmsg_delay().map(|d| d.as_secs()));
+    }
+    assert_eq!(delays, [None, None, Some(5), None, None, None]);
+
+    // the buckets refill over time
+    tokio::time::advance(Duration::from_secs(10)).await;
+    writer.privmsg(normal, "yo").send().await.unwrap();
+    assert_eq!(writer.privmsg_delay(), None);
+
+    // losing moderator falls back to the normal bucket, which is empty again
+    writer.set_moderator(moderator, false);
+    writer.privmsg(moderator, "yo").send().await.unwrap();
+    writer.privmsg(moderator, "yo").send().await.unwrap();
+    assert_eq!(writer.privmsg_delay(), Some(Duration::from_secs(5)));
+
+    let mut lines = BufReader::new(server).lines();
+    for channel in [
+      normal, normal, normal, moderator, moderator, moderator, normal, moderator, moderator,
+    ] {
+      let line = lines.next_line().await.unwrap().unwrap();
+      let line = IrcMessage::parse(line).unwrap();
+      assert_eq!(line.channel(), Some(channel.as_str()));
+    }
+  }
+
+  #[tokio::test]
+  async fn track_joined_channels() {
+    use tokio::io::AsyncReadExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let mut writer = ClientWriter::new(writer, &Config::default());
+    let channel = |name| ChannelRef::parse(name).unwrap();
+
+    writer.join(channel("#Forsen")).await.unwrap();
+    assert!(writer.is_joined(channel("#forsen")));
+    assert!(writer.is_joined(channel("#FORSEN")));
+    // redundant joins are not sent
+    writer.join(channel("#forsen")).await.unwrap();
+    writer
+      .join_all([channel("#forsen"), channel("#Pajlada"), channel("#pajlada")])
+      .await
+      .unwrap();
+    assert_eq!(
+      writer
+        .joined_channels()
+        .map(|c| c.as_str())
+        .collect::<Vec<_>>(),
+      ["#forsen", "#pajlada"]
+    );
+
+    writer.part(channel("#FORSEN")).await.unwrap();
+    assert!(!writer.is_joined(channel("#forsen")));
+    assert_eq!(writer.joined_channels().len(), 1);
+
+    let mut buf = [0u8; 128];
+    let n = server.read(&mut buf).await.unwrap();
+    assert_eq!(
+      &buf[..n],
+      b"JOIN #forsen\r\nJOIN #pajlada\r\nPART #forsen\r\n"
+    );
+  }
+
+  #[tokio::test]
+  async fn close() {
+    use tokio::io::AsyncReadExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let mut client = crate::client::tests::mock(stream, Config::default());
+    client
+      .join_all([
+        ChannelRef::parse("#forsen").unwrap(),
+        ChannelRef::parse("#pajlada").unwrap(),
+      ])
+      .await
+      .unwrap();
+    client.close().await.unwrap();
+
+    // the stream is shut down, so this reaches the end
+    let mut buf = Vec::new();
+    server.read_to_end(&mut buf).await.unwrap();
+    assert_eq!(
+      buf,
+      b"JOIN #forsen,#pajlada\r\nPART #forsen\r\nPART #pajlada\r\nQUIT\r\n"
+    );
+  }
+
+  #[tokio::test]
+  async fn close_is_idempotent() {
+    use tokio::io::AsyncReadExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let mut writer = ClientWriter::new(writer, &Config::default());
+
+    writer.close().await.unwrap();
+    writer.close().await.unwrap();
+    assert_eq!(writer.joined_channels().len(), 0);
+
+    let mut buf = Vec::new();
+    server.read_to_end(&mut buf).await.unwrap();
+    assert_eq!(buf, b"QUIT\r\n");
+  }
+
+  #[tokio::test]
+  async fn privmsg_confirmation() {
+    use futures_util::FutureExt;
+    use tokio::io::AsyncWriteExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let mut client = crate::client::tests::mock(stream, Config::default());
+    let channel = ChannelRef::parse("#forsen").unwrap();
+
+    let accepted = client
+      .privmsg(channel, "yo")
+      .client_nonce("a")
+      .send_confirmed()
+      .await
+      .unwrap();
+    let rejected = client
+      .privmsg(channel, "yo")
+      .client_nonce("b")
+      .send_confirmed()
+      .await
+      .unwrap();
+    let unanswered = client
+      .privmsg(channel, "yo")
+      .send_confirmed()
+      .await
+      .unwrap();
+    assert_eq!(accepted.nonce(), "a");
+    assert_eq!(unanswered.nonce().len(), 32);
+
+    server
+      .write_all(
+        b"@badge-info=;badges=;client-nonce=a;color=;display-name=bot;emote-sets=0;id=1;mod=0;subscriber=0;user-type= :tmi.twitch.tv USERSTATE #forsen\r\n\
+          @client-nonce=b;msg-id=msg_ratelimit :tmi.twitch.tv NOTICE #forsen :Your message was not sent because you are sending messages too quickly.\r\n",
+      )
+      .await
+      .unwrap();
+
+    let mut accepted = std::pin::pin!(accepted);
+    assert!(accepted.as_mut().now_or_never().is_none());
+    assert_eq!(client.recv().await.unwrap().command(), Command::UserState);
+    assert!(matches!(accepted.await, Ok(())));
+    assert_eq!(client.recv().await.unwrap().command(), Command::Notice);
+    assert!(matches!(rejected.await, Err(SendError::Rejected(id)) if id == "msg_ratelimit"));
+
+    drop(client);
+    assert!(matches!(unanswered.await, Err(SendError::StreamClosed)));
+  }
+
+  #[tokio::test]
+  async fn join_confirmation() {
+    use futures_util::FutureExt;
+    use tokio::io::AsyncWriteExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let config = Config::new(crate::client::Credentials::new("bot", "oauth:token"));
+    let mut client = crate::client::tests::mock(stream, config);
+    let forsen = ChannelRef::parse("#forsen").unwrap();
+    let suspended = ChannelRef::parse("#suspended").unwrap();
+
+    let joined = client.join_confirmed(forsen).await.unwrap();
+    let unavailable = client.join_confirmed(suspended).await.unwrap();
+    assert!(client.writer.is_joined(suspended));
+
+    server
+      .write_all(
+        b":bot!bot@bot.tmi.twitch.tv JOIN #forsen\r\n\
+          @msg-id=msg_channel_suspended :tmi.twitch.tv NOTICE #suspended :This channel does not exist or has been suspended.\r\n",
+      )
+      .await
+      .unwrap();
+
+    let mut joined = std::pin::pin!(joined);
+    assert!(joined.as_mut().now_or_never().is_none());
+    assert_eq!(client.recv().await.unwrap().command(), Command::Join);
+    assert!(matches!(joined.await, Ok(())));
+    assert_eq!(client.recv().await.unwrap().command(), Command::Notice);
+    assert!(matches!(
+      unavailable.await,
+      Err(JoinError::ChannelUnavailable)
+    ));
+    assert!(client.writer.is_joined(forsen));
+    assert!(!client.writer.is_joined(suspended));
+
+    // already joined
+    let joined = client.join_confirmed(forsen).await.unwrap();
+    assert!(matches!(joined.now_or_never(), Some(Ok(()))));
+  }
+
+  #[tokio::test]
+  async fn join_confirmation_room_not_found() {
+    use tokio::io::AsyncWriteExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let config = Config::new(crate::client::Credentials::new("bot", "oauth:token"));
+    let mut client = crate::client::tests::mock(stream, config);
+    let missing = ChannelRef::parse("#missing").unwrap();
+
+    let unavailable = client.join_confirmed(missing).await.unwrap();
+    server
+      .write_all(b"@msg-id=msg_room_not_found :tmi.twitch.tv NOTICE #missing :That channel does not exist.\r\n")
+      .await
+      .unwrap();
+
+    assert_eq!(client.recv().await.unwrap().command(), Command::Notice);
+    assert!(matches!(
+      unavailable.await,
+      Err(JoinError::ChannelUnavailable)
+    ));
+    assert!(!client.writer.is_joined(missing));
+  }
+
+  #[tokio::test]
+  async fn send_action() {
+    use tokio::io::AsyncReadExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let mut writer = ClientWriter::new(writer, &Config::default());
+    let channel = ChannelRef::parse("#forsen").unwrap();
+
+    writer
+      .action(channel, "waves")
+      .client_nonce("abc")
+      .send()
+      .await
+      .unwrap();
+    let mut buf = [0u8; 128];
+    let n = server.read(&mut buf).await.unwrap();
+    let line = std::str::from_utf8(&buf[..n]).unwrap();
+    assert_eq!(
+      line,
+      "@client-nonce=abc PRIVMSG #forsen :\u{0001}ACTION waves\u{0001}\r\n"
+    );
+
+    #[cfg(feature = "message-types")]
+    {
+      let line = format!(
+        "@badge-info=;badges=;color=;display-name=bot;emotes=;first-msg=0;flags=;id=1;mod=0;room-id=2;subscriber=0;tmi-sent-ts=0;turbo=0;user-id=3;user-type= :bot!bot@bot.tmi.twitch.tv {}",
+        line.trim_end().split_once(' ').unwrap().1
+      );
+      let Ok(crate::Message::Privmsg(message)) = crate::Message::parse(&line) else {
+        panic!("expected a privmsg");
+      };
+      assert!(message.is_action());
+      assert_eq!(message.text(), "waves");
+    }
+  }
+
+  #[cfg(feature = "message-types")]
+  #[tokio::test]
+  async fn reply_to_privmsg() {
+    use tokio::io::AsyncReadExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let mut writer = ClientWriter::new(writer, &Config::default());
+
+    // a reply in a thread, which is replied to using its own id
+    let message = crate::Message::parse("@badge-info=;badges=;color=;display-name=LeftSwing;emotes=;first-msg=0;flags=;id=9a0ad5b2-e6a1-4a2c-9f6b-5c1a4c9a7f11;mod=0;reply-parent-display-name=Pajlada;reply-parent-msg-body=@Retoon\\sno;reply-parent-msg-id=2d5f8a3e-19c4-4f3a-a7a2-0e5b3a1f6c2d;reply-parent-user-id=11148817;reply-parent-user-login=pajlada;reply-thread-parent-msg-id=6b13e51b-7ecb-43b5-ba5b-2bb5288df696;reply-thread-parent-user-login=retoon;returning-chatter=0;room-id=37940952;subscriber=0;tmi-sent-ts=1673925993585;turbo=0;user-id=133651738;user-type= :leftswing!leftswing@leftswing.tmi.twitch.tv PRIVMSG #retoon :@Pajlada yes").unwrap();
+    let crate::Message::Privmsg(message) = message else {
+      panic!("expected a privmsg");
+    };
+
+    writer
+      .reply_to(&message, "@LeftSwing :) no")
+      .client_nonce("abc")
+      .send()
+      .await
+      .unwrap();
+    let mut buf = [0u8; 256];
+    let n = server.read(&mut buf).await.unwrap();
+    assert_eq!(
+      std::str::from_utf8(&buf[..n]).unwrap(),
+      "@reply-parent-msg-id=9a0ad5b2-e6a1-4a2c-9f6b-5c1a4c9a7f11;client-nonce=abc PRIVMSG #retoon :@LeftSwing :) no\r\n"
+    );
+
+    // tag values are escaped
+    writer
+      .privmsg(message.channel(), "yo")
+      .client_nonce("a b;c")
+      .send()
+      .await
+      .unwrap();
+    let n = server.read(&mut buf).await.unwrap();
+    assert_eq!(
+      std::str::from_utf8(&buf[..n]).unwrap(),
+      "@client-nonce=a\\sb\\:c PRIVMSG #retoon :yo\r\n"
+    );
+  }
+
+  #[tokio::test]
+  async fn privmsg_returns_client_nonce() {
+    use tokio::io::AsyncReadExt;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let (_reader, writer) = crate::client::split(stream);
+    let mut writer = ClientWriter::new(writer, &Config::default());
+    let channel = ChannelRef::parse("#forsen").unwrap();
+
+    // `send` only sends a nonce if one was set
+    writer.privmsg(channel, "yo").send().await.unwrap();
+    let mut buf = [0u8; 128];
+    let n = server.read(&mut buf).await.unwrap();
+    assert_eq!(&buf[..n], b"PRIVMSG #forsen :yo\r\n");
+
+    let nonce = writer
+      .privmsg(channel, "yo")
+      .send_with_nonce()
+      .await
+      .unwrap();
+    assert_eq!(nonce.len(), 32);
+    let n = server.read(&mut buf).await.unwrap();
+    let line = IrcMessage::parse_bytes(&buf[..n - 2]).unwrap();
+    assert_eq!(line.tag(IrcTag::ClientNonce), Some(nonce.as_str()));
+    assert_eq!(line.text(), Some("yo"));
+
+    let nonce = writer
+      .privmsg(channel, "yo")
+      .client_nonce("abc")
+      .reply_to("def")
+      .send_with_nonce()
+      .await
+      .unwrap();
+    assert_eq!(nonce, "abc");
+    let n = server.read(&mut buf).await.unwrap();
+    assert_eq!(
+      &buf[..n],
+      b"@reply-parent-msg-id=def;client-nonce=abc PRIVMSG #forsen :yo\r\n"
+    );
+  }
+
+  #[test]
+  fn same_message_bypass_alternates() {
+    let mut bypass = SameMessageBypass::default();
+    assert_eq!(bypass.get(), "");
+    assert_eq!(bypass.get(), " ⠀");
+    assert_eq!(bypass.get(), "");
+  }
+
+  #[test]
+  fn reject_line_breaks() {
+    assert!(no_line_breaks("yo").is_ok());
+    assert!(no_line_breaks("").is_ok());
+    for text in ["yo\r\nJOIN #forsen", "yo\nPART #forsen", "yo\r"] {
+      assert!(matches!(no_line_breaks(text), Err(SendError::LineBreak)));
+    }
+  }
+
+  #[test]
+  fn classify_duplicate_notice() {
+    let notice = IrcMessage::parse("@msg-id=msg_duplicate :tmi.twitch.tv NOTICE #forsen :Your message was not sent because it is identical to the previous one you sent, less than 30 seconds ago.").unwrap();
+    assert!(matches!(
+      SendError::from_notice(&notice),
+      Some(SendError::Duplicate)
+    ));
+
+    let notice = IrcMessage::parse(
+      "@msg-id=msg_banned :tmi.twitch.tv NOTICE #forsen :You are permanently banned from talking in forsen.",
+    )
+    .unwrap();
+    assert!(SendError::from_notice(&notice).is_none());
+
+    let privmsg = IrcMessage::parse(
+      "@msg-id=msg_duplicate :forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :test",
+    )
+    .unwrap();
+    assert!(SendError::from_notice(&privmsg).is_none());
+  }
+}
@@ -1,13 +1,15 @@
 //! Random types and utilties used by the library.
 
 pub mod channel;
+pub mod dedup;
 
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::fmt::Debug;
 use std::ops::Deref;
 
-pub use channel::{Channel, ChannelRef, InvalidChannelName};
+pub use channel::{Channel, ChannelError, ChannelRef, InvalidChannelName};
+pub use dedup::DedupFilter;
 
 /// This type is like a [`Range`][std::ops::Range],
 /// only smaller, and also implements `Copy`.
@@ -97,15 +99,16 @@ where
   }
 }
 
-pub(crate) fn maybe_unescape<'a>(value: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
-  let mut value: Cow<'_, str> = value.into();
-  for i in 0..value.len() {
-    if value.as_bytes()[i] == b'\\' {
-      value = Cow::Owned(actually_unescape(&value, i));
-      break;
-    }
+/// Unescape a `value` according to the escaped characters that Twitch IRC supports.
+///
+/// Unlike [`unescape`][crate::irc::unescape], this only allocates if `value`
+/// contains escaped characters.
+pub fn maybe_unescape<'a>(value: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
+  let value: Cow<'_, str> = value.into();
+  match value.find(['\\', '⸝']) {
+    Some(start) => Cow::Owned(actually_unescape(&value, start)),
+    None => value,
   }
-  value
 }
 
 #[inline]
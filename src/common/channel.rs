@@ -12,6 +12,17 @@ impl ChannelRef {
     &self.0
   }
 
+  /// Get the channel login, which is the channel name without the `#` prefix.
+  ///
+  /// ```rust
+  /// use tmi::ChannelRef;
+  ///
+  /// assert_eq!(ChannelRef::parse("#forsen").unwrap().login(), "forsen");
+  /// ```
+  pub fn login(&self) -> &str {
+    &self.0[1..]
+  }
+
   /// Parse a string into a channel name.
   ///
   /// The channel name must begin with a `#` character.
@@ -22,6 +33,13 @@ impl ChannelRef {
     }
   }
 
+  /// Normalize the channel name by converting it to lowercase.
+  ///
+  /// Twitch channel names are case-insensitive, so `#Forsen` and `#forsen` are the same channel.
+  pub fn normalize(&self) -> Channel {
+    Channel(self.0.to_ascii_lowercase())
+  }
+
   pub(crate) fn from_unchecked(s: &str) -> &Self {
     // # Safety:
     // - `Self` is `repr(transparent)` and only holds a single `str` field,
@@ -83,11 +101,58 @@ impl ToOwned for ChannelRef {
 pub struct Channel(String);
 
 impl Channel {
+  /// Create a channel name from a login, prepending `#` if it's missing.
+  ///
+  /// ```rust
+  /// use tmi::Channel;
+  ///
+  /// assert_eq!(Channel::new("forsen").as_str(), "#forsen");
+  /// assert_eq!(Channel::new("#forsen").as_str(), "#forsen");
+  /// ```
+  pub fn new(login: impl Into<String>) -> Self {
+    let login = login.into();
+    match login.starts_with('#') {
+      true => Self(login),
+      false => Self(format!("#{login}")),
+    }
+  }
+
+  /// Create a normalized channel name from a login.
+  ///
+  /// The login is lowercased and prefixed with `#`. A leading `#` is accepted.
+  /// Twitch logins only contain ASCII letters, digits, and `_`,
+  /// so anything else is rejected.
+  ///
+  /// ```rust
+  /// use tmi::common::{Channel, ChannelError};
+  ///
+  /// assert_eq!(Channel::try_from_login("Forsen").unwrap().as_str(), "#forsen");
+  /// assert_eq!(Channel::try_from_login("for sen"), Err(ChannelError::InvalidCharacter(' ')));
+  /// ```
+  pub fn try_from_login(login: &str) -> Result<Self, ChannelError> {
+    let login = login.strip_prefix('#').unwrap_or(login);
+    if login.is_empty() {
+      return Err(ChannelError::Empty);
+    }
+    if let Some(c) = login
+      .chars()
+      .find(|c| !c.is_ascii_alphanumeric() && *c != '_')
+    {
+      return Err(ChannelError::InvalidCharacter(c));
+    }
+    Ok(Self(format!("#{}", login.to_ascii_lowercase())))
+  }
+
   /// Get the string value of the channel name.
   pub fn as_str(&self) -> &str {
     self.0.as_str()
   }
 
+  /// Get the channel login, which is the channel name without the `#` prefix.
+  pub fn login(&self) -> &str {
+    &self.0[1..]
+  }
+
   /// Parse a string into a channel name.
   ///
   /// The channel name must begin with a `#` character.
@@ -151,6 +216,31 @@ impl std::fmt::Display for InvalidChannelName {
 }
 impl std::error::Error for InvalidChannelName {}
 
+/// Failed to create a channel name from a login.
+///
+/// See [`Channel::try_from_login`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelError {
+  /// The login is empty.
+  Empty,
+
+  /// The login contains a character which is not an ASCII letter, digit, or `_`.
+  InvalidCharacter(char),
+}
+
+impl std::fmt::Display for ChannelError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ChannelError::Empty => f.write_str("invalid channel login: login is empty"),
+      ChannelError::InvalidCharacter(c) => {
+        write!(f, "invalid channel login: unexpected character {c:?}")
+      }
+    }
+  }
+}
+
+impl std::error::Error for ChannelError {}
+
 static_assert_send!(ChannelRef);
 static_assert_sync!(ChannelRef);
 
@@ -174,6 +264,41 @@ mod tests {
     );
     assert_eq!(Channel::parse("test".into()), Err(InvalidChannelName));
   }
+
+  #[test]
+  fn channel_login() {
+    assert_eq!(ChannelRef::parse("#forsen").unwrap().login(), "forsen");
+    assert_eq!(ChannelRef::parse("#forsen").unwrap().as_str(), "#forsen");
+    assert_eq!(Channel::parse("#forsen".into()).unwrap().login(), "forsen");
+
+    let channel = Channel::new("forsen");
+    assert_eq!(channel.as_str(), "#forsen");
+    assert_eq!(channel.login(), "forsen");
+    assert_eq!(Channel::new("#forsen"), channel);
+    assert_eq!(Channel::new(String::from("forsen")), channel);
+  }
+
+  #[test]
+  fn channel_from_login() {
+    assert_eq!(
+      Channel::try_from_login("ForSen_123").unwrap().as_str(),
+      "#forsen_123"
+    );
+    assert_eq!(
+      Channel::try_from_login("#Forsen").unwrap().as_str(),
+      "#forsen"
+    );
+    assert_eq!(Channel::try_from_login(""), Err(ChannelError::Empty));
+    assert_eq!(Channel::try_from_login("#"), Err(ChannelError::Empty));
+    assert_eq!(
+      Channel::try_from_login("for sen"),
+      Err(ChannelError::InvalidCharacter(' '))
+    );
+    assert_eq!(
+      Channel::try_from_login("forsen\r\nPRIVMSG"),
+      Err(ChannelError::InvalidCharacter('\r'))
+    );
+  }
 }
 
 #[cfg(feature = "serde")]
@@ -191,7 +316,7 @@ mod _serde {
     }
   }
 
-  impl<'ser> Serialize for &'ser ChannelRef {
+  impl Serialize for &ChannelRef {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
       S: Serializer,
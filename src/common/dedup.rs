@@ -0,0 +1,138 @@
+//! Filtering of messages which were received more than once.
+
+use crate::irc::{IrcMessageRef, Tag};
+use std::collections::{HashSet, VecDeque};
+
+/// Filters out messages which were already seen, based on their `id` tag.
+///
+/// When reading from multiple connections at once (for example when sharding channels,
+/// or in channels participating in shared chat), the same message may be received more than once.
+///
+/// Only the `capacity` most recently seen IDs are remembered.
+///
+/// ```rust
+/// let mut filter = tmi::DedupFilter::new(1024);
+/// assert!(!filter.seen_id("a"));
+/// assert!(filter.seen_id("a"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DedupFilter {
+  capacity: usize,
+  /// Least recently seen ID is at the front.
+  order: VecDeque<Box<str>>,
+  ids: HashSet<Box<str>>,
+}
+
+impl DedupFilter {
+  /// The number of IDs remembered by [`DedupFilter::default`].
+  pub const DEFAULT_CAPACITY: usize = 1024;
+
+  /// Create a filter which remembers up to `capacity` message IDs.
+  ///
+  /// A `capacity` of `0` is treated as `1`.
+  pub fn new(capacity: usize) -> Self {
+    let capacity = capacity.max(1);
+    Self {
+      capacity,
+      order: VecDeque::with_capacity(capacity),
+      ids: HashSet::with_capacity(capacity),
+    }
+  }
+
+  /// Returns `true` if a message with this `id` was seen before.
+  ///
+  /// The `id` is recorded as the most recently seen one.
+  pub fn seen_id(&mut self, id: &str) -> bool {
+    if self.ids.contains(id) {
+      if let Some(pos) = self.order.iter().position(|v| &**v == id) {
+        let id = self.order.remove(pos).unwrap();
+        self.order.push_back(id);
+      }
+      return true;
+    }
+
+    if self.order.len() == self.capacity {
+      if let Some(oldest) = self.order.pop_front() {
+        self.ids.remove(&oldest);
+      }
+    }
+    let id: Box<str> = id.into();
+    self.ids.insert(id.clone());
+    self.order.push_back(id);
+    false
+  }
+
+  /// Returns `true` if this `message` was seen before.
+  ///
+  /// Messages without an `id` tag are never considered duplicates.
+  pub fn seen_irc(&mut self, message: &IrcMessageRef<'_>) -> bool {
+    match message.tag(Tag::Id) {
+      Some(id) if !id.is_empty() => self.seen_id(id),
+      _ => false,
+    }
+  }
+
+  /// Returns `true` if this `message` was seen before.
+  #[cfg(feature = "message-types")]
+  pub fn seen(&mut self, message: &crate::msg::Privmsg<'_>) -> bool {
+    self.seen_id(message.message_id())
+  }
+
+  /// Number of remembered IDs.
+  pub fn len(&self) -> usize {
+    self.order.len()
+  }
+
+  /// Returns `true` if no IDs are remembered.
+  pub fn is_empty(&self) -> bool {
+    self.order.is_empty()
+  }
+}
+
+impl Default for DedupFilter {
+  fn default() -> Self {
+    Self::new(Self::DEFAULT_CAPACITY)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(feature = "message-types")]
+  #[test]
+  fn filters_same_privmsg_id() {
+    use crate::msg::{FromIrc, Privmsg};
+
+    let data = "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam";
+    let a = Privmsg::from_irc(IrcMessageRef::parse(data).unwrap()).unwrap();
+    let b = Privmsg::from_irc(IrcMessageRef::parse(data).unwrap()).unwrap();
+
+    let mut filter = DedupFilter::default();
+    assert!(!filter.seen(&a));
+    assert!(filter.seen(&b));
+  }
+
+  #[test]
+  fn messages_without_id() {
+    let mut filter = DedupFilter::default();
+    let ping = IrcMessageRef::parse(":tmi.twitch.tv PING :tmi.twitch.tv").unwrap();
+    assert!(!filter.seen_irc(&ping));
+    assert!(!filter.seen_irc(&ping));
+    assert!(filter.is_empty());
+  }
+
+  #[test]
+  fn evicts_least_recently_seen() {
+    let mut filter = DedupFilter::new(2);
+    assert!(!filter.seen_id("a"));
+    assert!(!filter.seen_id("b"));
+    // `a` is now the most recently seen
+    assert!(filter.seen_id("a"));
+    // evicts `b`
+    assert!(!filter.seen_id("c"));
+    assert_eq!(filter.len(), 2);
+    assert!(filter.seen_id("a"));
+    assert!(!filter.seen_id("b"));
+  }
+}
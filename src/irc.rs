@@ -16,19 +16,33 @@
 #[macro_use]
 mod macros;
 
-#[cfg(feature = "simd")]
+#[cfg(all(feature = "simd", not(feature = "force-scalar")))]
 mod simd;
 
 mod scalar;
 
-#[cfg(feature = "simd")]
-use simd::{parse_prefix, parse_tags};
+mod builder;
+pub use builder::{BuildError, IrcMessageBuilder};
 
-#[cfg(not(feature = "simd"))]
-use scalar::{parse_prefix, parse_tags};
+mod decoder;
+pub use decoder::MessageDecoder;
 
-use crate::common::{ChannelRef, Span};
+#[cfg(feature = "twitch-codec")]
+mod codec;
+#[cfg(feature = "twitch-codec")]
+pub use codec::{CodecError, IrcCodec};
+
+#[cfg(all(feature = "simd", not(feature = "force-scalar")))]
+use simd::{count_tags, parse_prefix, parse_tags, BACKEND};
+
+#[cfg(any(not(feature = "simd"), feature = "force-scalar"))]
+use scalar::{count_tags, parse_prefix, parse_tags, BACKEND};
+
+use crate::common::{maybe_unescape, ChannelRef, Span};
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fmt::{Debug, Display};
+use std::str::FromStr;
 
 /// A base IRC message.
 ///
@@ -42,16 +56,172 @@ pub struct IrcMessageRef<'src> {
 #[derive(Clone)]
 struct IrcMessageParts {
   tags: RawTags,
+  /// The unparsed tag section of a message from [`IrcMessageRef::parse_lazy`].
+  lazy_tags: Option<Span>,
   prefix: Option<RawPrefix>,
   command: RawCommand,
   channel: Option<Span>,
   params: Option<Span>,
 }
 
+impl IrcMessageParts {
+  /// The tags of the message, whether they were parsed eagerly or lazily.
+  #[inline]
+  fn tags<'a, 'src: 'a>(
+    &'a self,
+    src: &'src str,
+  ) -> impl Iterator<Item = (Tag<'src>, &'src str)> + 'a {
+    let lazy = match self.lazy_tags {
+      Some(span) => &src[span],
+      None => "",
+    };
+    let lazy = lazy.split(';').filter_map(|pair| {
+      // same rules as the eager parser: the key ends at the first `=`,
+      // and tags without a `=` or with an empty key are skipped.
+      let (key, value) = pair.split_once('=')?;
+      (!key.is_empty()).then(|| (Tag::parse(key), value))
+    });
+
+    self.tags.iter().map(move |pair| pair.get(src)).chain(lazy)
+  }
+
+  /// The value of the `msg-param-<name>` tag.
+  fn msg_param<'src>(&self, src: &'src str, name: &str) -> Option<&'src str> {
+    self
+      .tags(src)
+      .find(|(tag, _)| tag.as_str().strip_prefix("msg-param-") == Some(name))
+      .map(|(_, value)| value)
+  }
+}
+
+/// Reusable buffers for [`IrcMessageRef::parse_into`].
+///
+/// Parsing a message allocates space for its tags.
+/// When parsing many messages in a loop, an arena lets each message reuse
+/// the space of a previous one, which was given back via [`ParseArena::reclaim`].
+///
+/// With the `inline-tags` feature, typical messages store their tags inline anyway,
+/// so an arena mostly pays off without that feature.
+#[derive(Default)]
+pub struct ParseArena {
+  tags: RawTags,
+}
+
+impl ParseArena {
+  /// Create an empty arena.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Take back the buffers of a `message` which is no longer needed.
+  ///
+  /// The larger of the two buffers is kept.
+  pub fn reclaim(&mut self, message: IrcMessageRef<'_>) {
+    let mut tags = message.parts.tags;
+    if tags.capacity() > self.tags.capacity() {
+      tags.clear();
+      self.tags = tags;
+    }
+  }
+}
+
 impl<'src> IrcMessageRef<'src> {
   /// Parse a single Twitch IRC message.
+  ///
+  /// This never panics. Any input which is not a valid message results in [`None`].
+  /// The parser is fuzzed, see the `fuzz` directory.
   pub fn parse(src: &'src str) -> Option<Self> {
-    Self::parse_inner(src, Whitelist::<16, _>(whitelist_insert_all))
+    Self::parse_detailed(src).ok()
+  }
+
+  /// Parse a single Twitch IRC message, and report why it is invalid if it is.
+  ///
+  /// This accepts exactly the same messages as [`IrcMessageRef::parse`].
+  ///
+  /// ```rust
+  /// use tmi::{IrcMessageRef, ParseError};
+  ///
+  /// let err = IrcMessageRef::parse_detailed("@id=1;mod=0").unwrap_err();
+  /// assert_eq!(err, ParseError::UnterminatedTags(11));
+  /// ```
+  pub fn parse_detailed(src: &'src str) -> Result<Self, ParseError> {
+    Self::parse_inner(
+      src,
+      Whitelist::<DEFAULT_TAG_CAPACITY, _>(whitelist_insert_all),
+      RawTags::new(),
+    )
+  }
+
+  /// Parse a single Twitch IRC message, reserving space for `IC` tags up front.
+  ///
+  /// [`IrcMessageRef::parse`] reserves [`DEFAULT_TAG_CAPACITY`] tags, which is enough
+  /// for most messages. Tag-heavy messages such as `USERNOTICE` may have more than that,
+  /// in which case a larger `IC` avoids growing the tag buffer while parsing.
+  ///
+  /// ```rust
+  /// use tmi::IrcMessageRef;
+  ///
+  /// let src = "@badges=;color=;id=1 :forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo";
+  /// let message = IrcMessageRef::parse_with_capacity::<32>(src).unwrap();
+  /// assert_eq!(message.tag(tmi::Tag::Id), Some("1"));
+  /// ```
+  pub fn parse_with_capacity<const IC: usize>(src: &'src str) -> Option<Self> {
+    Self::parse_inner(
+      src,
+      Whitelist::<IC, _>(whitelist_insert_all),
+      RawTags::new(),
+    )
+    .ok()
+  }
+
+  /// Parse a single Twitch IRC message, without parsing its tags up front.
+  ///
+  /// Instead, the tags are scanned each time they are accessed, for example
+  /// by [`IrcMessageRef::tag`]. A lookup stops at the first matching tag.
+  /// This is cheaper than [`IrcMessageRef::parse`] when only one or two tags are read,
+  /// but more expensive when many are, as each lookup scans the tags again.
+  ///
+  /// Apart from that, the message behaves exactly like one from [`IrcMessageRef::parse`].
+  ///
+  /// ```rust
+  /// use tmi::{IrcMessageRef, Tag};
+  ///
+  /// let src = "@badges=;color=;id=1 :forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo";
+  /// let message = IrcMessageRef::parse_lazy(src).unwrap();
+  /// assert_eq!(message.tag(Tag::Id), Some("1"));
+  /// ```
+  pub fn parse_lazy(src: &'src str) -> Option<Self> {
+    let line = src.trim_end_matches(['\r', '\n']);
+    let mut pos = 0usize;
+
+    let lazy_tags = skip_tags(line, &mut pos);
+    Self::parse_rest(src, line, pos, RawTags::new(), lazy_tags).ok()
+  }
+
+  /// Parse a single Twitch IRC message, reusing the buffers in `arena`.
+  ///
+  /// The message borrows from `src`, and takes the buffers out of `arena`.
+  /// Give them back with [`ParseArena::reclaim`] once you're done with the message,
+  /// so that the next call doesn't have to allocate.
+  ///
+  /// ```rust
+  /// use tmi::{IrcMessageRef, ParseArena};
+  ///
+  /// let mut arena = ParseArena::new();
+  /// for line in ["@id=1 :tmi.twitch.tv PING", "@id=2 :tmi.twitch.tv PING"] {
+  ///   let message = IrcMessageRef::parse_into(line, &mut arena).unwrap();
+  ///   println!("{:?}", message.tag(tmi::Tag::Id));
+  ///   arena.reclaim(message);
+  /// }
+  /// ```
+  pub fn parse_into(src: &'src str, arena: &mut ParseArena) -> Option<Self> {
+    let tags = std::mem::take(&mut arena.tags);
+    Self::parse_inner(
+      src,
+      Whitelist::<DEFAULT_TAG_CAPACITY, _>(whitelist_insert_all),
+      tags,
+    )
+    .ok()
   }
 
   /// Parse a single Twitch IRC message with a tag whitelist.
@@ -69,26 +239,69 @@ impl<'src> IrcMessageRef<'src> {
   where
     F: Fn(&str, &mut RawTags, Span, Span),
   {
-    Self::parse_inner(src, whitelist)
+    Self::parse_inner(src, whitelist, RawTags::new()).ok()
+  }
+
+  /// Parse a buffer which may contain multiple Twitch IRC messages.
+  ///
+  /// The messages may be separated by either `\r\n` or `\n`. Empty lines are skipped.
+  /// The final message does not have to be terminated.
+  ///
+  /// Each message borrows from `src`.
+  ///
+  /// ```rust
+  /// let buffer = ":tmi.twitch.tv PING\r\n:tmi.twitch.tv PONG\r\n";
+  /// for message in tmi::IrcMessageRef::parse_many(buffer) {
+  ///   println!("{:?}", message.unwrap().command());
+  /// }
+  /// ```
+  pub fn parse_many(src: &'src str) -> impl Iterator<Item = Result<Self, ParseError>> + 'src {
+    src
+      .split('\n')
+      .map(|line| line.strip_suffix('\r').unwrap_or(line))
+      .filter(|line| !line.is_empty())
+      .map(Self::parse_detailed)
   }
 
   #[inline(always)]
-  fn parse_inner<const IC: usize, F>(src: &'src str, whitelist: Whitelist<IC, F>) -> Option<Self>
+  fn parse_inner<const IC: usize, F>(
+    src: &'src str,
+    whitelist: Whitelist<IC, F>,
+    tags: RawTags,
+  ) -> Result<Self, ParseError>
   where
     F: Fn(&str, &mut RawTags, Span, Span),
   {
+    // a trailing line terminator is not part of the message, and an empty line
+    // (or one made up only of a terminator) is not a message at all.
+    // spans into `line` are also valid spans into `src`.
+    let line = src.trim_end_matches(['\r', '\n']);
     let mut pos = 0usize;
 
-    let tags = parse_tags(src, &mut pos, &whitelist);
-    let prefix = parse_prefix(src, &mut pos);
-    let command = parse_command(src, &mut pos)?;
-    let channel = parse_channel(src, &mut pos);
-    let params = parse_params(src, &pos);
+    let tags = parse_tags(line, &mut pos, &whitelist, tags);
+    Self::parse_rest(src, line, pos, tags, None)
+  }
 
-    Some(Self {
+  #[inline(always)]
+  fn parse_rest(
+    src: &'src str,
+    line: &str,
+    mut pos: usize,
+    tags: RawTags,
+    lazy_tags: Option<Span>,
+  ) -> Result<Self, ParseError> {
+    let prefix = parse_prefix_checked(line, &mut pos)?;
+    let Some(command) = parse_command(line, &mut pos) else {
+      return Err(missing_command(line, pos));
+    };
+    let channel = parse_channel(line, &mut pos);
+    let params = parse_params(line, &pos);
+
+    Ok(Self {
       src,
       parts: IrcMessageParts {
         tags,
+        lazy_tags,
         prefix,
         command,
         channel,
@@ -103,8 +316,32 @@ impl<'src> IrcMessageRef<'src> {
   }
 
   /// Get an iterator over the message [`Tag`]s.
+  ///
+  /// The tags are yielded in the order in which they appear in the message.
+  ///
+  /// Iterating does not allocate. Tags are located once during parsing,
+  /// and both the [`Tag`] and the value borrow from the source string.
+  /// Values are yielded as they appear on the wire, see [`unescape`] to unescape them.
   pub fn tags(&self) -> impl Iterator<Item = (Tag<'src>, &'src str)> + '_ {
-    self.parts.tags.iter().map(|pair| pair.get(self.src))
+    self.parts.tags(self.src)
+  }
+
+  /// Get an iterator over the message tags as `(name, value)` pairs.
+  ///
+  /// This is [`IrcMessageRef::tags`] without the [`Tag`] enum,
+  /// which is convenient when handling known and unknown tags the same way.
+  /// It doesn't allocate either.
+  ///
+  /// ```rust
+  /// let src = "@foo-bar=baz;msg-id=highlighted-message :a!a@a.tmi.twitch.tv PRIVMSG #a :b";
+  /// let message = tmi::IrcMessageRef::parse(src).unwrap();
+  /// assert_eq!(
+  ///   message.tags_raw().collect::<Vec<_>>(),
+  ///   [("foo-bar", "baz"), ("msg-id", "highlighted-message")]
+  /// );
+  /// ```
+  pub fn tags_raw(&self) -> impl Iterator<Item = (&'src str, &'src str)> + '_ {
+    self.tags().map(|(tag, value)| (tag.as_str(), value))
   }
 
   /// Get the message [`Prefix`].
@@ -112,6 +349,17 @@ impl<'src> IrcMessageRef<'src> {
     self.parts.prefix.map(|prefix| prefix.get(self.src))
   }
 
+  /// Get the raw message prefix, without the leading `:`.
+  ///
+  /// ```rust
+  /// let src = ":forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo";
+  /// let message = tmi::IrcMessageRef::parse(src).unwrap();
+  /// assert_eq!(message.prefix_str(), Some("forsen!forsen@forsen.tmi.twitch.tv"));
+  /// ```
+  pub fn prefix_str(&self) -> Option<&'src str> {
+    self.parts.prefix.map(|prefix| &self.src[prefix.span()])
+  }
+
   /// Get the message [`Command`].
   pub fn command(&self) -> Command<'src> {
     self.parts.command.get(self.src)
@@ -133,6 +381,29 @@ impl<'src> IrcMessageRef<'src> {
     self.parts.params.map(|span| &self.src[span])
   }
 
+  /// Get an iterator over the individual message params.
+  ///
+  /// Unlike [`IrcMessageRef::params`], this includes the [channel][`IrcMessageRef::channel`].
+  /// Params are separated by spaces, except for the last one, which is prefixed by `:`
+  /// and may contain spaces.
+  ///
+  /// ```rust
+  /// let msg = tmi::IrcMessageRef::parse(":tmi.twitch.tv 353 justinfan = #forsen :forsen pajlada")
+  ///   .unwrap();
+  /// let params: Vec<_> = msg.params_iter().collect();
+  /// assert_eq!(params, ["justinfan", "=", "#forsen", "forsen pajlada"]);
+  /// ```
+  pub fn params_iter(&self) -> impl Iterator<Item = &'src str> + '_ {
+    let channel = self.channel().map(|channel| channel.as_str());
+    let params = self.params().unwrap_or_default();
+    channel.into_iter().chain(split_params(params))
+  }
+
+  /// Get the `n`-th message param, see [`IrcMessageRef::params_iter`].
+  pub fn param(&self, n: usize) -> Option<&'src str> {
+    self.params_iter().nth(n)
+  }
+
   /// Retrieve the value of `tag`.
   ///
   /// `tag` can provided as:
@@ -153,24 +424,65 @@ impl<'src> IrcMessageRef<'src> {
     let tag = tag.into();
     self
       .parts
-      .tags
-      .iter()
-      .find(|RawTagPair(key, _)| key.get(self.src) == tag)
-      .map(|RawTagPair(_, value)| &self.src[*value])
+      .tags(self.src)
+      .find(|(key, _)| *key == tag)
+      .map(|(_, value)| value)
+  }
+
+  /// Retrieve the value of a tag, parsed as an unsigned integer.
+  ///
+  /// Returns [`None`] if the tag is missing, or its value is not a number.
+  ///
+  /// ```rust
+  /// let src = "@bits=100;mod=1;display-name=Riot\\sGames :a!a@a.tmi.twitch.tv PRIVMSG #a :cheer100";
+  /// let message = tmi::IrcMessageRef::parse(src).unwrap();
+  /// assert_eq!(message.tag_u64(tmi::Tag::Bits), Some(100));
+  /// assert_eq!(message.tag_bool(tmi::Tag::Mod), Some(true));
+  /// assert_eq!(message.tag_unescaped(tmi::Tag::DisplayName).as_deref(), Some("Riot Games"));
+  /// ```
+  pub fn tag_u64<'a>(&self, tag: impl Into<Tag<'a>>) -> Option<u64> {
+    self.tag(tag)?.parse().ok()
+  }
+
+  /// Retrieve the value of a tag, parsed as a boolean.
+  ///
+  /// Twitch sends booleans as `1` and `0`, and any other number is also treated
+  /// as `true`. Returns [`None`] if the tag is missing, or its value is not a number.
+  pub fn tag_bool<'a>(&self, tag: impl Into<Tag<'a>>) -> Option<bool> {
+    parse_bool(self.tag(tag)?)
+  }
+
+  /// Retrieve the [unescaped][`maybe_unescape`] value of a tag.
+  pub fn tag_unescaped<'a>(&self, tag: impl Into<Tag<'a>>) -> Option<Cow<'src, str>> {
+    self.tag(tag).map(maybe_unescape)
+  }
+
+  /// Retrieve the value of the `msg-param-<name>` tag.
+  ///
+  /// This gives access to `msg-param-*` tags which are not modeled by the typed messages.
+  ///
+  /// ```rust
+  /// let src = "@msg-id=resub;msg-param-cumulative-months=2 :tmi.twitch.tv USERNOTICE #xqcow";
+  /// let message = tmi::IrcMessageRef::parse(src).unwrap();
+  /// assert_eq!(message.msg_param("cumulative-months"), Some("2"));
+  /// ```
+  pub fn msg_param(&self, name: &str) -> Option<&'src str> {
+    self.parts.msg_param(self.src, name)
   }
 
-  /// Returns the contents of the params after the last `:`.
+  /// Get the trailing param of the message, which is the last of the
+  /// [params][`IrcMessageRef::params_iter`] if it is prefixed by `:`.
+  ///
+  /// This is usually the text of the message, e.g. for a `PRIVMSG` or `NOTICE`.
+  /// Returns [`None`] if there is no trailing param.
+  ///
+  /// ```rust
+  /// let msg = tmi::IrcMessageRef::parse(":tmi.twitch.tv NOTICE #forsen :Login unsuccessful")
+  ///   .unwrap();
+  /// assert_eq!(msg.text(), Some("Login unsuccessful"));
+  /// ```
   pub fn text(&self) -> Option<&'src str> {
-    match self.parts.params {
-      Some(params) => {
-        let params = &self.src[params];
-        match params.find(':') {
-          Some(start) => Some(&params[start + 1..]),
-          None => None,
-        }
-      }
-      None => None,
-    }
+    split_trailing(self.params()?).1
   }
 }
 
@@ -196,12 +508,74 @@ pub struct IrcMessage {
 
 impl IrcMessage {
   /// Parse a single Twitch IRC message.
+  ///
+  /// Like [`IrcMessageRef::parse`], this never panics.
   pub fn parse(src: impl ToString) -> Option<Self> {
     let src = src.to_string();
-    let parts = IrcMessageRef::parse_inner(&src, Whitelist::<16, _>(whitelist_insert_all))?.parts;
+    Self::parse_detailed(src).ok()
+  }
+
+  /// Parse a single Twitch IRC message, and report why it is invalid if it is.
+  ///
+  /// See [`IrcMessageRef::parse_detailed`].
+  pub fn parse_detailed(src: impl ToString) -> Result<Self, ParseError> {
+    let src = src.to_string();
+    let parts = IrcMessageRef::parse_inner(
+      &src,
+      Whitelist::<DEFAULT_TAG_CAPACITY, _>(whitelist_insert_all),
+      RawTags::new(),
+    )?
+    .parts;
+    Ok(IrcMessage { src, parts })
+  }
+
+  /// Parse a single Twitch IRC message, reserving space for `IC` tags up front.
+  ///
+  /// See [`IrcMessageRef::parse_with_capacity`].
+  pub fn parse_with_capacity<const IC: usize>(src: impl ToString) -> Option<Self> {
+    let src = src.to_string();
+    let parts = IrcMessageRef::parse_inner(
+      &src,
+      Whitelist::<IC, _>(whitelist_insert_all),
+      RawTags::new(),
+    )
+    .ok()?
+    .parts;
+    Some(IrcMessage { src, parts })
+  }
+
+  /// Parse a single Twitch IRC message, without parsing its tags up front.
+  ///
+  /// See [`IrcMessageRef::parse_lazy`].
+  pub fn parse_lazy(src: impl ToString) -> Option<Self> {
+    let src = src.to_string();
+    let parts = IrcMessageRef::parse_lazy(&src)?.parts;
     Some(IrcMessage { src, parts })
   }
 
+  /// Parse a single Twitch IRC message from raw bytes.
+  ///
+  /// The message is rejected if it is not valid UTF-8.
+  ///
+  /// ```rust
+  /// use tmi::{Command, IrcMessage};
+  ///
+  /// let msg = IrcMessage::parse_bytes(b":tmi.twitch.tv PRIVMSG #forsen :yo").unwrap();
+  /// assert_eq!(msg.command(), Command::Privmsg);
+  /// assert!(IrcMessage::parse_bytes(b":tmi.twitch.tv PRIVMSG #forsen :\xFF").is_none());
+  /// ```
+  pub fn parse_bytes(src: &[u8]) -> Option<Self> {
+    Self::parse_bytes_detailed(src).ok()
+  }
+
+  /// Parse a single Twitch IRC message from raw bytes, and report why it is invalid if it is.
+  ///
+  /// See [`IrcMessage::parse_bytes`] and [`IrcMessageRef::parse_detailed`].
+  pub fn parse_bytes_detailed(src: &[u8]) -> Result<Self, ParseError> {
+    let src = std::str::from_utf8(src).map_err(|e| ParseError::InvalidUtf8(e.valid_up_to()))?;
+    Self::parse_detailed(src)
+  }
+
   /// Parse a single Twitch IRC message with a tag whitelist.
   ///
   /// ```rust,ignore
@@ -218,18 +592,65 @@ impl IrcMessage {
     F: Fn(&str, &mut RawTags, Span, Span),
   {
     let src = src.to_string();
-    let parts = IrcMessageRef::parse_inner(&src, whitelist)?.parts;
+    let parts = IrcMessageRef::parse_inner(&src, whitelist, RawTags::new())
+      .ok()?
+      .parts;
     Some(IrcMessage { src, parts })
   }
 
+  /// Estimate the number of tags in `src` without parsing it.
+  ///
+  /// This only counts the `;` separators in the tag list, so it is much cheaper
+  /// than a full parse. The estimate is exact for well-formed messages.
+  ///
+  /// ```rust
+  /// let src = "@badges=;color=;id=1 :forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo";
+  /// assert_eq!(tmi::IrcMessage::estimate_tag_count(src), 3);
+  /// ```
+  pub fn estimate_tag_count(src: &str) -> usize {
+    count_tags(src)
+  }
+
+  /// Read the command of `src` without parsing the rest of the message.
+  ///
+  /// The tags are skipped without being parsed, which makes this much cheaper than
+  /// a full parse when messages are only routed or filtered by their command.
+  /// The result is the same as [`IrcMessage::parse`] followed by [`IrcMessage::command`].
+  ///
+  /// ```rust
+  /// use tmi::{Command, IrcMessage};
+  ///
+  /// let src = "@badges=;color=;id=1 :forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo";
+  /// assert_eq!(IrcMessage::peek_command(src), Some(Command::Privmsg));
+  /// ```
+  pub fn peek_command(src: &str) -> Option<Command<'_>> {
+    let line = src.trim_end_matches(['\r', '\n']);
+    let mut pos = 0usize;
+
+    skip_tags(line, &mut pos);
+    parse_prefix_checked(line, &mut pos).ok()?;
+    let command = parse_command(line, &mut pos)?;
+
+    Some(command.get(line))
+  }
+
   /// Get the string from which this message was parsed.
   pub fn raw(&self) -> &str {
     &self.src
   }
 
   /// Get an iterator over the message [`Tag`]s.
+  ///
+  /// See [`IrcMessageRef::tags`].
   pub fn tags(&self) -> impl Iterator<Item = (Tag<'_>, &'_ str)> + '_ {
-    self.parts.tags.iter().map(|pair| pair.get(&self.src))
+    self.parts.tags(&self.src)
+  }
+
+  /// Get an iterator over the message tags as `(name, value)` pairs.
+  ///
+  /// See [`IrcMessageRef::tags_raw`].
+  pub fn tags_raw(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+    self.tags().map(|(tag, value)| (tag.as_str(), value))
   }
 
   /// Get the message [`Prefix`].
@@ -237,6 +658,14 @@ impl IrcMessage {
     self.parts.prefix.map(|prefix| prefix.get(&self.src))
   }
 
+  /// Get the raw message prefix, without the leading `:`.
+  pub fn prefix_str(&self) -> Option<&str> {
+    self
+      .parts
+      .prefix
+      .map(|prefix| &self.src.as_str()[prefix.span()])
+  }
+
   /// Get the message [`Command`].
   pub fn command(&self) -> Command<'_> {
     self.parts.command.get(&self.src)
@@ -254,6 +683,20 @@ impl IrcMessage {
     self.parts.params.map(|span| &self.src.as_str()[span])
   }
 
+  /// Get an iterator over the individual message params.
+  ///
+  /// See [`IrcMessageRef::params_iter`].
+  pub fn params_iter(&self) -> impl Iterator<Item = &str> + '_ {
+    let channel = self.channel();
+    let params = self.params().unwrap_or_default();
+    channel.into_iter().chain(split_params(params))
+  }
+
+  /// Get the `n`-th message param, see [`IrcMessageRef::params_iter`].
+  pub fn param(&self, n: usize) -> Option<&str> {
+    self.params_iter().nth(n)
+  }
+
   /// Retrieve the value of `tag`.
   ///
   /// `tag` can provided as:
@@ -274,21 +717,42 @@ impl IrcMessage {
     let tag = tag.into();
     self
       .parts
-      .tags
-      .iter()
-      .find(|RawTagPair(key, _)| key.get(&self.src) == tag)
-      .map(|RawTagPair(_, value)| &self.src.as_str()[*value])
+      .tags(&self.src)
+      .find(|(key, _)| *key == tag)
+      .map(|(_, value)| value)
+  }
+
+  /// Retrieve the value of a tag, parsed as an unsigned integer.
+  ///
+  /// See [`IrcMessageRef::tag_u64`].
+  pub fn tag_u64<'a>(&self, tag: impl Into<Tag<'a>>) -> Option<u64> {
+    self.tag(tag)?.parse().ok()
+  }
+
+  /// Retrieve the value of a tag, parsed as a boolean.
+  ///
+  /// See [`IrcMessageRef::tag_bool`].
+  pub fn tag_bool<'a>(&self, tag: impl Into<Tag<'a>>) -> Option<bool> {
+    parse_bool(self.tag(tag)?)
   }
 
-  /// Returns the contents of the params after the last `:`.
+  /// Retrieve the [unescaped][`maybe_unescape`] value of a tag.
+  pub fn tag_unescaped<'a>(&self, tag: impl Into<Tag<'a>>) -> Option<Cow<'_, str>> {
+    self.tag(tag).map(maybe_unescape)
+  }
+
+  /// Retrieve the value of the `msg-param-<name>` tag.
+  ///
+  /// See [`IrcMessageRef::msg_param`].
+  pub fn msg_param(&self, name: &str) -> Option<&str> {
+    self.parts.msg_param(&self.src, name)
+  }
+
+  /// Get the trailing param of the message.
+  ///
+  /// See [`IrcMessageRef::text`].
   pub fn text(&self) -> Option<&str> {
-    match self.params() {
-      Some(params) => match params.find(':') {
-        Some(start) => Some(&params[start + 1..]),
-        None => None,
-      },
-      None => None,
-    }
+    split_trailing(self.params()?).1
   }
 }
 
@@ -387,6 +851,121 @@ pub fn unescape(value: &str) -> String {
   out
 }
 
+/// Parse a boolean tag value, which Twitch sends as a number, usually `1` or `0`.
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+  value.parse::<u8>().ok().map(|n| n > 0)
+}
+
+/// Escape a `value` according to the escaped characters that Twitch IRC supports.
+///
+/// This is the inverse of [`unescape`], and may be used to build tag values.
+/// If nothing needs to be escaped, then `value` is returned as-is.
+///
+/// Like Twitch, this replaces `,` with `⸝`, so the elements of a comma-separated
+/// value such as `badges` must be escaped separately. Because [`unescape`] turns
+/// every `⸝` into a `,`, a `⸝` in `value` does not survive the round trip.
+pub fn escape(value: &str) -> Cow<'_, str> {
+  let Some(start) = value.find(['\\', ' ', ';', '\r', '\n', ',']) else {
+    return Cow::Borrowed(value);
+  };
+  let mut out = String::with_capacity(value.len() + 8);
+  out.push_str(&value[..start]);
+  for char in value[start..].chars() {
+    match char {
+      '\\' => out.push_str("\\\\"),
+      ' ' => out.push_str("\\s"),
+      ';' => out.push_str("\\:"),
+      '\r' => out.push_str("\\r"),
+      '\n' => out.push_str("\\n"),
+      ',' => out.push('⸝'),
+      c => out.push(c),
+    }
+  }
+  Cow::Owned(out)
+}
+
+/// Failed to parse an IRC message.
+///
+/// See [`IrcMessageRef::parse_detailed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+  /// The message is empty, or only contains a line terminator.
+  Empty,
+
+  /// The tags are not followed by a space, so the message ends within the tags.
+  ///
+  /// The offset is the end of the message.
+  UnterminatedTags(usize),
+
+  /// The prefix at this byte offset is malformed, or is not followed by a command.
+  MalformedPrefix(usize),
+
+  /// The command at this byte offset is missing.
+  MissingCommand(usize),
+
+  /// The message is not valid UTF-8 at this byte offset.
+  ///
+  /// Only returned by [`IrcMessage::parse_bytes_detailed`].
+  InvalidUtf8(usize),
+}
+
+impl ParseError {
+  /// The byte offset in the message at which parsing failed.
+  pub fn offset(&self) -> usize {
+    match self {
+      ParseError::Empty => 0,
+      ParseError::UnterminatedTags(offset)
+      | ParseError::MalformedPrefix(offset)
+      | ParseError::MissingCommand(offset)
+      | ParseError::InvalidUtf8(offset) => *offset,
+    }
+  }
+}
+
+impl Display for ParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ParseError::Empty => f.write_str("failed to parse irc message: message is empty"),
+      ParseError::UnterminatedTags(offset) => write!(
+        f,
+        "failed to parse irc message: unterminated tags at byte {offset}"
+      ),
+      ParseError::MalformedPrefix(offset) => write!(
+        f,
+        "failed to parse irc message: malformed prefix at byte {offset}"
+      ),
+      ParseError::MissingCommand(offset) => write!(
+        f,
+        "failed to parse irc message: missing command at byte {offset}"
+      ),
+      ParseError::InvalidUtf8(offset) => write!(
+        f,
+        "failed to parse irc message: invalid utf-8 at byte {offset}"
+      ),
+    }
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Name of the parser backend which was compiled in.
+///
+/// This is one of `"sse2"`, `"neon"` or `"wasm-simd128"` if the `simd` feature is enabled,
+/// or `"scalar"` otherwise. The backend is selected at compile time from the target
+/// features, so this does not change at runtime.
+///
+/// ```rust
+/// println!("parsing messages using {}", tmi::irc::simd_backend());
+/// ```
+pub fn simd_backend() -> &'static str {
+  BACKEND
+}
+
+/// Number of tags reserved up front by [`IrcMessageRef::parse`] and [`IrcMessage::parse`].
+///
+/// Use [`IrcMessageRef::parse_with_capacity`] to reserve a different amount.
+pub const DEFAULT_TAG_CAPACITY: usize = 16;
+
 /// A tag whitelist. Only the allowed tags will be parsed and stored.
 pub struct Whitelist<const IC: usize, F>(F);
 
@@ -406,6 +985,89 @@ where
   }
 }
 
+impl<const IC: usize, F> Whitelist<IC, F>
+where
+  F: Fn(&str, &mut RawTags, Span, Span),
+{
+  /// Borrow the whitelist, so that it may be used to parse more than one message.
+  ///
+  /// ```rust
+  /// let whitelist = tmi::irc::DynWhitelist::from_tags([tmi::Tag::Id]);
+  /// for src in ["@id=1 PING", "@id=2 PING"] {
+  ///   let message = tmi::IrcMessageRef::parse_with_whitelist(src, whitelist.by_ref()).unwrap();
+  ///   assert_eq!(message.tags().count(), 1);
+  /// }
+  /// ```
+  pub fn by_ref(&self) -> Whitelist<IC, &F> {
+    Whitelist(&self.0)
+  }
+
+  /// Combine two whitelists into one which allows the tags allowed by either of them.
+  ///
+  /// The result reserves space for `IC` tags, like `self`.
+  ///
+  /// ```rust
+  /// let privmsg = tmi::whitelist!(Color, DisplayName);
+  /// let usernotice = tmi::whitelist!(DisplayName, MsgId);
+  /// let message = tmi::IrcMessageRef::parse_with_whitelist(
+  ///   "@color=#FF0000;display-name=Retoon;id=1;msg-id=sub PING",
+  ///   privmsg.union(usernotice),
+  /// )
+  /// .unwrap();
+  /// assert_eq!(message.tags().count(), 3);
+  /// ```
+  pub fn union<const IC2: usize, G>(
+    self,
+    other: Whitelist<IC2, G>,
+  ) -> Whitelist<IC, impl Fn(&str, &mut RawTags, Span, Span)>
+  where
+    G: Fn(&str, &mut RawTags, Span, Span),
+  {
+    let (a, b) = (self.0, other.0);
+    Whitelist(
+      move |src: &str, map: &mut RawTags, tag: Span, value: Span| {
+        // only ask `b` if `a` did not already insert the tag
+        let len = map.len();
+        a(src, map, tag, value);
+        if map.len() == len {
+          b(src, map, tag, value);
+        }
+      },
+    )
+  }
+}
+
+/// A tag whitelist which is built at runtime.
+///
+/// Unlike [`whitelist!`], this may also contain unknown tags.
+///
+/// ```rust
+/// use tmi::irc::DynWhitelist;
+/// use tmi::Tag;
+///
+/// let tags: Vec<Tag> = vec![Tag::Badges, Tag::Color, Tag::DisplayName];
+/// let whitelist = DynWhitelist::from_tags(tags);
+/// ```
+pub type DynWhitelist = Whitelist<DEFAULT_TAG_CAPACITY, DynWhitelistFn>;
+
+#[doc(hidden)]
+pub type DynWhitelistFn = Box<dyn Fn(&str, &mut RawTags, Span, Span) + Send + Sync>;
+
+impl<const IC: usize> Whitelist<IC, DynWhitelistFn> {
+  /// Create a whitelist which allows the given `tags`.
+  pub fn from_tags<'a>(tags: impl IntoIterator<Item = Tag<'a>>) -> Self {
+    let tags = tags
+      .into_iter()
+      .map(|tag| tag.as_str().to_owned())
+      .collect::<HashSet<_>>();
+    Whitelist(Box::new(move |src, map, tag, value| {
+      if tags.contains(&src[tag]) {
+        map.push(RawTagPair(RawTag::parse(src, tag), value));
+      }
+    }))
+  }
+}
+
 #[inline(always)]
 fn whitelist_insert_all(src: &str, map: &mut RawTags, tag: Span, value: Span) {
   map.push(RawTagPair(RawTag::parse(src, tag), value));
@@ -415,9 +1077,25 @@ fn whitelist_insert_all(src: &str, map: &mut RawTags, tag: Span, value: Span) {
 #[derive(Clone)]
 pub struct RawTagPair(pub RawTag, pub Span);
 
+/// The tags of a message, in the order in which they appear in the message.
+///
+/// This must stay a sequence, because [`IrcMessageRef::tags`] guarantees the order.
 #[doc(hidden)]
+#[cfg(not(feature = "inline-tags"))]
 pub type RawTags = Vec<RawTagPair>;
 
+/// The tags of a message, in the order in which they appear in the message.
+///
+/// Up to `INLINE_TAGS` tags are stored without allocating,
+/// which covers a typical `PRIVMSG`.
+#[doc(hidden)]
+#[cfg(feature = "inline-tags")]
+pub type RawTags = smallvec::SmallVec<[RawTagPair; INLINE_TAGS]>;
+
+/// Number of tags stored inline by [`RawTags`].
+#[cfg(feature = "inline-tags")]
+const INLINE_TAGS: usize = 24;
+
 impl RawTagPair {
   #[doc(hidden)]
   #[inline]
@@ -442,6 +1120,7 @@ enum RawCommand {
   RoomState,
   UserNotice,
   UserState,
+  HostTarget,
   Capability,
   RplWelcome,
   RplYourHost,
@@ -452,6 +1131,7 @@ enum RawCommand {
   RplMotd,
   RplMotdStart,
   RplEndOfMotd,
+  Numeric(u16, Span),
   Other(Span),
 }
 
@@ -473,6 +1153,7 @@ impl RawCommand {
       RawCommand::RoomState => Command::RoomState,
       RawCommand::UserNotice => Command::UserNotice,
       RawCommand::UserState => Command::UserState,
+      RawCommand::HostTarget => Command::HostTarget,
       RawCommand::Capability => Command::Capability,
       RawCommand::RplWelcome => Command::RplWelcome,
       RawCommand::RplYourHost => Command::RplYourHost,
@@ -483,6 +1164,7 @@ impl RawCommand {
       RawCommand::RplMotd => Command::RplMotd,
       RawCommand::RplMotdStart => Command::RplMotdStart,
       RawCommand::RplEndOfMotd => Command::RplEndOfMotd,
+      RawCommand::Numeric(code, span) => Command::Numeric(ReplyCode(*code), &src[*span]),
       RawCommand::Other(span) => Command::Other(&src[*span]),
     }
   }
@@ -519,6 +1201,8 @@ pub enum Command<'src> {
   UserNotice,
   /// Identifies a user's chat settings or properties
   UserState,
+  /// Starts or stops hosting another channel
+  HostTarget,
   /// Requesting an IRC capability
   Capability,
   // Numeric commands
@@ -540,6 +1224,12 @@ pub enum Command<'src> {
   RplMotdStart,
   /// `376`
   RplEndOfMotd,
+  /// Any other numeric reply, such as `421`.
+  ///
+  /// Numeric replies with a dedicated variant, such as [`Command::RplWelcome`],
+  /// are never represented by this variant. The string is the reply code as it was sent,
+  /// such as `"421"`. Use [`Command::from_raw`] to create one.
+  Numeric(ReplyCode, &'src str),
   /// Unknown command
   Other(&'src str),
 }
@@ -551,6 +1241,26 @@ impl<'src> Display for Command<'src> {
 }
 
 impl<'src> Command<'src> {
+  /// Get the known command named `cmd`.
+  ///
+  /// Returns [`None`] if `cmd` would parse as [`Command::Other`].
+  /// Three digit numeric replies are always known.
+  /// Use [`Command::from`] to fall back to [`Command::Other`] instead.
+  ///
+  /// ```rust
+  /// use tmi::Command;
+  ///
+  /// assert_eq!(Command::from_raw("001"), Some(Command::RplWelcome));
+  /// assert_eq!(Command::from_raw("421").unwrap().numeric(), Some(421));
+  /// assert_eq!(Command::from_raw("4210"), None);
+  /// ```
+  pub fn from_raw(cmd: &'src str) -> Option<Self> {
+    match known_command(cmd) {
+      Some(known) => Some(known.get("")),
+      None => parse_numeric(cmd).map(|code| Command::Numeric(ReplyCode(code), cmd)),
+    }
+  }
+
   /// Get the string value of the [`Command`].
   pub fn as_str(&self) -> &'src str {
     use Command::*;
@@ -569,6 +1279,7 @@ impl<'src> Command<'src> {
       RoomState => "ROOMSTATE",
       UserNotice => "USERNOTICE",
       UserState => "USERSTATE",
+      HostTarget => "HOSTTARGET",
       Capability => "CAP",
       RplWelcome => "001",
       RplYourHost => "002",
@@ -579,13 +1290,48 @@ impl<'src> Command<'src> {
       RplMotd => "372",
       RplMotdStart => "375",
       RplEndOfMotd => "376",
+      Numeric(_, code) => code,
       Other(cmd) => cmd,
     }
   }
+
+  /// Get the reply code of a numeric command.
+  ///
+  /// Numeric commands consist of exactly 3 ASCII digits, such as `001` or `421`.
+  /// Returns [`None`] for any other command, including ones which merely start with digits.
+  pub fn numeric(&self) -> Option<u16> {
+    match self {
+      Command::Numeric(code, _) => Some(code.get()),
+      _ => parse_numeric(self.as_str()),
+    }
+  }
 }
 
-macro_rules! tags_def {
-  (
+/// The code of a numeric reply which has no dedicated [`Command`] variant.
+///
+/// It is always in `0..=999`, see [`Command::Numeric`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReplyCode(u16);
+
+impl ReplyCode {
+  /// The numeric value of the reply code.
+  pub fn get(self) -> u16 {
+    self.0
+  }
+}
+
+/// Parse a numeric command, which consists of exactly 3 ASCII digits.
+fn parse_numeric(cmd: &str) -> Option<u16> {
+  match cmd.as_bytes() {
+    &[a, b, c] if [a, b, c].iter().all(u8::is_ascii_digit) => {
+      Some((a - b'0') as u16 * 100 + (b - b'0') as u16 * 10 + (c - b'0') as u16)
+    }
+    _ => None,
+  }
+}
+
+macro_rules! tags_def {
+  (
     $tag:ident, $raw_tag:ident, $tag_mod:ident;
     $($(#[$meta:meta])* $bytes:literal; $key:literal = $name:ident),*
   ) => {
@@ -617,6 +1363,16 @@ macro_rules! tags_def {
           _ => Self::Unknown(src),
         }
       }
+
+      #[doc = concat!("Parse a known [`", stringify!($tag), "`] from a string.")]
+      ///
+      #[doc = concat!("Unlike [`", stringify!($tag), "::parse`], this returns [`None`] for unknown tags.")]
+      pub fn from_raw(src: &str) -> Option<$tag<'static>> {
+        match src.as_bytes() {
+          $($bytes => Some($tag::$name),)*
+          _ => None,
+        }
+      }
     }
 
     #[doc(hidden)]
@@ -735,9 +1491,84 @@ tags_def! {
   b"msg-param-mass-gift-count"; "msg-param-mass-gift-count" = MsgParamMassGiftCount,
   b"msg-param-gift-month-being-redeemed"; "msg-param-gift-month-being-redeemed" = MsgParamGiftMonthBeingRedeemed,
   b"msg-param-anon-gift"; "msg-param-anon-gift" = MsgParamAnonGift,
-  b"custom-reward-id"; "custom-reward-id" = CustomRewardId
+  b"custom-reward-id"; "custom-reward-id" = CustomRewardId,
+  b"source-room-id"; "source-room-id" = SourceRoomId,
+  b"source-badge-info"; "source-badge-info" = SourceBadgeInfo,
+  b"source-badges"; "source-badges" = SourceBadges,
+  b"source-id"; "source-id" = SourceId,
+  b"pinned-chat-paid-amount"; "pinned-chat-paid-amount" = PinnedChatPaidAmount,
+  b"pinned-chat-paid-currency"; "pinned-chat-paid-currency" = PinnedChatPaidCurrency,
+  b"pinned-chat-paid-exponent"; "pinned-chat-paid-exponent" = PinnedChatPaidExponent,
+  b"pinned-chat-paid-level"; "pinned-chat-paid-level" = PinnedChatPaidLevel,
+  b"pinned-chat-paid-is-system-message"; "pinned-chat-paid-is-system-message" = PinnedChatPaidIsSystemMessage
 }
 
+/// Parses a known [`Command`], see [`Command::from_raw`].
+///
+/// [`Command::Numeric`] borrows its reply code from the string,
+/// so numeric replies without a dedicated variant are only known to [`Command::from_raw`].
+impl FromStr for Command<'static> {
+  type Err = UnknownCommandError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    known_command(s)
+      .map(|cmd| cmd.get(""))
+      .ok_or(UnknownCommandError)
+  }
+}
+
+/// Maps unknown commands to [`Command::Other`].
+impl<'src> From<&'src str> for Command<'src> {
+  fn from(value: &'src str) -> Self {
+    Command::from_raw(value).unwrap_or(Command::Other(value))
+  }
+}
+
+impl<'src> PartialEq<str> for Command<'src> {
+  fn eq(&self, other: &str) -> bool {
+    self.as_str() == other
+  }
+}
+
+impl<'src, 'a> PartialEq<&'a str> for Command<'src> {
+  fn eq(&self, other: &&'a str) -> bool {
+    self.as_str() == *other
+  }
+}
+
+/// The command is not a known variant of [`Command`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownCommandError;
+
+impl Display for UnknownCommandError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("unknown command")
+  }
+}
+
+impl std::error::Error for UnknownCommandError {}
+
+/// Parses a known [`Tag`], see [`Tag::from_raw`].
+impl FromStr for Tag<'static> {
+  type Err = UnknownTagError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Tag::from_raw(s).ok_or(UnknownTagError)
+  }
+}
+
+/// The tag name is not a variant of [`Tag`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownTagError;
+
+impl Display for UnknownTagError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("unknown tag")
+  }
+}
+
+impl std::error::Error for UnknownTagError {}
+
 impl<'src> Display for Tag<'src> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     f.write_str(self.as_str())
@@ -760,6 +1591,15 @@ impl RawPrefix {
       host: &src[self.host],
     }
   }
+
+  /// The span of the whole prefix, `nick!user@host`.
+  fn span(&self) -> Span {
+    let start = self.nick.unwrap_or(self.host).start;
+    Span {
+      start,
+      end: self.host.end,
+    }
+  }
 }
 
 // TODO: have prefix only be two variants: `User` and `Host`
@@ -789,6 +1629,53 @@ impl<'src> std::fmt::Display for Prefix<'src> {
   }
 }
 
+/// `@a=a;b=b;c= <rest>`
+///
+/// Skips the tags without parsing them, and returns the span of the tags after the `@`.
+#[inline(always)]
+fn skip_tags(src: &str, pos: &mut usize) -> Option<Span> {
+  if !src[*pos..].starts_with('@') {
+    return None;
+  }
+
+  // the tags end at the first ` `, tag values never contain one.
+  let start = *pos + 1;
+  let end = src[start..].find(' ').map_or(src.len(), |i| start + i);
+  *pos = (end + 1).min(src.len());
+
+  Some(Span::from(start..end))
+}
+
+/// `:nick!user@host <rest>`, see [`parse_prefix`].
+///
+/// Unlike [`parse_prefix`], a `:` which does not begin a valid prefix is an error,
+/// instead of being read as the command.
+#[inline(always)]
+fn parse_prefix_checked(src: &str, pos: &mut usize) -> Result<Option<RawPrefix>, ParseError> {
+  if !src[*pos..].starts_with(':') {
+    return Ok(None);
+  }
+  match parse_prefix(src, pos) {
+    Some(prefix) => Ok(Some(prefix)),
+    None => Err(ParseError::MalformedPrefix(*pos)),
+  }
+}
+
+/// Why there is no command at `pos`.
+#[cold]
+fn missing_command(line: &str, pos: usize) -> ParseError {
+  if line.is_empty() {
+    return ParseError::Empty;
+  }
+
+  // tag values never contain a ` `, so tags which reach the end
+  // of the line without one were not terminated.
+  if line.starts_with('@') && pos == line.len() && !line.ends_with(' ') {
+    return ParseError::UnterminatedTags(line.len());
+  }
+  ParseError::MissingCommand(pos)
+}
+
 /// `COMMAND <rest>`
 ///
 /// Returns `None` if command is unknown *and* empty
@@ -802,8 +1689,27 @@ fn parse_command(src: &str, pos: &mut usize) -> Option<RawCommand> {
     None => (src.len(), src.len()),
   };
 
-  use RawCommand as C;
   let cmd = match &src[*pos..end] {
+    "" => return None,
+    other => {
+      let span = Span::from(*pos..end);
+      match (known_command(other), parse_numeric(other)) {
+        (Some(known), _) => known,
+        (None, Some(code)) => RawCommand::Numeric(code, span),
+        (None, None) => RawCommand::Other(span),
+      }
+    }
+  };
+
+  *pos = next_pos;
+
+  Some(cmd)
+}
+
+#[inline(always)]
+fn known_command(cmd: &str) -> Option<RawCommand> {
+  use RawCommand as C;
+  Some(match cmd {
     "PING" => C::Ping,
     "PONG" => C::Pong,
     "JOIN" => C::Join,
@@ -818,6 +1724,7 @@ fn parse_command(src: &str, pos: &mut usize) -> Option<RawCommand> {
     "ROOMSTATE" => C::RoomState,
     "USERNOTICE" => C::UserNotice,
     "USERSTATE" => C::UserState,
+    "HOSTTARGET" => C::HostTarget,
     "CAP" => C::Capability,
     "001" => C::RplWelcome,
     "002" => C::RplYourHost,
@@ -828,13 +1735,8 @@ fn parse_command(src: &str, pos: &mut usize) -> Option<RawCommand> {
     "372" => C::RplMotd,
     "375" => C::RplMotdStart,
     "376" => C::RplEndOfMotd,
-    other if !other.is_empty() => C::Other(Span::from(*pos..end)),
     _ => return None,
-  };
-
-  *pos = next_pos;
-
-  Some(cmd)
+  })
 }
 
 /// #channel <rest>
@@ -860,6 +1762,34 @@ fn parse_channel(src: &str, pos: &mut usize) -> Option<Span> {
   }
 }
 
+/// Split the raw `params` into individual params.
+///
+/// The last param may be prefixed by `:`, in which case it extends to the end of `params`.
+fn split_params(params: &str) -> impl Iterator<Item = &str> {
+  let (middle, trailing) = split_trailing(params);
+  middle
+    .split(' ')
+    .filter(|param| !param.is_empty())
+    .chain(trailing)
+}
+
+/// Split the raw `params` into the middle params, and the trailing param without its `:` prefix.
+///
+/// Only a `:` at the start of a param begins the trailing param.
+fn split_trailing(params: &str) -> (&str, Option<&str>) {
+  let mut rest = params;
+  loop {
+    rest = rest.trim_start_matches(' ');
+    if let Some(trailing) = rest.strip_prefix(':') {
+      return (&params[..params.len() - rest.len()], Some(trailing));
+    }
+    match rest.split_once(' ') {
+      Some((_, next)) => rest = next,
+      None => return (params, None),
+    }
+  }
+}
+
 #[inline(always)]
 fn parse_params(src: &str, pos: &usize) -> Option<Span> {
   if !src[*pos..].is_empty() {
@@ -886,6 +1816,203 @@ mod tests {
       assert_eq!(&data[pos..], "<rest>");
     }
 
+    #[test]
+    fn estimate_tag_count() {
+      for data in [
+        "PING :tmi.twitch.tv",
+        ":forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo",
+        "@id=1 PRIVMSG #forsen :a;b;c",
+        "@login=test;id=asdf :forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo",
+        "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam",
+      ] {
+        let actual = IrcMessageRef::parse(data).unwrap().tags().count();
+        assert_eq!(IrcMessage::estimate_tag_count(data), actual, "{data:?}");
+      }
+    }
+
+    #[test]
+    fn peek_command() {
+      for data in [
+        "PING :tmi.twitch.tv",
+        ":tmi.twitch.tv PONG tmi.twitch.tv :x",
+        ":forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo",
+        "@id=1 PRIVMSG #forsen :a;b;c",
+        "@login=test;id=asdf :forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo",
+        "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam",
+        "@msg-id=sub :tmi.twitch.tv USERNOTICE #forsen\r\n",
+        ":justinfan12345.tmi.twitch.tv 353 justinfan12345 = #forsen :forsen",
+        ":tmi.twitch.tv CUSTOMCOMMAND #forsen",
+        "@id=1",
+        "@id=1 ",
+        ":tmi.twitch.tv",
+        ":nick!host PING",
+        "",
+        "\r\n",
+      ] {
+        let expected = IrcMessageRef::parse(data).map(|msg| msg.command());
+        assert_eq!(IrcMessage::peek_command(data), expected, "{data:?}");
+      }
+    }
+
+    #[test]
+    fn trailing_line_terminator() {
+      for data in [
+        ":a!a@a.tmi.twitch.tv PRIVMSG #a :hello there\r",
+        ":a!a@a.tmi.twitch.tv PRIVMSG #a :hello there\n",
+        ":a!a@a.tmi.twitch.tv PRIVMSG #a :hello there\r\n",
+      ] {
+        let msg = IrcMessageRef::parse(data).unwrap();
+        assert_eq!(msg.text(), Some("hello there"), "{data:?}");
+        assert_eq!(msg.params(), Some(":hello there"), "{data:?}");
+
+        let msg = IrcMessage::parse(data).unwrap();
+        assert_eq!(msg.text(), Some("hello there"), "{data:?}");
+      }
+
+      let msg = IrcMessageRef::parse("@id=1 :tmi.twitch.tv PING\r").unwrap();
+      assert_eq!(msg.tag(Tag::Id), Some("1"));
+      assert_eq!(msg.command(), Command::Ping);
+    }
+
+    #[test]
+    fn empty_lines() {
+      for data in ["", " ", "  ", "\r\n", "\n", "\r", " \r\n"] {
+        assert!(IrcMessageRef::parse(data).is_none(), "{data:?}");
+        assert!(IrcMessage::parse(data).is_none(), "{data:?}");
+      }
+    }
+
+    #[test]
+    fn truncated_lines() {
+      // these must not panic, regardless of whether they parse
+      for data in [
+        "@",
+        "@ ",
+        "@=",
+        "@;",
+        "@a",
+        "@a=",
+        "@a=b",
+        "@a=b;",
+        ":",
+        ": ",
+        ":a!",
+        ":a@",
+        "@a=b :",
+        "@a=b :c",
+        "#",
+        "PRIVMSG #",
+      ] {
+        let _ = IrcMessageRef::parse(data);
+      }
+    }
+
+    /// Call every accessor, so that lazily computed parts are also checked for panics.
+    fn exercise(data: &str) {
+      let Some(msg) = IrcMessageRef::parse(data) else {
+        return;
+      };
+      for (tag, value) in msg.tags() {
+        let _ = (tag.as_str(), unescape(value));
+      }
+      let _ = (msg.prefix(), msg.prefix_str(), msg.command().numeric());
+      let _ = (msg.channel(), msg.params(), msg.text());
+      #[cfg(feature = "message-types")]
+      let _ = msg.clone().as_typed();
+      let _ = msg.into_owned();
+    }
+
+    const SAMPLES: &[&str] = &[
+      "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam",
+      "@badge-info=;badges=;color=;display-name=테스트계정420;emotes=25:0-4;id=1;mod=0;room-id=1;tmi-sent-ts=1;user-id=1;user-type= :테스트계정420!a@a.tmi.twitch.tv PRIVMSG #pajlada :Kappa 👉 ABC",
+      "@a=é;b=日本;c=\\s\\:x :a!a@a PRIVMSG #é :é",
+      "@msg-id=resub;msg-param-cumulative-months=2;system-msg=a\\sb :tmi.twitch.tv USERNOTICE #xqcow :xqcL",
+      ":tmi.twitch.tv CAP * ACK :twitch.tv/commands twitch.tv/tags",
+      ":justinfan.tmi.twitch.tv 353 justinfan = #forsen :forsen pajlada",
+      "PING :tmi.twitch.tv",
+    ];
+
+    #[test]
+    fn every_prefix_and_suffix() {
+      // truncating at every position also moves the tag separators across SIMD chunk edges
+      for sample in SAMPLES {
+        for (i, _) in sample.char_indices() {
+          exercise(&sample[..i]);
+          exercise(&sample[i..]);
+        }
+      }
+    }
+
+    #[test]
+    fn random_lines() {
+      const ALPHABET: &[&str] = &[
+        "@", ":", ";", "=", " ", "!", "#", ",", "-", "/", "\\", "\r", "\n", "a", "1", "0", "é",
+        "日", "👉", "PRIVMSG", "emotes=", "25:0-4", "badges=", "msg-id=",
+      ];
+
+      // xorshift, so that failures are reproducible without extra dependencies
+      let mut state = 0x2545_f491_4f6c_dd1d_u64;
+      let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+      };
+
+      let mut line = String::new();
+      for _ in 0..20_000 {
+        line.clear();
+        let len = next() % 48;
+        for _ in 0..len {
+          line.push_str(ALPHABET[next() as usize % ALPHABET.len()]);
+        }
+        exercise(&line);
+      }
+    }
+
+    #[test]
+    fn numeric_command() {
+      let cases = [
+        ("001", Command::RplWelcome, Some(1)),
+        ("421", Command::Numeric(ReplyCode(421), "421"), Some(421)),
+        ("007", Command::Numeric(ReplyCode(7), "007"), Some(7)),
+        ("1", Command::Other("1"), None),
+        ("12ab", Command::Other("12ab"), None),
+        ("0012", Command::Other("0012"), None),
+        ("PING", Command::Ping, None),
+      ];
+
+      for (cmd, expected, code) in cases {
+        let data = format!(":tmi.twitch.tv {cmd} :rest");
+        let msg = IrcMessageRef::parse(&data).unwrap();
+        assert_eq!(msg.command(), expected, "{cmd}");
+        assert_eq!(msg.command().numeric(), code, "{cmd}");
+      }
+    }
+
+    #[test]
+    fn numeric_replies() {
+      let msg = IrcMessageRef::parse(":tmi.twitch.tv 001 nick :Welcome, GLHF!").unwrap();
+      assert_eq!(msg.command(), Command::RplWelcome);
+      assert_eq!(msg.command().as_str(), "001");
+      assert_eq!(msg.command().numeric(), Some(1));
+      assert_eq!(msg.text(), Some("Welcome, GLHF!"));
+
+      let msg = IrcMessageRef::parse(":nick.tmi.twitch.tv 353 nick = #forsen :nick forsen pajlada")
+        .unwrap();
+      assert_eq!(msg.command(), Command::RplNames);
+      assert_eq!(msg.command().numeric(), Some(353));
+      assert_eq!(msg.text(), Some("nick forsen pajlada"));
+
+      let msg = IrcMessageRef::parse(":tmi.twitch.tv 421 nick WHO :Unknown command").unwrap();
+      assert_eq!(msg.command(), Command::from_raw("421").unwrap());
+      assert_eq!(msg.command().as_str(), "421");
+      assert_eq!(Command::from_raw("005").unwrap().to_string(), "005");
+      assert_eq!(Command::from_raw("001"), Some(Command::RplWelcome));
+      assert_eq!(Command::from_raw("353"), Some(Command::RplNames));
+      assert_eq!(Command::from_raw("1000"), None);
+    }
+
     #[test]
     fn channel() {
       let data = "#channel <rest>";
@@ -903,6 +2030,66 @@ mod tests {
       assert_eq!(params.get(data), data)
     }
 
+    #[test]
+    fn params_iter() {
+      let msg = IrcMessageRef::parse(
+        ":justinfan12345.tmi.twitch.tv 353 justinfan12345 = #forsen :forsen pajlada randers",
+      )
+      .unwrap();
+      assert_eq!(
+        msg.params_iter().collect::<Vec<_>>(),
+        ["justinfan12345", "=", "#forsen", "forsen pajlada randers"]
+      );
+      assert_eq!(msg.param(2), Some("#forsen"));
+      assert_eq!(msg.param(3), Some("forsen pajlada randers"));
+      assert_eq!(msg.param(4), None);
+
+      let msg = IrcMessageRef::parse(
+        ":forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :hello there :) #pajlada",
+      )
+      .unwrap();
+      assert_eq!(
+        msg.params_iter().collect::<Vec<_>>(),
+        ["#forsen", "hello there :) #pajlada"]
+      );
+
+      let msg = IrcMessage::parse(":tmi.twitch.tv CAP * ACK :").unwrap();
+      assert_eq!(msg.params_iter().collect::<Vec<_>>(), ["*", "ACK", ""]);
+      assert_eq!(msg.param(2), Some(""));
+
+      let msg = IrcMessageRef::parse(":tmi.twitch.tv JOIN #forsen").unwrap();
+      assert_eq!(msg.params_iter().collect::<Vec<_>>(), ["#forsen"]);
+      let msg = IrcMessageRef::parse("PING").unwrap();
+      assert_eq!(msg.params_iter().count(), 0);
+    }
+
+    #[test]
+    fn notice_text() {
+      let data = "@msg-id=msg_banned :tmi.twitch.tv NOTICE #forsen :You are permanently banned from talking in forsen.";
+
+      let msg = IrcMessageRef::parse(data).unwrap();
+      assert_eq!(msg.command(), Command::Notice);
+      assert_eq!(msg.channel().map(|c| c.as_str()), Some("#forsen"));
+      assert_eq!(
+        msg.text(),
+        Some("You are permanently banned from talking in forsen.")
+      );
+      assert_eq!(msg.text(), msg.params_iter().last());
+      assert_eq!(msg.clone().into_owned().text(), msg.text());
+
+      let msg = IrcMessageRef::parse(":tmi.twitch.tv JOIN #forsen").unwrap();
+      assert_eq!(msg.text(), None);
+
+      // a `:` inside a middle param does not start the trailing param
+      let msg = IrcMessageRef::parse(":a!a@a.tmi.twitch.tv PRIVMSG #a b:c :d e").unwrap();
+      assert_eq!(msg.text(), Some("d e"));
+      assert_eq!(msg.params_iter().collect::<Vec<_>>(), ["#a", "b:c", "d e"]);
+      assert_eq!(msg.clone().into_owned().text(), msg.text());
+
+      let msg = IrcMessageRef::parse(":a!a@a.tmi.twitch.tv PRIVMSG #a b:c").unwrap();
+      assert_eq!(msg.text(), None);
+    }
+
     #[test]
     fn notice_without_channel() {
       let data = ":tmi.twitch.tv NOTICE * :Improperly formatted auth";
@@ -913,6 +2100,525 @@ mod tests {
       assert_eq!(msg.params(), Some("* :Improperly formatted auth"));
     }
 
+    #[test]
+    fn bytes() {
+      let data = b"@display-name=test :test!test@test.tmi.twitch.tv PRIVMSG #test :hello";
+      let msg = IrcMessage::parse_bytes(data).unwrap();
+      assert_eq!(msg.command(), Command::Privmsg);
+      assert_eq!(msg.tag(Tag::DisplayName), Some("test"));
+      assert_eq!(msg.text(), Some("hello"));
+    }
+
+    #[test]
+    fn bytes_invalid_utf8_in_params() {
+      let data =
+        b"@display-name=test :test!test@test.tmi.twitch.tv PRIVMSG #test :hello \xF0\x28\x8C";
+      assert_eq!(
+        IrcMessage::parse_bytes_detailed(data).unwrap_err(),
+        ParseError::InvalidUtf8(70)
+      );
+      assert!(IrcMessage::parse_bytes(b":tmi.twitch.tv PING :\xFF\r\n").is_none());
+    }
+
+    #[test]
+    fn bytes_same_as_str() {
+      let cases = [
+        "@display-name=test :test!test@test.tmi.twitch.tv PRIVMSG #test :hello",
+        "@display-name=tëst PRIVMSG #test :hëllo there\r\n",
+        ":a!b@c PRIVMSG #test :hi",
+        ":a@c #test PRIVMSG :hi",
+        ":tmi.twitch.tv CAP * ACK :twitch.tv/commands",
+        "PING",
+        "PING :tmi.twitch.tv\r\n",
+        "@a=b :tmi.twitch.tv ROOMSTATE #test",
+      ];
+      for case in cases {
+        let msg = IrcMessage::parse_bytes(case.as_bytes()).unwrap();
+        let expected = IrcMessage::parse(case).unwrap();
+        assert_eq!(format!("{msg:?}"), format!("{expected:?}"), "{case:?}");
+        assert_eq!(msg.raw(), case);
+      }
+    }
+
+    #[test]
+    fn bytes_invalid_utf8_before_params() {
+      let data = b"@display-name=\xFF PRIVMSG #test :hello";
+      assert_eq!(
+        IrcMessage::parse_bytes_detailed(data).unwrap_err(),
+        ParseError::InvalidUtf8(14)
+      );
+      assert_eq!(
+        IrcMessage::parse_bytes_detailed(b"").unwrap_err(),
+        ParseError::Empty
+      );
+      assert_eq!(
+        IrcMessage::parse_bytes_detailed(b"@a=b").unwrap_err(),
+        ParseError::UnterminatedTags(4)
+      );
+    }
+
+    #[test]
+    fn many() {
+      let data = ":tmi.twitch.tv PING :a\r\n\r\n@room-id=1 :tmi.twitch.tv ROOMSTATE #test\n:tmi.twitch.tv PONG";
+      let messages = IrcMessageRef::parse_many(data)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+      assert_eq!(messages.len(), 3);
+      assert_eq!(messages[0].command(), Command::Ping);
+      assert_eq!(messages[0].text(), Some("a"));
+      assert_eq!(messages[1].command(), Command::RoomState);
+      assert_eq!(messages[1].tag(Tag::RoomId), Some("1"));
+      assert_eq!(messages[1].channel().map(|c| c.as_str()), Some("#test"));
+      assert_eq!(messages[2].command(), Command::Pong);
+    }
+
+    #[test]
+    fn many_trailing_fragment() {
+      let data = ":tmi.twitch.tv PING\r\n@room-id=1;subs-only=0 :tmi.twitch.tv ";
+      let mut messages = IrcMessageRef::parse_many(data);
+      assert_eq!(messages.next().unwrap().unwrap().command(), Command::Ping);
+      assert_eq!(
+        messages.next().unwrap().err(),
+        Some(ParseError::MissingCommand(38))
+      );
+      assert!(messages.next().is_none());
+
+      assert_eq!(IrcMessageRef::parse_many("\r\n\n").count(), 0);
+    }
+
+    #[test]
+    fn prefix_str() {
+      for (data, prefix) in [
+        (":nick!user@host PRIVMSG #a :b", Some("nick!user@host")),
+        (":nick@host PRIVMSG #a :b", Some("nick@host")),
+        ("@id=1 :tmi.twitch.tv PING", Some("tmi.twitch.tv")),
+        ("PING :tmi.twitch.tv", None),
+      ] {
+        assert_eq!(IrcMessageRef::parse(data).unwrap().prefix_str(), prefix);
+        assert_eq!(IrcMessage::parse(data).unwrap().prefix_str(), prefix);
+      }
+    }
+
+    #[test]
+    fn many_tags() {
+      // around the inline capacity of `RawTags`, and well beyond it
+      for count in [0, 1, 23, 24, 25, 100] {
+        let tags = (0..count)
+          .map(|i| format!("tag-{i}=value-{i}"))
+          .collect::<Vec<_>>()
+          .join(";");
+        let data = format!("@{tags} :a!a@a.tmi.twitch.tv PRIVMSG #a :b");
+        let data = data.strip_prefix("@ ").unwrap_or(&data);
+
+        let msg = IrcMessageRef::parse(data).unwrap();
+        assert_eq!(msg.tags().count(), count, "{count}");
+        for (i, (key, value)) in msg.tags_raw().enumerate() {
+          assert_eq!(key, format!("tag-{i}"));
+          assert_eq!(value, format!("value-{i}"));
+        }
+        assert_eq!(msg.text(), Some("b"));
+
+        let msg = msg.into_owned();
+        assert_eq!(msg.tags().count(), count, "{count}");
+        if count > 0 {
+          let last = count - 1;
+          assert_eq!(
+            msg.tag(format!("tag-{last}").as_str()),
+            Some(format!("value-{last}").as_str())
+          );
+        }
+      }
+    }
+
+    #[test]
+    fn parse_detailed() {
+      let cases = [
+        ("", ParseError::Empty),
+        ("\r\n", ParseError::Empty),
+        ("@id=1;mod=0", ParseError::UnterminatedTags(11)),
+        ("@id=1;mod=0\r\n", ParseError::UnterminatedTags(11)),
+        ("@id=1 ", ParseError::MissingCommand(6)),
+        (":tmi.twitch.tv ", ParseError::MissingCommand(15)),
+        ("@id=1 :tmi.twitch.tv  PING", ParseError::MissingCommand(21)),
+        (":", ParseError::MalformedPrefix(0)),
+        (":a!b@c", ParseError::MalformedPrefix(0)),
+        (":a!b@c\r\n", ParseError::MalformedPrefix(0)),
+        ("@id=1 :a!b@c", ParseError::MalformedPrefix(6)),
+        (":nick!host PING", ParseError::MalformedPrefix(0)),
+      ];
+      for (data, expected) in cases {
+        assert!(IrcMessageRef::parse(data).is_none(), "{data:?}");
+        assert_eq!(
+          IrcMessageRef::parse_detailed(data).err(),
+          Some(expected),
+          "{data:?}"
+        );
+        assert_eq!(
+          IrcMessage::parse_detailed(data).err(),
+          Some(expected),
+          "{data:?}"
+        );
+      }
+
+      let msg = IrcMessageRef::parse_detailed(":tmi.twitch.tv PING").unwrap();
+      assert_eq!(msg.command(), Command::Ping);
+      assert_eq!(ParseError::MissingCommand(6).offset(), 6);
+      assert_eq!(
+        ParseError::MissingCommand(6).to_string(),
+        "failed to parse irc message: missing command at byte 6"
+      );
+    }
+
+    #[test]
+    fn parse_lazy() {
+      for data in [
+        "PING :tmi.twitch.tv",
+        ":forsen!forsen@forsen.tmi.twitch.tv PRIVMSG #forsen :yo",
+        "@id=1 PRIVMSG #forsen :a;b;c",
+        "@ PING",
+        "@a=b;=c;d;e==f;;g=;h :tmi.twitch.tv PING",
+        "@id=1",
+        "@msg-id=sub;custom-tag=x\\sy :tmi.twitch.tv USERNOTICE #forsen\r\n",
+        "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam",
+      ] {
+        let eager = IrcMessageRef::parse(data);
+        let lazy = IrcMessageRef::parse_lazy(data);
+        assert_eq!(format!("{eager:?}"), format!("{lazy:?}"), "{data:?}");
+        let (Some(eager), Some(lazy)) = (eager, lazy) else {
+          continue;
+        };
+
+        assert_eq!(
+          lazy.tags_raw().collect::<Vec<_>>(),
+          eager.tags_raw().collect::<Vec<_>>(),
+          "{data:?}"
+        );
+        for (tag, _) in eager.tags() {
+          assert_eq!(lazy.tag(tag.clone()), eager.tag(tag), "{data:?}");
+        }
+        assert_eq!(lazy.tag(Tag::Login), None);
+        assert_eq!(lazy.tag("custom-tag"), eager.tag("custom-tag"));
+
+        let owned = IrcMessage::parse_lazy(data).unwrap();
+        assert_eq!(
+          owned.tags_raw().collect::<Vec<_>>(),
+          eager.tags_raw().collect::<Vec<_>>(),
+          "{data:?}"
+        );
+        let owned = lazy.into_owned();
+        assert_eq!(owned.tags().count(), eager.tags().count(), "{data:?}");
+      }
+    }
+
+    #[test]
+    fn parse_with_capacity() {
+      let many = (0..40)
+        .map(|i| format!("tag-{i}={i}"))
+        .collect::<Vec<_>>()
+        .join(";");
+      let many = format!("@{many} :tmi.twitch.tv PING");
+
+      let msg = IrcMessageRef::parse_with_capacity::<64>(&many).unwrap();
+      assert!(msg.parts.tags.capacity() >= 64);
+      assert_eq!(msg.tags().count(), 40);
+      assert_eq!(msg.tag("tag-39"), Some("39"));
+      assert_eq!(
+        msg.tags_raw().collect::<Vec<_>>(),
+        IrcMessageRef::parse(&many)
+          .unwrap()
+          .tags_raw()
+          .collect::<Vec<_>>()
+      );
+
+      let msg = IrcMessage::parse_with_capacity::<64>(&many).unwrap();
+      assert_eq!(msg.tags().count(), 40);
+      assert_eq!(msg.tag("tag-0"), Some("0"));
+    }
+
+    #[test]
+    fn parse_into_reuses_tags() {
+      let many = (0..40)
+        .map(|i| format!("tag-{i}={i}"))
+        .collect::<Vec<_>>()
+        .join(";");
+      let many = format!("@{many} :tmi.twitch.tv PING");
+
+      let mut arena = ParseArena::new();
+      let msg = IrcMessageRef::parse_into(&many, &mut arena).unwrap();
+      assert_eq!(msg.tags().count(), 40);
+      let ptr = msg.parts.tags.as_ptr();
+      arena.reclaim(msg);
+
+      let msg = IrcMessageRef::parse_into("@id=1;mod=0 :tmi.twitch.tv PING", &mut arena).unwrap();
+      assert_eq!(msg.parts.tags.as_ptr(), ptr);
+      assert_eq!(
+        msg.tags_raw().collect::<Vec<_>>(),
+        [("id", "1"), ("mod", "0")]
+      );
+      arena.reclaim(msg);
+
+      let msg = IrcMessageRef::parse_into(":tmi.twitch.tv PING", &mut arena).unwrap();
+      assert_eq!(msg.tags().count(), 0);
+      assert_eq!(msg.command(), Command::Ping);
+      arena.reclaim(msg);
+
+      let msg = IrcMessageRef::parse_into("@id=2 :tmi.twitch.tv PING", &mut arena).unwrap();
+      assert_eq!(msg.parts.tags.as_ptr(), ptr);
+      assert_eq!(msg.tags_raw().collect::<Vec<_>>(), [("id", "2")]);
+    }
+
+    #[test]
+    fn tags_raw() {
+      let data = "@foo=a\\sb;display-name=A;empty= :a!a@a.tmi.twitch.tv PRIVMSG #a :b";
+      let expected = [("foo", "a\\sb"), ("display-name", "A"), ("empty", "")];
+
+      let msg = IrcMessageRef::parse(data).unwrap();
+      assert_eq!(msg.tags_raw().collect::<Vec<_>>(), expected);
+
+      let msg = IrcMessage::parse(data).unwrap();
+      assert_eq!(msg.tags_raw().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn tags_in_wire_order() {
+      let data = "@user-type=;room-id=1;zzz=3;badges=;aaa=2;id=0;display-name=a :a!a@a.tmi.twitch.tv PRIVMSG #a :b";
+      let expected = [
+        "user-type",
+        "room-id",
+        "zzz",
+        "badges",
+        "aaa",
+        "id",
+        "display-name",
+      ];
+
+      let msg = IrcMessageRef::parse(data).unwrap();
+      let names = msg.tags().map(|(tag, _)| tag.as_str()).collect::<Vec<_>>();
+      assert_eq!(names, expected);
+
+      let msg = IrcMessage::parse(data).unwrap();
+      let names = msg.tags().map(|(tag, _)| tag.as_str()).collect::<Vec<_>>();
+      assert_eq!(names, expected);
+
+      let msg = IrcMessageRef::parse_with_whitelist(data, whitelist!(Id, RoomId)).unwrap();
+      let names = msg.tags().map(|(tag, _)| tag.as_str()).collect::<Vec<_>>();
+      assert_eq!(names, ["room-id", "id"]);
+    }
+
+    #[test]
+    fn blacklist() {
+      let data = "@badge-info=subscriber/22;badges=subscriber/3012;color=#FFFF00;display-name=FELYP8;emote-only=1;emotes=521050:0-6,8-14,16-22,24-30,32-38,40-46,48-54,56-62,64-70,72-78,80-86,88-94,96-102,104-110,148-154,156-162,164-170,172-178,180-186,188-194,196-202,204-210,212-218,220-226,228-234,236-242,244-250,252-258,260-266/302827730:112-119/302827734:121-128/302827735:130-137/302827737:139-146;first-msg=0;flags=;foo-bar=baz;id=1844235a-c24e-4e18-937b-805d6601aebe;mod=0;returning-chatter=0;room-id=22484632;subscriber=1;tmi-sent-ts=1685664001040;turbo=0;user-id=162760707;user-type= :felyp8!felyp8@felyp8.tmi.twitch.tv PRIVMSG #forsen :forsenE forsenE";
+
+      let msg = IrcMessageRef::parse_with_whitelist(data, blacklist!(Emotes)).unwrap();
+      assert_eq!(msg.tag(Tag::Emotes), None);
+      assert_eq!(msg.tag(Tag::DisplayName), Some("FELYP8"));
+      assert_eq!(msg.tag("foo-bar"), Some("baz"));
+      assert_eq!(
+        msg.tags().count(),
+        IrcMessageRef::parse(data).unwrap().tags().count() - 1
+      );
+
+      let msg = IrcMessage::parse_with_whitelist(data, blacklist!(Emotes, Flags)).unwrap();
+      assert_eq!(msg.tag(Tag::Emotes), None);
+      assert_eq!(msg.tag(Tag::Flags), None);
+      assert_eq!(msg.tag(Tag::DisplayName), Some("FELYP8"));
+    }
+
+    #[test]
+    fn simd_backend() {
+      let expected = if cfg!(any(not(feature = "simd"), feature = "force-scalar")) {
+        "scalar"
+      } else if cfg!(target_arch = "x86_64") {
+        "sse2"
+      } else if cfg!(target_arch = "aarch64") {
+        "neon"
+      } else {
+        "wasm-simd128"
+      };
+      assert_eq!(super::simd_backend(), expected);
+    }
+
+    #[test]
+    fn typed_tags() {
+      let data = "@badge-info=;badges=bits/100;bits=100;color=;display-name=Riot\\sGames;emotes=;first-msg=;id=1;mod=0;room-id=2;subscriber=x;user-id=3 :riotgames!riotgames@riotgames.tmi.twitch.tv PRIVMSG #pajlada :cheer100";
+
+      let msg = IrcMessageRef::parse(data).unwrap();
+      assert_eq!(msg.tag_u64(Tag::Bits), Some(100));
+      assert_eq!(msg.tag_u64(Tag::Color), None);
+      assert_eq!(msg.tag_u64(Tag::TmiSentTs), None);
+      assert_eq!(msg.tag_bool(Tag::Mod), Some(false));
+      assert_eq!(msg.tag_bool(Tag::FirstMsg), None);
+      assert_eq!(msg.tag_bool(Tag::Subscriber), None);
+      assert_eq!(
+        msg.tag_unescaped(Tag::DisplayName).as_deref(),
+        Some("Riot Games")
+      );
+      assert!(matches!(
+        msg.tag_unescaped(Tag::Id),
+        Some(Cow::Borrowed("1"))
+      ));
+
+      let msg = IrcMessage::parse(data.replace("mod=0", "mod=1")).unwrap();
+      assert_eq!(msg.tag_u64(Tag::Bits), Some(100));
+      assert_eq!(msg.tag_bool(Tag::Mod), Some(true));
+      assert_eq!(
+        msg.tag_unescaped(Tag::DisplayName).as_deref(),
+        Some("Riot Games")
+      );
+    }
+
+    #[test]
+    fn whitelist_union() {
+      let data = "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;foo-bar=baz;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817 :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam";
+
+      let whitelist = whitelist!(Color).union(whitelist!(DisplayName));
+      let msg = IrcMessageRef::parse_with_whitelist(data, whitelist).unwrap();
+      assert_eq!(
+        msg.tags().map(|(tag, _)| tag.as_str()).collect::<Vec<_>>(),
+        ["color", "display-name"]
+      );
+
+      // overlapping tags are only inserted once
+      let whitelist = whitelist!(Color, Id)
+        .union(DynWhitelist::from_tags([Tag::Id, Tag::Unknown("foo-bar")]))
+        .union(whitelist!());
+      let msg = IrcMessage::parse_with_whitelist(data, whitelist.by_ref()).unwrap();
+      assert_eq!(
+        msg.tags().map(|(tag, _)| tag.as_str()).collect::<Vec<_>>(),
+        ["color", "foo-bar", "id"]
+      );
+    }
+
+    #[test]
+    fn dynamic_whitelist() {
+      let data = "@badge-info=subscriber/22;badges=subscriber/3012;color=#FFFF00;display-name=FELYP8;emote-only=1;emotes=521050:0-6,8-14;first-msg=0;flags=;foo-bar=baz;id=1844235a-c24e-4e18-937b-805d6601aebe;mod=0 :felyp8!felyp8@felyp8.tmi.twitch.tv PRIVMSG #forsen :forsenE forsenE";
+
+      let expected =
+        IrcMessage::parse_with_whitelist(data, whitelist!(Badges, Color, DisplayName, Emotes))
+          .unwrap();
+      let expected = expected.tags().collect::<Vec<_>>();
+
+      let tags = ["badges", "color", "display-name", "emotes"]
+        .iter()
+        .map(|name| Tag::from_raw(name).unwrap())
+        .collect::<Vec<_>>();
+      let whitelist = DynWhitelist::from_tags(tags);
+      for _ in 0..2 {
+        let message = IrcMessage::parse_with_whitelist(data, whitelist.by_ref()).unwrap();
+        assert_eq!(message.tags().collect::<Vec<_>>(), expected);
+      }
+
+      let whitelist = DynWhitelist::from_tags([Tag::Id, Tag::Unknown("foo-bar")]);
+      let message = IrcMessageRef::parse_with_whitelist(data, whitelist).unwrap();
+      assert_eq!(
+        message
+          .tags()
+          .map(|(tag, _)| tag.as_str())
+          .collect::<Vec<_>>(),
+        ["foo-bar", "id"]
+      );
+    }
+
+    #[test]
+    fn commands_and_tags_as_keys() {
+      use std::collections::{BTreeMap, HashMap};
+
+      let lines = [
+        ":tmi.twitch.tv PING",
+        "@id=1;mod=0 :a!a@a.tmi.twitch.tv PRIVMSG #forsen :yo",
+        "@id=2;x-custom=1 :b!b@b.tmi.twitch.tv PRIVMSG #forsen :yo",
+        ":tmi.twitch.tv 421 justinfan :Unknown command",
+        ":tmi.twitch.tv WALLOPS :hi",
+        ":tmi.twitch.tv WALLOPS :hi again",
+      ];
+
+      let mut commands = HashMap::<Command, u64>::new();
+      let mut tags = BTreeMap::<Tag, u64>::new();
+      for line in lines {
+        let msg = IrcMessageRef::parse(line).unwrap();
+        *commands.entry(msg.command()).or_default() += 1;
+        for (tag, _) in msg.tags() {
+          *tags.entry(tag).or_default() += 1;
+        }
+      }
+
+      assert_eq!(commands.len(), 4);
+      assert_eq!(commands[&Command::Ping], 1);
+      assert_eq!(commands[&Command::Privmsg], 2);
+      assert_eq!(commands[&Command::from_raw("421").unwrap()], 1);
+      assert_eq!(commands[&Command::Other("WALLOPS")], 2);
+
+      assert_eq!(
+        tags.into_iter().collect::<Vec<_>>(),
+        [(Tag::Id, 2), (Tag::Mod, 1), (Tag::Unknown("x-custom"), 1)]
+      );
+    }
+
+    #[test]
+    fn command_from_str() {
+      for (name, cmd) in [
+        ("PRIVMSG", Command::Privmsg),
+        ("CAP", Command::Capability),
+        ("353", Command::RplNames),
+        ("001", Command::RplWelcome),
+      ] {
+        assert_eq!(name.parse::<Command>(), Ok(cmd));
+        assert_eq!(Command::from(name), cmd);
+        assert_eq!(cmd, name);
+        assert!(cmd == *name);
+      }
+
+      assert_eq!("FOO".parse::<Command>(), Err(UnknownCommandError));
+      assert_eq!("421".parse::<Command>(), Err(UnknownCommandError));
+      assert_eq!(
+        Command::from("421"),
+        Command::Numeric(ReplyCode(421), "421")
+      );
+      assert_eq!("privmsg".parse::<Command>(), Err(UnknownCommandError));
+      assert_eq!(Command::from("FOO"), Command::Other("FOO"));
+      assert_eq!(Command::Other("FOO"), "FOO");
+      assert_ne!(Command::Privmsg, "PING");
+
+      let msg = IrcMessageRef::parse(":tmi.twitch.tv 353 justinfan :rest").unwrap();
+      assert_eq!(msg.command(), "353");
+    }
+
+    #[test]
+    fn tag_from_str() {
+      for tag in [
+        Tag::DisplayName,
+        Tag::MsgId,
+        Tag::RoomId,
+        Tag::MsgParamGiftMonths,
+      ] {
+        assert_eq!(tag.as_str().parse::<Tag>(), Ok(tag.clone()));
+        assert_eq!(Tag::from_raw(tag.as_str()), Some(tag));
+      }
+      assert_eq!("foo-bar".parse::<Tag>(), Err(UnknownTagError));
+      assert_eq!(Tag::from_raw("foo-bar"), None);
+      assert_eq!(Tag::from_raw("Display-Name"), None);
+    }
+
+    #[test]
+    fn unknown_tags() {
+      let data = "@foo-bar=baz;id=1;pinned-chat-paid-amount=500 :a!a@a.tmi.twitch.tv PRIVMSG #a :b";
+
+      let msg = IrcMessageRef::parse(data).unwrap();
+      assert_eq!(msg.tag("foo-bar"), Some("baz"));
+      assert_eq!(msg.tag("pinned-chat-paid-amount"), Some("500"));
+      assert_eq!(msg.tag("id"), Some("1"));
+      assert_eq!(msg.tag("foo"), None);
+
+      let msg = IrcMessage::parse(data).unwrap();
+      assert_eq!(msg.tag("foo-bar"), Some("baz"));
+      assert_eq!(msg.tag("id"), Some("1"));
+
+      // unknown tags are not whitelisted
+      let msg = IrcMessageRef::parse_with_whitelist(data, whitelist!(Id)).unwrap();
+      assert_eq!(msg.tag("foo-bar"), None);
+      assert_eq!(msg.tag("id"), Some("1"));
+    }
+
     #[test]
     fn regression_parse_prefix() {
       let data = ":justinfan57624!justinfan57624@justinfan57624.tmi.twitch.tv JOIN #riotgames";
@@ -929,10 +2635,188 @@ mod tests {
       );
     }
 
+    #[test]
+    fn prefix_shapes() {
+      let cases = [
+        (":tmi.twitch.tv PING", Some((None, None, "tmi.twitch.tv"))),
+        (":nick@host PING", Some((Some("nick"), None, "host"))),
+        (
+          ":nick!user@host PING",
+          Some((Some("nick"), Some("user"), "host")),
+        ),
+        (
+          ":nick!user@host@host PING",
+          Some((Some("nick"), Some("user"), "host@host")),
+        ),
+        (": PING", Some((None, None, ""))),
+        (":!@ PING", Some((Some(""), Some(""), ""))),
+        (":nick!host PING", None),
+        (":nick@host!user PING", None),
+        (":tmi.twitch.tv", None),
+        (":", None),
+      ];
+
+      // long prefixes cross the SIMD chunk boundary
+      let long = "a".repeat(20);
+      let long_nick = format!(":{long}!{long}@{long}.tmi.twitch.tv PING");
+      let long_host = format!(":{long}{long}.tmi.twitch.tv NOTICE #a :a!b@c");
+      let nick_host = format!("{long}.tmi.twitch.tv");
+      let host = format!("{long}{long}.tmi.twitch.tv");
+      let long_cases = [
+        (
+          long_nick.as_str(),
+          Some((Some(long.as_str()), Some(long.as_str()), nick_host.as_str())),
+        ),
+        (long_host.as_str(), Some((None, None, host.as_str()))),
+      ];
+
+      for (data, expected) in cases.into_iter().chain(long_cases) {
+        let expected = expected.map(|(nick, user, host)| Prefix { nick, user, host });
+
+        // the active implementation and the scalar fallback must agree
+        for parse_prefix in [parse_prefix, scalar::parse_prefix] {
+          let mut pos = 0;
+          let prefix = parse_prefix(data, &mut pos).map(|prefix| prefix.get(data));
+          assert_eq!(prefix, expected, "{data:?}");
+          if expected.is_some() {
+            assert_eq!(&data[pos..], data.split_once(' ').unwrap().1, "{data:?}");
+          } else {
+            assert_eq!(pos, 0, "{data:?}");
+          }
+        }
+      }
+    }
+
+    #[test]
+    fn tags_match_scalar() {
+      const KEY: &[u8] = b"abcdefghijklmnopqrstuvwxyz-";
+      const VALUE: &[&str] = &[
+        "a", "0", "#", "-", "=", "/", "\\s", "\\:", "\\\\", "é", "日本", "👉",
+      ];
+
+      let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+      let mut next = move |n: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize % n
+      };
+
+      let mut data = String::new();
+      for _ in 0..20_000 {
+        data.clear();
+        data.push('@');
+        for i in 0..next(24) {
+          if i > 0 {
+            data.push(';');
+          }
+          // key lengths up to 32 put the `=` and `;` on both sides of 16 byte boundaries,
+          // and the occasional empty key or missing `=` makes the tag malformed
+          for _ in 0..next(33) {
+            data.push(KEY[next(KEY.len())] as char);
+          }
+          if next(16) > 0 {
+            data.push('=');
+          }
+          // empty values are common, e.g. `emotes=`
+          if next(3) > 0 {
+            for _ in 0..next(20) {
+              data.push_str(VALUE[next(VALUE.len())]);
+            }
+          }
+        }
+        if next(4) > 0 {
+          data.push_str(" :tmi.twitch.tv PING");
+        }
+
+        let whitelist = Whitelist::<DEFAULT_TAG_CAPACITY, _>(whitelist_insert_all);
+        let (mut expected_pos, mut actual_pos) = (0, 0);
+        let expected = scalar::parse_tags(&data, &mut expected_pos, &whitelist, RawTags::new());
+        let actual = parse_tags(&data, &mut actual_pos, &whitelist, RawTags::new());
+
+        let expected = expected
+          .iter()
+          .map(|tag| tag.get(&data))
+          .collect::<Vec<_>>();
+        let actual = actual.iter().map(|tag| tag.get(&data)).collect::<Vec<_>>();
+        assert_eq!(actual, expected, "{data:?}");
+        assert_eq!(actual_pos, expected_pos, "{data:?}");
+      }
+    }
+
+    #[test]
+    fn regression_prefix_delimiters_after_end() {
+      // `!` and `@` after the prefix used to be treated as part of it by the SIMD parser
+      let msg = IrcMessageRef::parse(":x :a!a@a PRIVMSG #a :b").unwrap();
+      assert_eq!(msg.prefix().unwrap().host, "x");
+      assert_eq!(msg.prefix().unwrap().nick, None);
+
+      let msg = IrcMessageRef::parse(":tmi.twitch.tv NOTICE #a :hi @a!a").unwrap();
+      assert_eq!(msg.prefix().unwrap().host, "tmi.twitch.tv");
+
+      let _ = IrcMessageRef::parse(":a@b!c PRIVMSG #a :b");
+    }
+
     #[test]
     fn regression_equals_in_tag_value() {
       let data = "@display-name=Dixtor334;emotes=;first-msg=0;flags=;id=0b4c70e4-9a47-4ce1-9c3e-8f78111cdc19;mod=0;reply-parent-display-name=minosura;reply-parent-msg-body=https://youtu.be/-ek4MFjz_eM?list=PL91C6439FD45DE2F3\\sannytfDinkDonk\\sstrimmer\\skorean\\sone;reply-parent-msg-id=7f811788-b897-4b4c-9f91-99fafe70eb7f;reply-parent-user-id=141993641;reply-parent-user-login=minosura;returning-chatter=0;room-id=56418014;subscriber=1;tmi-sent-ts=1686049636367;turbo=0;user-id=73714767;user-type= :dixtor334!dixtor334@dixtor334.tmi.twitch.tv PRIVMSG #anny :@minosura @anny";
       assert_eq!("https://youtu.be/-ek4MFjz_eM?list=PL91C6439FD45DE2F3\\sannytfDinkDonk\\sstrimmer\\skorean\\sone", IrcMessageRef::parse(data).unwrap().tag(Tag::ReplyParentMsgBody).unwrap());
     }
   }
+
+  #[test]
+  fn escape_roundtrip() {
+    for value in [
+      "Riot Games",
+      "forsen",
+      "",
+      "a;b c\\d\r\ne",
+      "\\s is not a space",
+    ] {
+      assert_eq!(unescape(&escape(value)), value);
+    }
+    assert_eq!(escape("Riot Games"), "Riot\\sGames");
+    assert_eq!(escape("a;b\\c\r\n"), "a\\:b\\\\c\\r\\n");
+    assert_eq!(escape("subscriber/22,3"), "subscriber/22⸝3");
+    assert!(matches!(escape("forsen"), Cow::Borrowed("forsen")));
+  }
+
+  #[test]
+  fn escape_roundtrip_random() {
+    const ALPHABET: &[char] = &[
+      '\\', ' ', ';', ':', ',', '⸝', '\r', '\n', 's', 'r', 'n', 'a', 'é', '👉',
+    ];
+
+    // xorshift, so that failures are reproducible without extra dependencies
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move || {
+      state ^= state << 13;
+      state ^= state >> 7;
+      state ^= state << 17;
+      state
+    };
+
+    let mut value = String::new();
+    for _ in 0..20_000 {
+      value.clear();
+      for _ in 0..next() % 16 {
+        value.push(ALPHABET[next() as usize % ALPHABET.len()]);
+      }
+      let escaped = escape(&value);
+      assert!(!escaped.contains([' ', ';', ',', '\r', '\n']), "{value:?}");
+      assert_eq!(unescape(&escaped), value.replace('⸝', ","), "{value:?}");
+      assert_eq!(maybe_unescape(escaped.as_ref()), unescape(&escaped));
+    }
+  }
+
+  #[test]
+  fn unescape_without_escapes_borrows() {
+    assert!(matches!(maybe_unescape("forsen"), Cow::Borrowed("forsen")));
+    assert!(matches!(maybe_unescape(""), Cow::Borrowed("")));
+    for value in ["Riot\\sGames", "a\\:b", "subscriber/22⸝3", "trailing\\"] {
+      let unescaped = maybe_unescape(value);
+      assert!(matches!(unescaped, Cow::Owned(_)), "{value:?}");
+      assert_eq!(unescaped, unescape(value));
+    }
+  }
 }
@@ -0,0 +1,263 @@
+use super::escape;
+use std::fmt::{Display, Write};
+
+/// Builder for an outgoing IRC message.
+///
+/// The message is assembled as `[@tags] COMMAND [params...] [:trailing]\r\n`.
+/// Tag values are [escaped][`escape`], everything else is validated by [`IrcMessageBuilder::build`].
+///
+/// ```rust
+/// use tmi::IrcMessageBuilder;
+///
+/// let line = IrcMessageBuilder::new("PRIVMSG")
+///   .tag("reply-parent-msg-id", "b34ccfc7-4977-403a-8a94-33c6bac34fb8")
+///   .param("#forsen")
+///   .trailing("hello there")
+///   .build()
+///   .unwrap();
+/// assert_eq!(
+///   line,
+///   "@reply-parent-msg-id=b34ccfc7-4977-403a-8a94-33c6bac34fb8 PRIVMSG #forsen :hello there\r\n"
+/// );
+/// ```
+///
+/// The result is terminated by `\r\n`, so it may be sent using `ClientWriter::send_raw`.
+#[derive(Clone, Debug)]
+pub struct IrcMessageBuilder<'a> {
+  command: &'a str,
+  tags: Vec<(&'a str, &'a str)>,
+  params: Vec<&'a str>,
+  trailing: Option<&'a str>,
+}
+
+impl<'a> IrcMessageBuilder<'a> {
+  /// Start building a message with the given `command`, e.g. `PRIVMSG`.
+  pub fn new(command: &'a str) -> Self {
+    Self {
+      command,
+      tags: Vec::new(),
+      params: Vec::new(),
+      trailing: None,
+    }
+  }
+
+  /// Add a tag. The `value` is escaped when the message is built.
+  pub fn tag(mut self, key: &'a str, value: &'a str) -> Self {
+    self.tags.push((key, value));
+    self
+  }
+
+  /// Add a middle param, which may not contain spaces.
+  pub fn param(mut self, param: &'a str) -> Self {
+    self.params.push(param);
+    self
+  }
+
+  /// Add several middle params, see [`IrcMessageBuilder::param`].
+  pub fn params(mut self, params: impl IntoIterator<Item = &'a str>) -> Self {
+    self.params.extend(params);
+    self
+  }
+
+  /// Set the trailing param, which is the only one that may contain spaces.
+  pub fn trailing(mut self, trailing: &'a str) -> Self {
+    self.trailing = Some(trailing);
+    self
+  }
+
+  /// Validate the message, and write it into a `\r\n`-terminated line.
+  pub fn build(&self) -> Result<String, BuildError> {
+    let mut out = String::with_capacity(self.len_hint());
+    self.write_to(&mut out)?;
+    Ok(out)
+  }
+
+  /// Validate the message, and append it to `out` as a `\r\n`-terminated line.
+  ///
+  /// Nothing is written if the message is invalid.
+  pub fn write_to(&self, out: &mut String) -> Result<(), BuildError> {
+    self.validate()?;
+
+    if !self.tags.is_empty() {
+      out.push('@');
+      for (i, (key, value)) in self.tags.iter().enumerate() {
+        if i > 0 {
+          out.push(';');
+        }
+        let _ = write!(out, "{key}={}", escape(value));
+      }
+      out.push(' ');
+    }
+    out.push_str(self.command);
+    for param in &self.params {
+      out.push(' ');
+      out.push_str(param);
+    }
+    if let Some(trailing) = self.trailing {
+      out.push_str(" :");
+      out.push_str(trailing);
+    }
+    out.push_str("\r\n");
+
+    Ok(())
+  }
+
+  fn validate(&self) -> Result<(), BuildError> {
+    // `PRIVMSG` or a numeric reply like `001`
+    let command = self.command;
+    let is_word = !command.is_empty() && command.bytes().all(|c| c.is_ascii_uppercase());
+    let is_numeric = command.len() == 3 && command.bytes().all(|c| c.is_ascii_digit());
+    if !is_word && !is_numeric {
+      return Err(BuildError::InvalidCommand);
+    }
+
+    for (i, (key, value)) in self.tags.iter().enumerate() {
+      if key.is_empty() || key.contains(['=', ';', ' ', '\r', '\n']) || value.contains('\0') {
+        return Err(BuildError::InvalidTag(i));
+      }
+    }
+
+    for (i, param) in self.params.iter().enumerate() {
+      if param.is_empty() || param.starts_with(':') || param.contains([' ', '\r', '\n', '\0']) {
+        return Err(BuildError::InvalidParam(i));
+      }
+    }
+
+    if let Some(trailing) = self.trailing {
+      if trailing.contains(['\r', '\n', '\0']) {
+        return Err(BuildError::InvalidTrailing);
+      }
+    }
+
+    Ok(())
+  }
+
+  fn len_hint(&self) -> usize {
+    let tags: usize = self.tags.iter().map(|(k, v)| k.len() + v.len() + 2).sum();
+    let params: usize = self.params.iter().map(|p| p.len() + 1).sum();
+    let trailing = self.trailing.map_or(0, |t| t.len() + 2);
+    tags + 1 + self.command.len() + params + trailing + 2
+  }
+}
+
+/// Failed to build an IRC message.
+///
+/// See [`IrcMessageBuilder::build`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildError {
+  /// The command is not made up of uppercase ASCII letters, or three digits.
+  InvalidCommand,
+
+  /// The tag at this index has an empty key, or contains a character which may not be escaped.
+  InvalidTag(usize),
+
+  /// The param at this index is empty, starts with `:`, or contains a space or a line break.
+  InvalidParam(usize),
+
+  /// The trailing param contains a line break.
+  InvalidTrailing,
+}
+
+impl Display for BuildError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      BuildError::InvalidCommand => write!(f, "invalid irc message: invalid command"),
+      BuildError::InvalidTag(i) => write!(f, "invalid irc message: invalid tag at index {i}"),
+      BuildError::InvalidParam(i) => write!(f, "invalid irc message: invalid param at index {i}"),
+      BuildError::InvalidTrailing => write!(f, "invalid irc message: invalid trailing param"),
+    }
+  }
+}
+
+impl std::error::Error for BuildError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{Command, IrcMessageRef};
+
+  #[test]
+  fn build_privmsg() {
+    let line = IrcMessageBuilder::new("PRIVMSG")
+      .tag("client-nonce", "abc")
+      .tag("reply-parent-msg-id", "a b;c")
+      .param("#forsen")
+      .trailing("hello :) there")
+      .build()
+      .unwrap();
+    assert_eq!(
+      line,
+      "@client-nonce=abc;reply-parent-msg-id=a\\sb\\:c PRIVMSG #forsen :hello :) there\r\n"
+    );
+
+    let msg = IrcMessageRef::parse(&line).unwrap();
+    assert_eq!(msg.command(), Command::Privmsg);
+    assert_eq!(msg.channel().unwrap().as_str(), "#forsen");
+    assert_eq!(msg.text(), Some("hello :) there"));
+    assert_eq!(msg.tag("reply-parent-msg-id"), Some("a\\sb\\:c"));
+  }
+
+  #[test]
+  fn build_join() {
+    let line = IrcMessageBuilder::new("JOIN")
+      .param("#forsen,#pajlada")
+      .build()
+      .unwrap();
+    assert_eq!(line, "JOIN #forsen,#pajlada\r\n");
+
+    let line = IrcMessageBuilder::new("CAP")
+      .params(["REQ"])
+      .trailing("twitch.tv/commands twitch.tv/tags")
+      .build()
+      .unwrap();
+    assert_eq!(line, "CAP REQ :twitch.tv/commands twitch.tv/tags\r\n");
+
+    let mut out = String::from("PING\r\n");
+    IrcMessageBuilder::new("001").write_to(&mut out).unwrap();
+    assert_eq!(out, "PING\r\n001\r\n");
+  }
+
+  #[test]
+  fn build_invalid() {
+    let cases = [
+      (
+        IrcMessageBuilder::new("privmsg"),
+        BuildError::InvalidCommand,
+      ),
+      (IrcMessageBuilder::new(""), BuildError::InvalidCommand),
+      (
+        IrcMessageBuilder::new("PRIV MSG"),
+        BuildError::InvalidCommand,
+      ),
+      (IrcMessageBuilder::new("01"), BuildError::InvalidCommand),
+      (
+        IrcMessageBuilder::new("JOIN").param("#a").param("#b c"),
+        BuildError::InvalidParam(1),
+      ),
+      (
+        IrcMessageBuilder::new("JOIN").param(":#a"),
+        BuildError::InvalidParam(0),
+      ),
+      (
+        IrcMessageBuilder::new("JOIN").param(""),
+        BuildError::InvalidParam(0),
+      ),
+      (
+        IrcMessageBuilder::new("PRIVMSG").trailing("a\r\nQUIT"),
+        BuildError::InvalidTrailing,
+      ),
+      (
+        IrcMessageBuilder::new("PRIVMSG").tag("a=b", "c"),
+        BuildError::InvalidTag(0),
+      ),
+    ];
+
+    for (builder, expected) in cases {
+      assert_eq!(builder.build(), Err(expected), "{builder:?}");
+    }
+
+    let mut out = String::new();
+    assert!(IrcMessageBuilder::new("join").write_to(&mut out).is_err());
+    assert!(out.is_empty());
+  }
+}
@@ -0,0 +1,141 @@
+use super::{IrcMessage, ParseError};
+use bytes::{Buf, BufMut, BytesMut};
+use std::fmt::Display;
+use std::io;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// A [`tokio_util::codec`] for `\r\n`-terminated IRC messages.
+///
+/// This allows using [`Framed`][tokio_util::codec::Framed] on top of any
+/// `AsyncRead + AsyncWrite` connection.
+///
+/// - The [`Decoder`] yields an [`IrcMessage`] for every complete line, skipping empty lines.
+///   Any incomplete line is kept in the buffer until the rest of it is received.
+/// - The [`Encoder`] writes a single line, and terminates it with `\r\n`.
+#[derive(Clone, Debug, Default)]
+pub struct IrcCodec {
+  /// Position in the buffer up to which we know there is no `\r\n`.
+  searched: usize,
+}
+
+impl IrcCodec {
+  /// Create a new codec.
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+impl Decoder for IrcCodec {
+  type Item = IrcMessage;
+  type Error = CodecError;
+
+  fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+    loop {
+      // a `\r` may have been the last byte searched previously,
+      // so step back by one to find a `\r\n` split across reads.
+      let from = self.searched.saturating_sub(1);
+      let Some(offset) = src[from..].windows(2).position(|w| w == b"\r\n") else {
+        self.searched = src.len();
+        return Ok(None);
+      };
+      let end = from + offset;
+      self.searched = 0;
+
+      let line = src.split_to(end);
+      src.advance(2);
+      if line.is_empty() {
+        continue;
+      }
+
+      return IrcMessage::parse_bytes_detailed(&line)
+        .map(Some)
+        .map_err(CodecError::Parse);
+    }
+  }
+}
+
+impl<'a> Encoder<&'a str> for IrcCodec {
+  type Error = CodecError;
+
+  fn encode(&mut self, item: &'a str, dst: &mut BytesMut) -> Result<(), Self::Error> {
+    let line = item.strip_suffix("\r\n").unwrap_or(item);
+    if line.contains(['\r', '\n']) {
+      return Err(CodecError::InvalidLine);
+    }
+    dst.reserve(line.len() + 2);
+    dst.put_slice(line.as_bytes());
+    dst.put_slice(b"\r\n");
+    Ok(())
+  }
+}
+
+/// Failed to decode or encode an IRC message.
+#[derive(Debug)]
+pub enum CodecError {
+  /// The underlying I/O operation failed.
+  Io(io::Error),
+
+  /// Failed to parse the message.
+  Parse(ParseError),
+
+  /// Attempted to encode a line which contains `\r` or `\n`.
+  InvalidLine,
+}
+
+impl From<io::Error> for CodecError {
+  fn from(value: io::Error) -> Self {
+    Self::Io(value)
+  }
+}
+
+impl Display for CodecError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      CodecError::Io(e) => write!(f, "codec error: {e}"),
+      CodecError::Parse(e) => write!(f, "codec error: {e}"),
+      CodecError::InvalidLine => write!(f, "codec error: line contains `\\r` or `\\n`"),
+    }
+  }
+}
+
+impl std::error::Error for CodecError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::irc::Command;
+
+  #[test]
+  fn decode_retains_partial() {
+    let mut codec = IrcCodec::new();
+    let mut buf = BytesMut::from(&b":tmi.twitch.tv PING :a\r\n:tmi.twitch.tv PO"[..]);
+
+    let msg = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(msg.command(), Command::Ping);
+    assert_eq!(msg.text(), Some("a"));
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+    assert_eq!(&buf[..], b":tmi.twitch.tv PO");
+
+    buf.extend_from_slice(b"NG\r");
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+    buf.extend_from_slice(b"\n\r\n");
+    let msg = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(msg.command(), Command::Pong);
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+    assert!(buf.is_empty());
+  }
+
+  #[test]
+  fn encode_line() {
+    let mut codec = IrcCodec::new();
+    let mut buf = BytesMut::new();
+    codec.encode("PING :a", &mut buf).unwrap();
+    codec.encode("PING :b\r\n", &mut buf).unwrap();
+    assert_eq!(&buf[..], b"PING :a\r\nPING :b\r\n");
+
+    assert!(matches!(
+      codec.encode("PRIVMSG #a :b\r\nJOIN #c", &mut buf),
+      Err(CodecError::InvalidLine)
+    ));
+  }
+}
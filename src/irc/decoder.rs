@@ -0,0 +1,161 @@
+use super::{IrcMessage, ParseError};
+
+/// Incremental decoder for a stream of `\r\n`-terminated IRC messages.
+///
+/// Feed it chunks of bytes as they are received using [`MessageDecoder::feed`],
+/// and retrieve complete messages using [`MessageDecoder::next_message`].
+/// Any incomplete line is retained until the rest of it is fed in.
+///
+/// ```rust
+/// let mut decoder = tmi::MessageDecoder::new();
+/// decoder.feed(b":tmi.twitch.tv PI");
+/// assert!(decoder.next_message().is_none());
+/// decoder.feed(b"NG\r\n");
+/// assert_eq!(decoder.next_message().unwrap().unwrap().command(), tmi::Command::Ping);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MessageDecoder {
+  buf: Vec<u8>,
+  /// Start of the unconsumed part of `buf`.
+  start: usize,
+  /// Position in `buf` up to which we know there is no `\r\n`.
+  searched: usize,
+}
+
+impl MessageDecoder {
+  /// Create a new decoder with an empty buffer.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Append a `chunk` of bytes to the buffer.
+  pub fn feed(&mut self, chunk: &[u8]) {
+    // compact the buffer before growing it
+    if self.start > 0 {
+      self.buf.drain(..self.start);
+      self.searched -= self.start;
+      self.start = 0;
+    }
+    self.buf.extend_from_slice(chunk);
+  }
+
+  /// Decode the next complete message in the buffer.
+  ///
+  /// Returns [`None`] if the buffer does not contain a complete message.
+  /// Empty lines are skipped.
+  pub fn next_message(&mut self) -> Option<Result<IrcMessage, ParseError>> {
+    loop {
+      let line = self.next_line()?;
+      if line.is_empty() {
+        continue;
+      }
+      return Some(IrcMessage::parse_bytes_detailed(line));
+    }
+  }
+
+  /// Returns an iterator over all complete messages currently in the buffer.
+  pub fn messages(&mut self) -> impl Iterator<Item = Result<IrcMessage, ParseError>> + '_ {
+    std::iter::from_fn(move || self.next_message())
+  }
+
+  /// The buffered bytes which do not yet form a complete message.
+  pub fn remaining(&self) -> &[u8] {
+    &self.buf[self.start..]
+  }
+
+  fn next_line(&mut self) -> Option<&[u8]> {
+    // a `\r` may have been the last byte searched previously,
+    // so step back by one to find a `\r\n` split across chunks.
+    let from = std::cmp::max(self.start, self.searched.saturating_sub(1));
+    match self.buf[from..].windows(2).position(|w| w == b"\r\n") {
+      Some(offset) => {
+        let end = from + offset;
+        let start = self.start;
+        self.start = end + 2;
+        self.searched = self.start;
+        Some(&self.buf[start..end])
+      }
+      None => {
+        self.searched = self.buf.len();
+        None
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::irc::Command;
+
+  const DATA: &[u8] = b"@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam\r\n:tmi.twitch.tv PING :tmi.twitch.tv\r\n\r\n:randers811!randers811@randers811.tmi.twitch.tv JOIN #pajlada\r\n";
+
+  fn commands(messages: &[IrcMessage]) -> Vec<Command<'_>> {
+    messages.iter().map(|m| m.command()).collect()
+  }
+
+  #[test]
+  fn whole_buffer() {
+    let mut decoder = MessageDecoder::new();
+    decoder.feed(DATA);
+    let messages = decoder.messages().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(
+      commands(&messages),
+      [Command::Privmsg, Command::Ping, Command::Join]
+    );
+    assert!(decoder.remaining().is_empty());
+  }
+
+  #[test]
+  fn split_cr_lf() {
+    let mut decoder = MessageDecoder::new();
+    decoder.feed(b":tmi.twitch.tv PING\r");
+    assert!(decoder.next_message().is_none());
+    assert_eq!(decoder.remaining(), b":tmi.twitch.tv PING\r");
+    decoder.feed(b"\n:tmi.twitch.tv PO");
+    assert_eq!(
+      decoder.next_message().unwrap().unwrap().command(),
+      Command::Ping
+    );
+    assert!(decoder.next_message().is_none());
+    assert_eq!(decoder.remaining(), b":tmi.twitch.tv PO");
+    decoder.feed(b"NG\r\n");
+    assert_eq!(
+      decoder.next_message().unwrap().unwrap().command(),
+      Command::Pong
+    );
+  }
+
+  #[test]
+  fn arbitrary_chunk_boundaries() {
+    for chunk_size in 1..DATA.len() {
+      let mut decoder = MessageDecoder::new();
+      let mut messages = vec![];
+      for chunk in DATA.chunks(chunk_size) {
+        decoder.feed(chunk);
+        messages.extend(decoder.messages().map(Result::unwrap));
+      }
+      assert_eq!(
+        commands(&messages),
+        [Command::Privmsg, Command::Ping, Command::Join],
+        "chunk size {chunk_size}"
+      );
+      assert_eq!(messages[0].text(), Some("dank cam"));
+      assert!(decoder.remaining().is_empty());
+    }
+  }
+
+  #[test]
+  fn invalid_utf8() {
+    let mut decoder = MessageDecoder::new();
+    decoder.feed(b":tmi.twitch.tv PRIVMSG #test :\xF0\x28\r\n@id=\xFF PING\r\n");
+    assert_eq!(
+      decoder.next_message().unwrap().unwrap_err(),
+      ParseError::InvalidUtf8(30)
+    );
+    assert_eq!(
+      decoder.next_message().unwrap().unwrap_err(),
+      ParseError::InvalidUtf8(4)
+    );
+  }
+}
@@ -25,3 +25,35 @@ macro_rules! whitelist {
     })
   )
 }
+
+/// Constructs a whitelist which allows every tag except the listed ones.
+///
+/// The tag names are the PascalCase variants of the [`Tag`][Tag] enum.
+/// Unknown tags are allowed.
+///
+/// ```rust
+/// let message = tmi::IrcMessageRef::parse_with_whitelist(
+///   "@display-name=Retoon;emotes=25:0-4;flags= :retoon!retoon@retoon.tmi.twitch.tv PRIVMSG #forsen :Kappa",
+///   tmi::blacklist!(Emotes, Flags),
+/// )
+/// .unwrap();
+/// assert_eq!(message.tag(tmi::Tag::DisplayName), Some("Retoon"));
+/// assert_eq!(message.tag(tmi::Tag::Emotes), None);
+/// ```
+///
+/// [Tag]: crate::irc::Tag
+#[macro_export]
+macro_rules! blacklist {
+  [$($tag:ident),*] => (
+    $crate::irc::Whitelist::<{$crate::irc::DEFAULT_TAG_CAPACITY}, _>::new({
+      #[allow(unused_variables)]
+      #[inline]
+      |src: &str, map: &mut $crate::irc::RawTags, tag: $crate::common::Span, value: $crate::common::Span| {
+        match src[tag].as_bytes() {
+          $($crate::irc::tags::$tag => {})*
+          _ => {map.push($crate::irc::RawTagPair($crate::irc::RawTag::parse(src, tag), value));}
+        };
+      }
+    })
+  )
+}
@@ -1,21 +1,24 @@
 use super::{RawPrefix, RawTags, Span, Whitelist};
 
+pub const BACKEND: &str = "scalar";
+
 /// `@a=a;b=b;c= :<rest>`
 #[inline(always)]
 pub fn parse_tags<const IC: usize, F>(
   src: &str,
   pos: &mut usize,
   whitelist: &Whitelist<IC, F>,
+  mut tags: RawTags,
 ) -> RawTags
 where
   F: Fn(&str, &mut RawTags, Span, Span),
 {
   if !src[*pos..].starts_with('@') {
-    return RawTags::new();
+    return tags;
   }
 
   let start = *pos + 1;
-  let mut tags = RawTags::with_capacity(IC);
+  tags.reserve(IC);
   let mut key = Span::from(start..0);
   let mut value = Span::from(0..0);
   let mut end = 0;
@@ -23,9 +26,9 @@ where
   let bytes = src.as_bytes();
   for i in start..bytes.len() {
     match unsafe { *bytes.get_unchecked(i) } {
-      b' ' if unsafe { *bytes.get_unchecked(i + 1) } == b':' => {
+      b' ' => {
         value.end = i as u32;
-        if key.end - key.start > 0 {
+        if key.end > key.start {
           whitelist.maybe_insert(src, &mut tags, key, value);
         }
         end = i + 1;
@@ -42,7 +45,9 @@ where
         let i = i as u32;
 
         value.end = i;
-        whitelist.maybe_insert(src, &mut tags, key, value);
+        if key.end > key.start {
+          whitelist.maybe_insert(src, &mut tags, key, value);
+        }
         key.start = i + 1;
         key.end = i + 1;
       }
@@ -50,52 +55,73 @@ where
     }
   }
 
+  // the tags were not terminated by a ` `, so they span the rest of the message
+  if end == 0 {
+    value.end = bytes.len() as u32;
+    if key.end > key.start {
+      whitelist.maybe_insert(src, &mut tags, key, value);
+    }
+    end = bytes.len();
+  }
+
   *pos = end;
 
   tags
 }
 
+/// `@a=a;b=b;c= :<rest>`
+#[inline(always)]
+pub fn count_tags(src: &str) -> usize {
+  let Some(tags) = src.strip_prefix('@') else {
+    return 0;
+  };
+  let tags = tags.split_once(' ').map_or(tags, |(tags, _)| tags);
+  match tags.is_empty() {
+    true => 0,
+    false => tags.bytes().filter(|&b| b == b';').count() + 1,
+  }
+}
+
 /// `:nick!user@host <rest>`
+///
+/// The prefix may also be `:nick@host` or `:host`.
+/// Anything else, such as `:nick!host` or `:nick@host!user`, is rejected.
 #[inline(always)]
 pub fn parse_prefix(src: &str, pos: &mut usize) -> Option<RawPrefix> {
   if !src[*pos..].starts_with(':') {
     return None;
   }
 
-  // :host <rest>
-  // :nick@host <rest>
-  // :nick!user@host <rest>
-  let bytes = src.as_bytes();
-
   let start = *pos + 1;
-  let mut host_start = start;
-  let mut nick = None;
-  let mut nick_end = None;
-  let mut user = None;
-  for i in start..bytes.len() {
-    match unsafe { *bytes.get_unchecked(i) } {
-      b' ' => {
-        let host = Span::from(host_start..i);
-        *pos = i + 1;
-        return Some(RawPrefix { nick, user, host });
-      }
-      b'@' => {
-        host_start = i + 1;
-        if let Some(nick_end) = nick_end {
-          user = Some(Span::from(nick_end + 1..i));
-        } else {
-          nick = Some(Span::from(start..i));
-        }
-      }
-      b'!' => {
-        nick = Some(Span::from(start..i));
-        nick_end = Some(i);
-      }
-      _ => {}
-    }
-  }
-
-  None
+  let end = start + src[start..].find(' ')?;
+  let prefix = &src[start..end];
+
+  // `!` and `@` are ASCII, so `start + i` is always a char boundary
+  let prefix = match (prefix.find('!'), prefix.find('@')) {
+    // :host <rest>
+    (None, None) => RawPrefix {
+      nick: None,
+      user: None,
+      host: Span::from(start..end),
+    },
+    // :nick@host <rest>
+    (None, Some(at)) => RawPrefix {
+      nick: Some(Span::from(start..start + at)),
+      user: None,
+      host: Span::from(start + at + 1..end),
+    },
+    // :nick!user@host <rest>
+    (Some(bang), Some(at)) if bang < at => RawPrefix {
+      nick: Some(Span::from(start..start + bang)),
+      user: Some(Span::from(start + bang + 1..start + at)),
+      host: Span::from(start + at + 1..end),
+    },
+    _ => return None,
+  };
+
+  *pos = end + 1;
+
+  Some(prefix)
 }
 
 #[cfg(test)]
@@ -109,7 +135,12 @@ mod tests {
     let data = "@login=test;id=asdf :<rest>";
     let mut pos = 0;
 
-    let tags = parse_tags(data, &mut pos, &Whitelist::<16, _>(whitelist_insert_all));
+    let tags = parse_tags(
+      data,
+      &mut pos,
+      &Whitelist::<16, _>(whitelist_insert_all),
+      RawTags::new(),
+    );
     assert_eq!(pos, 20);
     let tags = tags
       .into_iter()
@@ -118,12 +149,54 @@ mod tests {
     assert_eq!(&tags[..], &[(Tag::Login, "test"), (Tag::Id, "asdf")])
   }
 
+  #[test]
+  fn tags_without_prefix() {
+    let data = "@client-nonce=abc PRIVMSG #forsen :yo";
+    let mut pos = 0;
+
+    let tags = parse_tags(
+      data,
+      &mut pos,
+      &Whitelist::<16, _>(whitelist_insert_all),
+      RawTags::new(),
+    );
+    assert_eq!(&data[pos..], "PRIVMSG #forsen :yo");
+    let tags = tags
+      .into_iter()
+      .map(|tag| tag.get(data))
+      .collect::<Vec<_>>();
+    assert_eq!(&tags[..], &[(Tag::ClientNonce, "abc")]);
+
+    let data = "@mod=0;id=1000";
+    let mut pos = 0;
+    let tags = parse_tags(
+      data,
+      &mut pos,
+      &Whitelist::<16, _>(whitelist_insert_all),
+      RawTags::new(),
+    );
+    assert_eq!(pos, data.len());
+    assert_eq!(tags.len(), 2);
+
+    // a trailing ` ` used to be read past
+    let data = "@mod=0 ";
+    let mut pos = 0;
+    let tags = parse_tags(
+      data,
+      &mut pos,
+      &Whitelist::<16, _>(whitelist_insert_all),
+      RawTags::new(),
+    );
+    assert_eq!(pos, data.len());
+    assert_eq!(tags.len(), 1);
+  }
+
   #[test]
   fn whitelist_tags() {
     let data = "@login=test;id=asdf :<rest>";
     let mut pos = 0;
 
-    let tags = parse_tags(data, &mut pos, &whitelist!(Login));
+    let tags = parse_tags(data, &mut pos, &whitelist!(Login), RawTags::new());
     assert_eq!(pos, 20);
     let tags = tags
       .into_iter()
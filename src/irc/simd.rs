@@ -1,8 +1,16 @@
+//! SIMD implementations of the parser.
+//!
+//! Each implementation only provides the character searches, see [`Search`].
+//! The tag parsing logic built on top of them is shared.
+
+use crate::irc::{RawTags, Span, Whitelist};
+use std::ops::Add;
+
 #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
 pub(super) mod x86_sse;
 
 #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
-pub(super) use x86_sse::*;
+pub(super) use x86_sse::{parse_prefix, Sse2 as Backend};
 
 #[cfg(all(target_arch = "x86_64", not(target_feature = "sse2")))]
 const _: () = {
@@ -13,9 +21,322 @@ const _: () = {
 pub(super) mod arm_neon;
 
 #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
-pub(super) use arm_neon::*;
+pub(super) use arm_neon::{parse_prefix, Neon as Backend};
 
 #[cfg(all(target_arch = "aarch64", not(target_feature = "neon")))]
 const _: () = {
   compile_error!("cannot use SIMD - your CPU does not support Neon");
 };
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub(super) mod wasm_simd128;
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub(super) use wasm_simd128::{parse_prefix, Simd128 as Backend};
+
+#[cfg(all(target_arch = "wasm32", not(target_feature = "simd128")))]
+const _: () = {
+  compile_error!("cannot use SIMD - your target does not support simd128");
+};
+
+/// Name of the backend which was compiled in.
+pub(super) const BACKEND: &str = <Backend as Search>::NAME;
+
+/// The character searches used to parse tags, implemented by each SIMD backend.
+trait Search {
+  /// Name of the backend, see [`simd_backend`][crate::irc::simd_backend].
+  const NAME: &'static str;
+
+  /// Find the first `=` character in `s`.
+  fn find_equals(s: &str) -> Option<usize>;
+
+  /// Find the first `;` or ` ` character in `s`.
+  ///
+  /// If both are present in `s`, the earlier one is returned.
+  fn find_semi_or_space(s: &str) -> Option<Found>;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Found {
+  Semi(usize),
+  Space(usize),
+}
+
+impl Add<usize> for Found {
+  type Output = Self;
+
+  #[inline(always)]
+  fn add(self, rhs: usize) -> Self::Output {
+    match self {
+      Found::Semi(v) => Found::Semi(v + rhs),
+      Found::Space(v) => Found::Space(v + rhs),
+    }
+  }
+}
+
+impl Add<Found> for usize {
+  type Output = Found;
+
+  #[inline(always)]
+  fn add(self, rhs: Found) -> Self::Output {
+    match rhs {
+      Found::Semi(v) => Found::Semi(self + v),
+      Found::Space(v) => Found::Space(self + v),
+    }
+  }
+}
+
+/// Parse IRC message tags:
+///
+/// `@key=value;other=etc `
+///
+/// Tags consist of semicolon-separated key-value pairs.
+/// The tag list is terminated by a ` ` character.
+#[inline(always)]
+pub(super) fn parse_tags<const IC: usize, F>(
+  src: &str,
+  pos: &mut usize,
+  whitelist: &Whitelist<IC, F>,
+  tags: RawTags,
+) -> RawTags
+where
+  F: Fn(&str, &mut RawTags, Span, Span),
+{
+  parse_tags_with::<Backend, IC, F>(src, pos, whitelist, tags)
+}
+
+/// Count the IRC message tags without parsing them:
+///
+/// `@key=value;other=etc `
+///
+/// This counts the `;` characters before the ` ` which terminates the tag list.
+#[inline(always)]
+pub(super) fn count_tags(src: &str) -> usize {
+  count_tags_with::<Backend>(src)
+}
+
+#[inline(always)]
+fn parse_tags_with<S: Search, const IC: usize, F>(
+  src: &str,
+  pos: &mut usize,
+  whitelist: &Whitelist<IC, F>,
+  mut tags: RawTags,
+) -> RawTags
+where
+  F: Fn(&str, &mut RawTags, Span, Span),
+{
+  if !src[*pos..].starts_with('@') {
+    return tags;
+  }
+
+  // pre-allocate space for the tags
+  // this uses a configurable default `IC`, which stands for `Initial Capacity`.
+  // the library supports "whitelisting" tags, in which case we know the total
+  // capacity we will ever need and can pre-allocate it.
+  // in case we don't have a whitelist, then this will allocate 16 slots.
+  // `tags` may be a reused buffer, in which case it usually has enough capacity already.
+  tags.reserve(IC);
+
+  let mut key_start = *pos + 1;
+  loop {
+    // each tag ends at a `;`, or at the ` ` which ends the tags.
+    let (tag_end, last) = match S::find_semi_or_space(&src[key_start..]) {
+      Some(Found::Semi(i)) => (key_start + i, false),
+      Some(Found::Space(i)) => (key_start + i, true),
+      None => (src.len(), true),
+    };
+
+    // the key ends at the first `=` in the tag, any further `=` are part of the value.
+    // tags without a `=` or with an empty key are malformed, and skipped.
+    if let Some(key_end) = S::find_equals(&src[key_start..tag_end]) {
+      let key_end = key_start + key_end;
+      if key_end > key_start {
+        let key = Span::from(key_start..key_end);
+        let value = Span::from(key_end + 1..tag_end);
+        whitelist.maybe_insert(src, &mut tags, key, value);
+      }
+    }
+
+    if last {
+      // advance to after the ` `, if there is one
+      *pos = (tag_end + 1).min(src.len());
+      break;
+    }
+    // advance to after the `;`
+    key_start = tag_end + 1;
+  }
+
+  tags
+}
+
+#[inline(always)]
+fn count_tags_with<S: Search>(src: &str) -> usize {
+  let Some(tags) = src.strip_prefix('@') else {
+    return 0;
+  };
+  if tags.is_empty() || tags.starts_with(' ') {
+    return 0;
+  }
+
+  let mut count = 1;
+  let mut pos = 0;
+  while let Some(Found::Semi(end)) = S::find_semi_or_space(&tags[pos..]) {
+    count += 1;
+    pos += end + 1;
+  }
+  count
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::irc::whitelist_insert_all;
+
+  macro_rules! make {
+    ($($key:ident: $value:expr),* $(,)?) => (
+      [
+        $(($crate::Tag::$key, $value)),*
+      ].into_iter().collect::<Vec<_>>()
+    );
+  }
+
+  /// Run the tests below against each backend which can be compiled for the target.
+  macro_rules! backend_tests {
+    ($($(#[$meta:meta])* $name:ident: $backend:ty),* $(,)?) => {$(
+      $(#[$meta])*
+      mod $name {
+        #[test]
+        fn searches() {
+          super::searches::<$backend>();
+        }
+
+        #[test]
+        fn tags() {
+          super::tags::<$backend>();
+        }
+
+        #[test]
+        fn tags_whitelist() {
+          super::tags_whitelist::<$backend>();
+        }
+
+        #[test]
+        fn tags_count() {
+          super::tags_count::<$backend>();
+        }
+      }
+    )*};
+  }
+
+  backend_tests! {
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    sse2: crate::irc::simd::x86_sse::Sse2,
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    neon: crate::irc::simd::arm_neon::Neon,
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    simd128: crate::irc::simd::wasm_simd128::Simd128,
+  }
+
+  /// Compare the searches against `str::find`, with the needle at every position
+  /// of inputs which span multiple chunks.
+  fn searches<S: Search>() {
+    for len in 0..70 {
+      let haystack = "a".repeat(len);
+      assert_eq!(S::find_equals(&haystack), None);
+      assert_eq!(S::find_semi_or_space(&haystack), None);
+
+      for at in 0..len {
+        let mut haystack = haystack.clone().into_bytes();
+        haystack[at] = b'=';
+        let equals = String::from_utf8(haystack.clone()).unwrap();
+        assert_eq!(S::find_equals(&equals), Some(at), "{equals:?}");
+        assert_eq!(S::find_semi_or_space(&equals), None, "{equals:?}");
+
+        for (needle, found) in [(b';', Found::Semi(at)), (b' ', Found::Space(at))] {
+          haystack[at] = needle;
+          if at + 1 < len {
+            // a later delimiter of the other kind does not matter
+            haystack[len - 1] = if needle == b';' { b' ' } else { b';' };
+          }
+          let delimited = String::from_utf8(haystack.clone()).unwrap();
+          assert_eq!(
+            S::find_semi_or_space(&delimited),
+            Some(found),
+            "{delimited:?}"
+          );
+          assert_eq!(S::find_equals(&delimited), None, "{delimited:?}");
+        }
+      }
+    }
+  }
+
+  fn parse<'src, S: Search, const IC: usize, F>(
+    src: &'src str,
+    whitelist: &Whitelist<IC, F>,
+  ) -> (Vec<(crate::Tag<'src>, &'src str)>, usize)
+  where
+    F: Fn(&str, &mut RawTags, Span, Span),
+  {
+    let mut pos = 0;
+    let tags = parse_tags_with::<S, IC, F>(src, &mut pos, whitelist, RawTags::new())
+      .into_iter()
+      .map(|tag| tag.get(src))
+      .collect();
+    (tags, pos)
+  }
+
+  fn tags<S: Search>() {
+    let cases = [
+      ("", (vec![], "")),
+      ("@", (vec![], "")),
+      ("@ :asdf", (vec![], ":asdf")),
+      ("mod=0;id=1000", (vec![], "mod=0;id=1000")),
+      ("@mod=0;id=1000", (make! {Mod: "0", Id: "1000",}, "")),
+      ("@mod=0;id=1000 ", (make! {Mod: "0", Id: "1000",}, "")),
+      (
+        "@mod=0;id=1000 :asdf",
+        (make! {Mod: "0", Id: "1000",}, ":asdf"),
+      ),
+      (
+        "@mod=0;=x;flags;id=a=b :asdf;c=d",
+        (make! {Mod: "0", Id: "a=b",}, ":asdf;c=d"),
+      ),
+    ];
+
+    for (src, (expected_tags, expected_remainder)) in cases.into_iter() {
+      let (tags, pos) = parse::<S, 16, _>(src, &Whitelist::<16, _>(whitelist_insert_all));
+      assert_eq!(tags, expected_tags, "{src:?}");
+      assert_eq!(&src[pos..], expected_remainder, "{src:?}");
+    }
+  }
+
+  fn tags_whitelist<S: Search>() {
+    let cases = [
+      ("", (vec![], "")),
+      ("mod=0;id=1000", (vec![], "mod=0;id=1000")),
+      ("@mod=0;id=1000", (make! {Mod: "0"}, "")),
+      ("@mod=0;id=1000 ", (make! {Mod: "0"}, "")),
+      ("@mod=0;id=1000 :asdf", (make! {Mod: "0"}, ":asdf")),
+    ];
+
+    for (src, (expected_tags, expected_remainder)) in cases.into_iter() {
+      let (tags, pos) = parse::<S, 1, _>(src, &whitelist!(Mod));
+      assert_eq!(tags, expected_tags, "{src:?}");
+      assert_eq!(&src[pos..], expected_remainder, "{src:?}");
+    }
+  }
+
+  fn tags_count<S: Search>() {
+    let cases = [
+      ("", 0),
+      ("@", 0),
+      ("@ :tmi.twitch.tv PING", 0),
+      ("@mod=0", 1),
+      ("@mod=0;id=1000 :asdf;a=b", 2),
+    ];
+
+    for (src, expected) in cases {
+      assert_eq!(count_tags_with::<S>(src), expected, "{src:?}");
+    }
+  }
+}
@@ -7,7 +7,7 @@
 //!
 //! Archived link: https://web.archive.org/web/20230603011837/https://community.arm.com/arm-community-blogs/b/infrastructure-solutions-blog/posts/porting-x86-vector-bitmask-optimizations-to-arm-neon
 
-use crate::irc::{RawTags, Span, Whitelist};
+use super::Found;
 
 use core::arch::aarch64 as simd;
 use core::mem;
@@ -18,71 +18,21 @@ use std::ops::Add;
 /// because it was not faster. Instead just re-export the scalar impl.
 pub use crate::irc::scalar::parse_prefix;
 
-/// Parse IRC message tags:
-///
-/// `@key=value;other=etc `
-///
-/// Tags consist of semicolon-separated key-value pairs.
-/// The tag list is terminated by a ` ` character.
-#[inline(always)]
-pub fn parse_tags<const IC: usize, F>(
-  src: &str,
-  pos: &mut usize,
-  whitelist: &Whitelist<IC, F>,
-) -> RawTags
-where
-  F: Fn(&str, &mut RawTags, Span, Span),
-{
-  // This code is identical to the `x86_sse` version.
-  // It should not be duplicated, but seeing as there are only two SIMD implementations,
-  // I believe it is simpler to just copy the implementation, at least for now.
-  if !src[*pos..].starts_with('@') {
-    return RawTags::new();
-  }
+/// The Neon implementation of the character searches.
+pub struct Neon;
 
-  let mut tags = RawTags::with_capacity(IC);
+impl super::Search for Neon {
+  const NAME: &'static str = "neon";
 
-  let mut key_start = *pos + 1;
-  while !src[key_start..].is_empty() {
-    let Some(mut key_end) = find_equals(&src[key_start..]) else {
-      break;
-    };
-    key_end += key_start;
-
-    let value_start = key_end + 1;
-
-    match find_semi_or_space(&src[value_start..]) {
-      Some(Found::Semi(value_end)) => {
-        let value_end = value_end + value_start;
-        let key = Span::from(key_start..key_end);
-        let value = Span::from(value_start..value_end);
-        whitelist.maybe_insert(src, &mut tags, key, value);
-        // advance to after the `;`
-        key_start = value_end + 1;
-        continue;
-      }
-      Some(Found::Space(value_end)) => {
-        let value_end = value_end + value_start;
-        let key = Span::from(key_start..key_end);
-        let value = Span::from(value_start..value_end);
-        whitelist.maybe_insert(src, &mut tags, key, value);
-        // advance to after the ` `
-        key_start = value_end + 1;
-        break;
-      }
-      None => {
-        let key = Span::from(key_start..key_end);
-        let value = Span::from(value_start..src.len());
-        whitelist.maybe_insert(src, &mut tags, key, value);
-        key_start = src.len();
-        break;
-      }
-    }
+  #[inline(always)]
+  fn find_equals(s: &str) -> Option<usize> {
+    find_equals(s)
   }
 
-  *pos = key_start;
-
-  tags
+  #[inline(always)]
+  fn find_semi_or_space(s: &str) -> Option<Found> {
+    find_semi_or_space(s)
+  }
 }
 
 #[inline(always)]
@@ -162,36 +112,6 @@ fn find_equals(s: &str) -> Option<usize> {
   chunk16_test(s, test)
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-enum Found {
-  Semi(usize),
-  Space(usize),
-}
-
-impl Add<usize> for Found {
-  type Output = Self;
-
-  #[inline(always)]
-  fn add(self, rhs: usize) -> Self::Output {
-    match self {
-      Found::Semi(v) => Found::Semi(v + rhs),
-      Found::Space(v) => Found::Space(v + rhs),
-    }
-  }
-}
-
-impl Add<Found> for usize {
-  type Output = Found;
-
-  #[inline(always)]
-  fn add(self, rhs: Found) -> Self::Output {
-    match rhs {
-      Found::Semi(v) => Found::Semi(self + v),
-      Found::Space(v) => Found::Space(self + v),
-    }
-  }
-}
-
 /// Find the first `;` or ` ` character in `s`.
 ///
 /// If both are present in `s`, the one earlier one will be returned.
@@ -255,8 +175,6 @@ impl Mask {
 
 #[cfg(test)]
 mod tests {
-  use crate::irc::whitelist_insert_all;
-
   use super::*;
 
   #[test]
@@ -292,60 +210,6 @@ mod tests {
     }
   }
 
-  macro_rules! make {
-    ($($key:ident: $value:expr),* $(,)?) => (
-      [
-        $(($crate::Tag::$key, $value)),*
-      ].into_iter().collect::<Vec<_>>()
-    );
-  }
-
-  #[test]
-  fn tags() {
-    let cases = [
-      ("", (vec![], "")),
-      ("mod=0;id=1000", (vec![], "mod=0;id=1000")),
-      ("@mod=0;id=1000", (make! {Mod: "0", Id: "1000",}, "")),
-      ("@mod=0;id=1000 ", (make! {Mod: "0", Id: "1000",}, "")),
-      (
-        "@mod=0;id=1000 :asdf",
-        (make! {Mod: "0", Id: "1000",}, ":asdf"),
-      ),
-    ];
-
-    for (src, (expected_tags, expected_remainder)) in cases.into_iter() {
-      let mut pos = 0;
-      let actual_tags: Vec<_> =
-        parse_tags(src, &mut pos, &Whitelist::<16, _>(whitelist_insert_all))
-          .into_iter()
-          .map(|tag| tag.get(src))
-          .collect();
-      assert_eq!(actual_tags, expected_tags);
-      assert_eq!(&src[pos..], expected_remainder);
-    }
-  }
-
-  #[test]
-  fn tags_whitelist() {
-    let cases = [
-      ("", (vec![], "")),
-      ("mod=0;id=1000", (vec![], "mod=0;id=1000")),
-      ("@mod=0;id=1000", (make! {Mod: "0"}, "")),
-      ("@mod=0;id=1000 ", (make! {Mod: "0"}, "")),
-      ("@mod=0;id=1000 :asdf", (make! {Mod: "0"}, ":asdf")),
-    ];
-
-    for (src, (expected_tags, expected_remainder)) in cases.into_iter() {
-      let mut pos = 0;
-      let actual_tags: Vec<_> = parse_tags(src, &mut pos, &whitelist!(Mod))
-        .into_iter()
-        .map(|tag| tag.get(src))
-        .collect();
-      assert_eq!(actual_tags, expected_tags);
-      assert_eq!(&src[pos..], expected_remainder)
-    }
-  }
-
   #[test]
   fn prefix() {
     let data = ":nick!user@host <rest>";
@@ -0,0 +1,162 @@
+//! This implementation works exactly like the `x86_sse` one.
+//!
+//! `u8x16_bitmask` is the WASM equivalent of `movemask`, it produces
+//! a mask with one bit per lane, so finding the position of a character
+//! is done in the same way: by counting the trailing zeros of the mask.
+
+use super::Found;
+
+use core::arch::wasm32 as simd;
+use simd::v128;
+use std::ops::Add;
+
+/// We don't have a SIMD implementation of `parse_prefix` in WASM,
+/// so just re-export the scalar impl.
+pub use crate::irc::scalar::parse_prefix;
+
+/// The `simd128` implementation of the character searches.
+pub struct Simd128;
+
+impl super::Search for Simd128 {
+  const NAME: &'static str = "wasm-simd128";
+
+  #[inline(always)]
+  fn find_equals(s: &str) -> Option<usize> {
+    find_equals(s)
+  }
+
+  #[inline(always)]
+  fn find_semi_or_space(s: &str) -> Option<Found> {
+    find_semi_or_space(s)
+  }
+}
+
+#[inline(always)]
+fn chunk16_test<T, F>(s: &str, test: F) -> Option<T>
+where
+  T: Add<usize, Output = T>,
+  F: Fn(v128) -> Option<T>,
+{
+  // `v128_load` does not have any alignment requirements,
+  // so it is used both in the 16-byte chunk loop and for any trailing characters.
+
+  let bytes = s.as_bytes();
+
+  let mut i = 0usize;
+  while i + 16 <= bytes.len() {
+    let data = unsafe { simd::v128_load(bytes.as_ptr().add(i) as *const v128) };
+    if let Some(pos) = test(data) {
+      return Some(pos + i);
+    };
+    i += 16;
+  }
+  if i < bytes.len() {
+    let mut buf = [0u8; 16];
+    buf[..bytes.len() - i].copy_from_slice(&bytes[i..]); // memcpy
+
+    let data = unsafe { simd::v128_load(buf.as_ptr() as *const v128) };
+    if let Some(pos) = test(data) {
+      return Some(pos + i);
+    }
+  }
+
+  None
+}
+
+/// Find the first `=` character in `s`.
+///
+/// The implementation splits `s` into 16-byte chunks.
+///
+/// For each chunk, it compares each byte against the `=` character using `u8x16_eq`,
+/// and then obtains a mask with one bit per lane using `u8x16_bitmask`.
+/// If the mask is not empty, the position of the character is its trailing zeros.
+#[inline(always)]
+fn find_equals(s: &str) -> Option<usize> {
+  #[inline(always)]
+  fn test(data: v128) -> Option<usize> {
+    let mask = simd::u8x16_bitmask(simd::u8x16_eq(data, simd::u8x16_splat(b'=')));
+
+    if mask != 0 {
+      Some(mask.trailing_zeros() as usize)
+    } else {
+      None
+    }
+  }
+
+  chunk16_test(s, test)
+}
+
+/// Find the first `;` or ` ` character in `s`.
+///
+/// If both are present in `s`, the one earlier one will be returned.
+///
+/// This works exactly like `find_equals`, but performs two comparisons at a time
+/// in separate vectors, one for `;` and one for ` `.
+#[inline(always)]
+fn find_semi_or_space(s: &str) -> Option<Found> {
+  #[inline(always)]
+  fn test(data: v128) -> Option<Found> {
+    let semi_mask = simd::u8x16_bitmask(simd::u8x16_eq(data, simd::u8x16_splat(b';')));
+    let space_mask = simd::u8x16_bitmask(simd::u8x16_eq(data, simd::u8x16_splat(b' ')));
+
+    match (semi_mask != 0, space_mask != 0) {
+      (true, true) => {
+        let semi_tz = semi_mask.trailing_zeros() as usize;
+        let space_tz = space_mask.trailing_zeros() as usize;
+        if semi_tz < space_tz {
+          Some(Found::Semi(semi_tz))
+        } else {
+          Some(Found::Space(space_tz))
+        }
+      }
+      (true, false) => Some(Found::Semi(semi_mask.trailing_zeros() as usize)),
+      (false, true) => Some(Found::Space(space_mask.trailing_zeros() as usize)),
+      _ => None,
+    }
+  }
+
+  chunk16_test(s, test)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn equals() {
+    let cases = [
+      ("", None),
+      ("asdf=", Some(4)),
+      ("=asdf", Some(0)),
+      ("as=df", Some(2)),
+    ];
+
+    for (string, expected) in cases {
+      assert_eq!(find_equals(string), expected);
+    }
+  }
+
+  #[test]
+  fn semi_or_space() {
+    use Found::*;
+
+    let cases = [
+      ("", None),
+      (" ", Some(Space(0))),
+      (";", Some(Semi(0))),
+      (" ;", Some(Space(0))),
+      ("; ", Some(Semi(0))),
+      ("____________________; ", Some(Semi(20))),
+      ("____________________ ;", Some(Space(20))),
+    ];
+
+    for (string, expected) in cases {
+      assert_eq!(find_semi_or_space(string), expected);
+    }
+  }
+
+  #[test]
+  fn test_parse_data_0() {
+    crate::IrcMessage::parse(r"@badge-info=;badges=premium/1;color=#000000;display-name=Vicarun;emotes=;flags=;id=a0414f65-b471-46be-b6cc-f8d7cd0aa62c;login=vicarun;mod=0;msg-id=resub;msg-param-cumulative-months=20;msg-param-months=0;msg-param-multimonth-duration=1;msg-param-multimonth-tenure=0;msg-param-should-share-streak=0;msg-param-sub-plan-name=Channel\sSubscription\s(forsenlol);msg-param-sub-plan=Prime;msg-param-was-gifted=false;room-id=22484632;subscriber=1;system-msg=Vicarun\ssubscribed\swith\sPrime.\sThey've\ssubscribed\sfor\s20\smonths!;tmi-sent-ts=1685664553875;user-id=691811336;user-type= :tmi.twitch.tv USERNOTICE #forsen").unwrap();
+  }
+}
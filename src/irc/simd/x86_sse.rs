@@ -1,87 +1,26 @@
-use crate::irc::{RawPrefix, RawTags, Span, Whitelist};
+use super::Found;
+use crate::irc::{RawPrefix, Span};
 
 use core::arch::x86_64 as simd;
 use core::mem;
 use simd::__m128i;
 use std::ops::Add;
 
-/// Parse IRC message tags:
-///
-/// `@key=value;other=etc `
-///
-/// Tags consist of semicolon-separated key-value pairs.
-/// The tag list is terminated by a ` ` character.
-#[inline(always)]
-pub fn parse_tags<const IC: usize, F>(
-  src: &str,
-  pos: &mut usize,
-  whitelist: &Whitelist<IC, F>,
-) -> RawTags
-where
-  F: Fn(&str, &mut RawTags, Span, Span),
-{
-  if !src[*pos..].starts_with('@') {
-    return RawTags::new();
-  }
+/// The SSE2 implementation of the character searches.
+pub struct Sse2;
 
-  // pre-allocate space for the tags
-  // this uses a configurable default `IC`, which stands for `Initial Capacity`.
-  // the library supports "whitelisting" tags, in which case we know the total
-  // capacity we will ever need and can pre-allocate it.
-  // in case we don't have a whitelist, then this will allocate 16 slots.
-  let mut tags = RawTags::with_capacity(IC);
-
-  let mut key_start = *pos + 1;
-  while !src[key_start..].is_empty() {
-    let Some(mut key_end) = find_equals(&src[key_start..]) else {
-      // exit the loop if we don't find a key. this only happens if the input is malformed.
-      break;
-    };
-    key_end += key_start;
-
-    // `key_end` is inclusive, meaning `remainder[key_end] == '='`.
-    // value starts after the `=` character.
-    let value_start = key_end + 1;
-
-    // value ends at `;` or ` ` character.
-    match find_semi_or_space(&src[value_start..]) {
-      // if we found a semicolon, then insert the tag into the buffer,
-      // and attempt to find another tag.
-      Some(Found::Semi(value_end)) => {
-        let value_end = value_end + value_start;
-        let key = Span::from(key_start..key_end);
-        let value = Span::from(value_start..value_end);
-        whitelist.maybe_insert(src, &mut tags, key, value);
-        // advance to after the `;`
-        key_start = value_end + 1;
-        continue;
-      }
-      // if we found a space, then insert the tag into the buffer,
-      // and break out of the loop.
-      Some(Found::Space(value_end)) => {
-        let value_end = value_end + value_start;
-        let key = Span::from(key_start..key_end);
-        let value = Span::from(value_start..value_end);
-        whitelist.maybe_insert(src, &mut tags, key, value);
-        // advance to after the ` `
-        key_start = value_end + 1;
-        break;
-      }
-      // we've somehow found neither. this only happens if the input is malformed.
-      // we treat everything after the `=` as the value, and return an empty remainder.
-      None => {
-        let key = Span::from(key_start..key_end);
-        let value = Span::from(value_start..src.len());
-        whitelist.maybe_insert(src, &mut tags, key, value);
-        key_start = src.len();
-        break;
-      }
-    }
-  }
+impl super::Search for Sse2 {
+  const NAME: &'static str = "sse2";
 
-  *pos = key_start;
+  #[inline(always)]
+  fn find_equals(s: &str) -> Option<usize> {
+    find_equals(s)
+  }
 
-  tags
+  #[inline(always)]
+  fn find_semi_or_space(s: &str) -> Option<Found> {
+    find_semi_or_space(s)
+  }
 }
 
 /// This function splits `s` into 16-byte chunks, loads each chunk into a 128-bit vector,
@@ -172,36 +111,6 @@ fn find_equals(s: &str) -> Option<usize> {
   chunk16_test(s, test)
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-enum Found {
-  Semi(usize),
-  Space(usize),
-}
-
-impl Add<usize> for Found {
-  type Output = Self;
-
-  #[inline(always)]
-  fn add(self, rhs: usize) -> Self::Output {
-    match self {
-      Found::Semi(v) => Found::Semi(v + rhs),
-      Found::Space(v) => Found::Space(v + rhs),
-    }
-  }
-}
-
-impl Add<Found> for usize {
-  type Output = Found;
-
-  #[inline(always)]
-  fn add(self, rhs: Found) -> Self::Output {
-    match rhs {
-      Found::Semi(v) => Found::Semi(self + v),
-      Found::Space(v) => Found::Space(self + v),
-    }
-  }
-}
-
 /// Find the first `;` or ` ` character in `s`.
 ///
 /// If both are present in `s`, the one earlier one will be returned.
@@ -259,13 +168,21 @@ pub fn parse_prefix(src: &str, pos: &mut usize) -> Option<RawPrefix> {
   macro_rules! parse_chunk {
     ($i:ident, $at:ident, $bang:ident, $start:ident, $data:ident) => {
       let end_mask = unsafe { simd::_mm_movemask_epi8(simd::_mm_cmpeq_epi8($data, SPACE)) };
-      let at_mask = unsafe { simd::_mm_movemask_epi8(simd::_mm_cmpeq_epi8($data, AT)) };
-      let bang_mask = unsafe { simd::_mm_movemask_epi8(simd::_mm_cmpeq_epi8($data, BANG)) };
+      // `@` and `!` after the end of the prefix belong to the rest of the message
+      let before_end = match end_mask {
+        0 => !0,
+        _ => (1 << end_mask.trailing_zeros()) - 1,
+      };
+      let at_mask =
+        unsafe { simd::_mm_movemask_epi8(simd::_mm_cmpeq_epi8($data, AT)) } & before_end;
+      let bang_mask =
+        unsafe { simd::_mm_movemask_epi8(simd::_mm_cmpeq_epi8($data, BANG)) } & before_end;
 
-      if at_mask != 0 {
+      // only the first `@` and `!` delimit the prefix
+      if at_mask != 0 && $at == usize::MAX {
         $at = $i + at_mask.trailing_zeros() as usize
       };
-      if bang_mask != 0 {
+      if bang_mask != 0 && $bang == usize::MAX {
         $bang = $i + bang_mask.trailing_zeros() as usize
       };
 
@@ -285,6 +202,8 @@ pub fn parse_prefix(src: &str, pos: &mut usize) -> Option<RawPrefix> {
           },
           // nick!host -> invalid
           (_, usize::MAX) => return None,
+          // nick@host!user -> invalid
+          (bang, at) if bang > at => return None,
           (bang, at) => RawPrefix {
             nick: Some(Span::from($start..bang)),
             user: Some(Span::from(bang + 1..at)),
@@ -332,8 +251,6 @@ pub fn parse_prefix(src: &str, pos: &mut usize) -> Option<RawPrefix> {
 
 #[cfg(test)]
 mod tests {
-  use crate::irc::whitelist_insert_all;
-
   use super::*;
 
   #[test]
@@ -369,60 +286,6 @@ mod tests {
     }
   }
 
-  macro_rules! make {
-    ($($key:ident: $value:expr),* $(,)?) => (
-      [
-        $(($crate::Tag::$key, $value)),*
-      ].into_iter().collect::<Vec<_>>()
-    );
-  }
-
-  #[test]
-  fn tags() {
-    let cases = [
-      ("", (vec![], "")),
-      ("mod=0;id=1000", (vec![], "mod=0;id=1000")),
-      ("@mod=0;id=1000", (make! {Mod: "0", Id: "1000",}, "")),
-      ("@mod=0;id=1000 ", (make! {Mod: "0", Id: "1000",}, "")),
-      (
-        "@mod=0;id=1000 :asdf",
-        (make! {Mod: "0", Id: "1000",}, ":asdf"),
-      ),
-    ];
-
-    for (src, (expected_tags, expected_remainder)) in cases.into_iter() {
-      let mut pos = 0;
-      let actual_tags: Vec<_> =
-        parse_tags(src, &mut pos, &Whitelist::<16, _>(whitelist_insert_all))
-          .into_iter()
-          .map(|tag| tag.get(src))
-          .collect();
-      assert_eq!(actual_tags, expected_tags);
-      assert_eq!(&src[pos..], expected_remainder);
-    }
-  }
-
-  #[test]
-  fn tags_whitelist() {
-    let cases = [
-      ("", (vec![], "")),
-      ("mod=0;id=1000", (vec![], "mod=0;id=1000")),
-      ("@mod=0;id=1000", (make! {Mod: "0"}, "")),
-      ("@mod=0;id=1000 ", (make! {Mod: "0"}, "")),
-      ("@mod=0;id=1000 :asdf", (make! {Mod: "0"}, ":asdf")),
-    ];
-
-    for (src, (expected_tags, expected_remainder)) in cases.into_iter() {
-      let mut pos = 0;
-      let actual_tags: Vec<_> = parse_tags(src, &mut pos, &whitelist!(Mod))
-        .into_iter()
-        .map(|tag| tag.get(src))
-        .collect();
-      assert_eq!(actual_tags, expected_tags);
-      assert_eq!(&src[pos..], expected_remainder)
-    }
-  }
-
   #[test]
   fn prefix() {
     let data = ":nick!user@host <rest>";
@@ -1,9 +1,26 @@
 #![doc = include_str!("../README.md")]
 
-#[cfg(feature = "client")]
+#[cfg(feature = "tracing")]
 #[macro_use]
 extern crate tracing;
 
+// without the `tracing` feature, the client's events compile to nothing.
+#[cfg(not(feature = "tracing"))]
+#[allow(unused_macros)]
+macro_rules! trace {
+  ($($arg:tt)*) => {};
+}
+#[cfg(not(feature = "tracing"))]
+#[allow(unused_macros)]
+macro_rules! debug {
+  ($($arg:tt)*) => {};
+}
+#[cfg(not(feature = "tracing"))]
+#[allow(unused_macros)]
+macro_rules! warn {
+  ($($arg:tt)*) => {};
+}
+
 pub(crate) const fn assert_sync<T: ?Sized + Sync>() {}
 macro_rules! static_assert_sync {
   ($T:ty) => {
@@ -36,4 +53,4 @@ pub mod irc;
 pub use irc::*;
 
 pub mod common;
-pub use common::{Channel, ChannelRef};
+pub use common::{Channel, ChannelRef, DedupFilter};
@@ -27,6 +27,61 @@ impl<'src> IrcMessageRef<'src> {
   }
 }
 
+/// An owned [`IrcMessage`] together with its typed [`Message`] view.
+///
+/// Typed messages borrow from the message they were parsed from, so this keeps
+/// the [`IrcMessage`] and provides the typed view through [`TypedMessage::message`].
+/// The message is checked to parse when the `TypedMessage` is created.
+///
+/// ```rust
+/// use tmi::{IrcMessage, Message, TypedMessage};
+///
+/// let irc = IrcMessage::parse(":tmi.twitch.tv PING :tmi.twitch.tv").unwrap();
+/// let typed = TypedMessage::new(irc).unwrap();
+/// assert!(matches!(typed.message(), Message::Ping(_)));
+/// ```
+#[derive(Debug)]
+pub struct TypedMessage {
+  irc: IrcMessage,
+}
+
+impl TypedMessage {
+  /// Check that `irc` parses into a [`Message`], and wrap it.
+  ///
+  /// Commands without a typed variant are accepted as [`Message::Other`].
+  pub fn new(irc: IrcMessage) -> Result<Self, MessageParseError> {
+    irc.as_typed()?;
+    Ok(Self { irc })
+  }
+
+  /// The typed view of the message.
+  pub fn message(&self) -> Message<'_> {
+    // the message was successfully parsed in `new`, and parsing is deterministic
+    self
+      .irc
+      .as_typed()
+      .expect("a TypedMessage always parses into a Message")
+  }
+
+  /// The underlying [`IrcMessage`].
+  pub fn irc(&self) -> &IrcMessage {
+    &self.irc
+  }
+
+  /// Take the underlying [`IrcMessage`].
+  pub fn into_irc(self) -> IrcMessage {
+    self.irc
+  }
+}
+
+impl TryFrom<IrcMessage> for TypedMessage {
+  type Error = MessageParseError;
+
+  fn try_from(value: IrcMessage) -> Result<Self, Self::Error> {
+    Self::new(value)
+  }
+}
+
 /// Implemented for types which may be parsed from a base [`IrcMessage`].
 pub trait FromIrc<'src>: Sized + private::Sealed {
   /// Attempt to parse `Self` from an [`IrcMessage`].
@@ -38,10 +93,14 @@ pub trait FromIrc<'src>: Sized + private::Sealed {
 /// Note that this one
 #[derive(Clone, Debug)]
 pub enum Message<'src> {
+  Cap(Cap<'src>),
   ClearChat(ClearChat<'src>),
   ClearMsg(ClearMsg<'src>),
+  EndOfNames(EndOfNames<'src>),
   GlobalUserState(GlobalUserState<'src>),
+  HostTarget(HostTarget<'src>),
   Join(Join<'src>),
+  Names(Names<'src>),
   Notice(Notice<'src>),
   Part(Part<'src>),
   Ping(Ping<'src>),
@@ -88,10 +147,14 @@ impl<'src> FromIrc<'src> for Message<'src> {
   fn from_irc(message: IrcMessageRef<'src>) -> Result<Self, MessageParseError> {
     use crate::irc::Command as C;
     Ok(match message.command() {
+      C::Capability => Cap::from_irc(message)?.into(),
       C::ClearChat => ClearChat::from_irc(message)?.into(),
       C::ClearMsg => ClearMsg::from_irc(message)?.into(),
       C::GlobalUserState => GlobalUserState::from_irc(message)?.into(),
+      C::HostTarget => HostTarget::from_irc(message)?.into(),
       C::Join => Join::from_irc(message)?.into(),
+      C::RplNames => Names::from_irc(message)?.into(),
+      C::RplEndOfNames => EndOfNames::from_irc(message)?.into(),
       C::Notice => Notice::from_irc(message)?.into(),
       C::Part => Part::from_irc(message)?.into(),
       C::Ping => Ping::from_irc(message)?.into(),
@@ -137,10 +200,122 @@ pub enum Badge<'src> {
 }
 
 impl<'src> Badge<'src> {
+  /// Create a badge from its set ID and version, e.g. `moderator` and `1`.
+  ///
+  /// This is useful for badges which come from somewhere other than the `badges` tag,
+  /// such as the Helix API. Use [`parse_badges`] to parse the tags themselves.
+  ///
+  /// ```rust
+  /// use tmi::Badge;
+  ///
+  /// assert_eq!(Badge::parse("moderator", "1"), Badge::Moderator);
+  /// assert_eq!(Badge::parse("vip", "1").set_id(), "vip");
+  /// ```
+  pub fn parse(set_id: &'src str, version: &'src str) -> Self {
+    BadgeData {
+      name: Cow::Borrowed(set_id),
+      version: Cow::Borrowed(version),
+      extra: None,
+    }
+    .into()
+  }
+
   /// Get the base [`BadgeData`].
   pub fn as_badge_data(&self) -> BadgeData<'src> {
     BadgeData::from(self.clone())
   }
+
+  /// Name of the badge, e.g. `subscriber`.
+  pub fn name(&self) -> &str {
+    match self {
+      Badge::Staff => "staff",
+      Badge::Turbo => "turbo",
+      Badge::Broadcaster => "broadcaster",
+      Badge::Moderator => "moderator",
+      Badge::Subscriber(_) => "subscriber",
+      Badge::Other(data) => data.name(),
+    }
+  }
+
+  /// Set ID of the badge, e.g. `subscriber`.
+  ///
+  /// This is the same as [`Badge::name`], and is named after the field in the Helix API.
+  pub fn set_id(&self) -> &str {
+    self.name()
+  }
+
+  /// Version of the badge, e.g. `3012` for a `subscriber/3012` badge.
+  pub fn version(&self) -> &str {
+    match self {
+      Badge::Staff | Badge::Turbo | Badge::Broadcaster | Badge::Moderator => "1",
+      Badge::Subscriber(data) => data.version(),
+      Badge::Other(data) => data.version(),
+    }
+  }
+
+  /// Returns `true` if this badge is of the given `kind`, regardless of its version.
+  pub fn is(&self, kind: KnownBadge) -> bool {
+    self.name() == kind.as_str()
+  }
+}
+
+/// A well-known badge, without any version information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KnownBadge {
+  /// `staff`
+  Staff,
+  /// `admin`
+  Admin,
+  /// `global_mod`
+  GlobalMod,
+  /// `turbo`
+  Turbo,
+  /// `premium`
+  Premium,
+  /// `partner`
+  Partner,
+  /// `broadcaster`
+  Broadcaster,
+  /// `moderator`
+  Moderator,
+  /// `vip`
+  Vip,
+  /// `subscriber`
+  Subscriber,
+  /// `founder`
+  Founder,
+  /// `bits`
+  Bits,
+  /// `sub-gifter`
+  SubGifter,
+}
+
+impl KnownBadge {
+  /// Get the badge name, as it appears in the `badges` tag.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      KnownBadge::Staff => "staff",
+      KnownBadge::Admin => "admin",
+      KnownBadge::GlobalMod => "global_mod",
+      KnownBadge::Turbo => "turbo",
+      KnownBadge::Premium => "premium",
+      KnownBadge::Partner => "partner",
+      KnownBadge::Broadcaster => "broadcaster",
+      KnownBadge::Moderator => "moderator",
+      KnownBadge::Vip => "vip",
+      KnownBadge::Subscriber => "subscriber",
+      KnownBadge::Founder => "founder",
+      KnownBadge::Bits => "bits",
+      KnownBadge::SubGifter => "sub-gifter",
+    }
+  }
+}
+
+impl std::fmt::Display for KnownBadge {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.as_str())
+  }
 }
 
 impl<'src> From<Badge<'src>> for BadgeData<'src> {
@@ -278,6 +453,89 @@ generate_getters! {
     ///
     /// ⚠ This call will allocate and return a String if it needs to be unescaped.
     name -> Cow<'src, str> = maybe_unescape(self.name.clone()),
+
+    /// Display name, with the original tag value escaping intact.
+    name_escaped -> &str = self.name.as_ref(),
+  }
+}
+
+/// An RGB chat name color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+  /// Red channel.
+  pub r: u8,
+  /// Green channel.
+  pub g: u8,
+  /// Blue channel.
+  pub b: u8,
+}
+
+impl Color {
+  /// The default palette of name colors which Twitch assigns to users
+  /// who have not selected a color.
+  pub const TWITCH_DEFAULTS: [(&'static str, Color); 15] = [
+    ("Red", Color::rgb(0xFF, 0x00, 0x00)),
+    ("Blue", Color::rgb(0x00, 0x00, 0xFF)),
+    ("Green", Color::rgb(0x00, 0x80, 0x00)),
+    ("FireBrick", Color::rgb(0xB2, 0x22, 0x22)),
+    ("Coral", Color::rgb(0xFF, 0x7F, 0x50)),
+    ("YellowGreen", Color::rgb(0x9A, 0xCD, 0x32)),
+    ("OrangeRed", Color::rgb(0xFF, 0x45, 0x00)),
+    ("SeaGreen", Color::rgb(0x2E, 0x8B, 0x57)),
+    ("GoldenRod", Color::rgb(0xDA, 0xA5, 0x20)),
+    ("Chocolate", Color::rgb(0xD2, 0x69, 0x1E)),
+    ("CadetBlue", Color::rgb(0x5F, 0x9E, 0xA0)),
+    ("DodgerBlue", Color::rgb(0x1E, 0x90, 0xFF)),
+    ("HotPink", Color::rgb(0xFF, 0x69, 0xB4)),
+    ("BlueViolet", Color::rgb(0x8A, 0x2B, 0xE2)),
+    ("SpringGreen", Color::rgb(0x00, 0xFF, 0x7F)),
+  ];
+
+  /// Instantiate a color from its `r`, `g`, and `b` channels.
+  pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+    Self { r, g, b }
+  }
+
+  /// Parse a color in the `#RRGGBB` format, which is used by the `color` tag.
+  pub fn parse(s: &str) -> Option<Self> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+      return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Self::rgb(channel(0)?, channel(2)?, channel(4)?))
+  }
+
+  /// Get the name of the closest color in Twitch's [default palette][Color::TWITCH_DEFAULTS].
+  ///
+  /// This is useful for UIs which label colors by name.
+  pub fn nearest_twitch_default(&self) -> &'static str {
+    let distance = |other: &Color| {
+      let r = self.r as i32 - other.r as i32;
+      let g = self.g as i32 - other.g as i32;
+      let b = self.b as i32 - other.b as i32;
+      r * r + g * g + b * b
+    };
+    Self::TWITCH_DEFAULTS
+      .iter()
+      .min_by_key(|(_, color)| distance(color))
+      .map(|(name, _)| *name)
+      .unwrap_or("Red")
+  }
+}
+
+impl std::str::FromStr for Color {
+  type Err = MessageParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::parse(s).ok_or(MessageParseError)
+  }
+}
+
+impl std::fmt::Display for Color {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
   }
 }
 
@@ -285,16 +543,69 @@ fn is_not_empty<T: AsRef<str>>(s: &T) -> bool {
   !s.as_ref().is_empty()
 }
 
-fn parse_timestamp(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
-  use chrono::TimeZone;
-  chrono::Utc.timestamp_millis_opt(s.parse().ok()?).single()
+/// The time at which a message was sent.
+///
+/// This is stored as a [`DateTime`][chrono::DateTime] with the `chrono` feature,
+/// and as milliseconds since the Unix epoch without it.
+#[cfg(feature = "chrono")]
+type Timestamp = chrono::DateTime<chrono::Utc>;
+#[cfg(not(feature = "chrono"))]
+type Timestamp = u64;
+
+/// The largest `tmi-sent-ts` which `chrono` can represent.
+///
+/// Anything above it is rejected with and without the `chrono` feature.
+const MAX_TIMESTAMP_MILLIS: u64 = 8_210_298_412_799_999;
+
+/// Parse a `tmi-sent-ts` value.
+fn parse_timestamp(s: &str) -> Option<Timestamp> {
+  let millis: u64 = s
+    .parse()
+    .ok()
+    .filter(|&millis| millis <= MAX_TIMESTAMP_MILLIS)?;
+  #[cfg(feature = "chrono")]
+  {
+    use chrono::TimeZone;
+    chrono::Utc.timestamp_millis_opt(millis as i64).single()
+  }
+  #[cfg(not(feature = "chrono"))]
+  Some(millis)
+}
+
+#[cfg(feature = "chrono")]
+fn timestamp_millis(timestamp: &Timestamp) -> u64 {
+  timestamp.timestamp_millis() as u64
+}
+
+#[cfg(not(feature = "chrono"))]
+fn timestamp_millis(timestamp: &Timestamp) -> u64 {
+  *timestamp
+}
+
+fn to_system_time(timestamp: &Timestamp) -> std::time::SystemTime {
+  std::time::UNIX_EPOCH + std::time::Duration::from_millis(timestamp_millis(timestamp))
 }
 
 fn parse_duration(s: &str) -> Option<std::time::Duration> {
   Some(std::time::Duration::from_secs(s.parse().ok()?))
 }
 
-fn parse_message_text(input: &str) -> (&str, bool) {
+/// Strip the `\x01ACTION ` and `\x01` wrapper of a message sent with `/me`.
+///
+/// Returns the text, and whether it was an action. Text which is not fully wrapped
+/// is returned as-is.
+///
+/// ```rust
+/// use tmi::parse_message_text;
+///
+/// assert_eq!(parse_message_text("\u{0001}ACTION waves\u{0001}"), ("waves", true));
+/// assert_eq!(parse_message_text("waves"), ("waves", false));
+/// assert_eq!(
+///   parse_message_text("\u{0001}ACTION waves"),
+///   ("\u{0001}ACTION waves", false)
+/// );
+/// ```
+pub fn parse_message_text(input: &str) -> (&str, bool) {
   let Some(s) = input.strip_prefix("\u{0001}ACTION ") else {
     return (input, false);
   };
@@ -308,7 +619,20 @@ fn split_comma(s: &str) -> impl DoubleEndedIterator<Item = &str> + '_ {
   s.split(',')
 }
 
-fn parse_badges<'src>(badges: &'src str, badge_info: &'src str) -> Vec<Badge<'src>> {
+/// Parse the `badges` and `badge-info` tags into a list of [`Badge`]s.
+///
+/// Both are comma-separated lists of `<set_id>/<version>` pairs.
+/// Entries in `badge_info` are matched to badges by their set ID,
+/// and become the [`BadgeData::extra`] of that badge.
+///
+/// ```rust
+/// use tmi::{parse_badges, Badge};
+///
+/// let badges = parse_badges("moderator/1,subscriber/12", "subscriber/14");
+/// assert_eq!(badges[0], Badge::Moderator);
+/// assert!(matches!(&badges[1], Badge::Subscriber(sub) if sub.months() == 14));
+/// ```
+pub fn parse_badges<'src>(badges: &'src str, badge_info: &'src str) -> Vec<Badge<'src>> {
   if badges.is_empty() {
     return Vec::new();
   }
@@ -335,18 +659,26 @@ fn parse_badges<'src>(badges: &'src str, badge_info: &'src str) -> Vec<Badge<'sr
     .collect()
 }
 
-fn parse_bool(v: &str) -> bool {
-  v.parse::<u8>().ok().map(|n| n > 0).unwrap_or(false)
-}
-
+pub mod cap;
+pub use cap::*;
+pub mod cheers;
+pub use cheers::*;
 pub mod clear_chat;
 pub use clear_chat::*;
 pub mod clear_msg;
 pub use clear_msg::*;
+pub mod emotes;
+pub use emotes::*;
+pub mod flags;
+pub use flags::*;
 pub mod global_user_state;
 pub use global_user_state::*;
+pub mod host_target;
+pub use host_target::*;
 pub mod join;
 pub use join::*;
+pub mod names;
+pub use names::*;
 pub mod notice;
 pub use notice::*;
 pub mod part;
@@ -369,10 +701,14 @@ pub use whisper::*;
 mod private {
   pub trait Sealed {}
 }
+impl private::Sealed for Cap<'_> {}
 impl private::Sealed for ClearChat<'_> {}
 impl private::Sealed for ClearMsg<'_> {}
+impl private::Sealed for EndOfNames<'_> {}
 impl private::Sealed for GlobalUserState<'_> {}
+impl private::Sealed for HostTarget<'_> {}
 impl private::Sealed for Join<'_> {}
+impl private::Sealed for Names<'_> {}
 impl private::Sealed for Notice<'_> {}
 impl private::Sealed for Part<'_> {}
 impl private::Sealed for Ping<'_> {}
@@ -387,6 +723,96 @@ impl private::Sealed for Message<'_> {}
 static_assert_send!(Message<'_>);
 static_assert_sync!(Message<'_>);
 
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_badge_list() {
+    let badges = parse_badges("moderator/1,subscriber/12", "");
+    assert_eq!(
+      badges
+        .iter()
+        .map(|badge| (badge.set_id(), badge.version()))
+        .collect::<Vec<_>>(),
+      [("moderator", "1"), ("subscriber", "12")]
+    );
+    assert_eq!(badges[0], Badge::parse("moderator", "1"));
+    assert_eq!(badges[1], Badge::parse("subscriber", "12"));
+    assert!(badges[1].is(KnownBadge::Subscriber));
+
+    let badges = parse_badges("subscriber/3012,bits/100", "subscriber/14");
+    let Badge::Subscriber(sub) = &badges[0] else {
+      panic!("expected a subscriber badge, got {:?}", badges[0]);
+    };
+    assert_eq!(sub.version(), "3012");
+    assert_eq!(sub.months(), 14);
+    assert_eq!(badges[1].as_badge_data().extra(), None);
+    assert_eq!((badges[1].set_id(), badges[1].version()), ("bits", "100"));
+
+    assert!(parse_badges("", "subscriber/14").is_empty());
+  }
+
+  #[test]
+  fn parse_color() {
+    assert_eq!(Color::parse("#FF0000"), Some(Color::rgb(0xFF, 0, 0)));
+    assert_eq!(Color::parse("#8a2be2"), Some(Color::rgb(0x8A, 0x2B, 0xE2)));
+    assert_eq!(Color::parse(""), None);
+    assert_eq!(Color::parse("FF0000"), None);
+    assert_eq!(Color::parse("#FF00"), None);
+    assert_eq!(Color::parse("#GG0000"), None);
+    assert_eq!(Color::rgb(0x19, 0xE6, 0xE6).to_string(), "#19E6E6");
+  }
+
+  #[test]
+  fn nearest_twitch_default_color() {
+    assert_eq!(
+      Color::parse("#FF0000").unwrap().nearest_twitch_default(),
+      "Red"
+    );
+    assert_eq!(
+      Color::parse("#0000FE").unwrap().nearest_twitch_default(),
+      "Blue"
+    );
+    assert_eq!(
+      Color::parse("#FF6AB0").unwrap().nearest_twitch_default(),
+      "HotPink"
+    );
+  }
+
+  #[test]
+  fn parse_tmi_sent_ts() {
+    let timestamp = parse_timestamp("1594545155039").unwrap();
+    assert_eq!(timestamp_millis(&timestamp), 1594545155039);
+    assert_eq!(
+      to_system_time(&timestamp),
+      std::time::UNIX_EPOCH + std::time::Duration::from_millis(1594545155039)
+    );
+    assert_eq!(parse_timestamp(""), None);
+    assert_eq!(parse_timestamp("-1"), None);
+    assert_eq!(parse_timestamp("abc"), None);
+    assert!(parse_timestamp(&MAX_TIMESTAMP_MILLIS.to_string()).is_some());
+    assert_eq!(
+      parse_timestamp(&(MAX_TIMESTAMP_MILLIS + 1).to_string()),
+      None
+    );
+    assert_eq!(parse_timestamp(&u64::MAX.to_string()), None);
+  }
+
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn parse_tmi_sent_ts_chrono() {
+    assert_eq!(
+      parse_timestamp("1594545155039").map(|ts| ts.to_rfc3339()),
+      Some("2020-07-12T09:12:35.039+00:00".into())
+    );
+    assert_eq!(
+      chrono::DateTime::<chrono::Utc>::MAX_UTC.timestamp_millis(),
+      MAX_TIMESTAMP_MILLIS as i64
+    );
+  }
+}
+
 #[cfg(feature = "serde")]
 mod _serde {
   use super::*;
@@ -0,0 +1,112 @@
+//! Sent in response to a capability request or listing.
+
+use super::MessageParseError;
+use crate::irc::{Command, IrcMessageRef};
+use std::borrow::Cow;
+
+/// Sent in response to a capability request or listing.
+///
+/// ```text,ignore
+/// :tmi.twitch.tv CAP * ACK :twitch.tv/commands twitch.tv/tags
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cap<'src> {
+  subcommand: CapSubcommand,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  capabilities: Vec<Cow<'src, str>>,
+}
+
+generate_getters! {
+  <'src> for Cap<'src> as self {
+    /// The `CAP` subcommand.
+    subcommand -> CapSubcommand,
+
+    /// Iterator over the capabilities, e.g. `twitch.tv/tags`.
+    capabilities -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator
+      = self.capabilities.iter().map(|v| v.as_ref()),
+
+    /// Number of capabilities.
+    num_capabilities -> usize = self.capabilities.len(),
+  }
+}
+
+/// The subcommand of a [`Cap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CapSubcommand {
+  /// The requested capabilities were enabled.
+  Ack,
+
+  /// The requested capabilities were rejected.
+  Nak,
+
+  /// The list of capabilities supported by the server.
+  Ls,
+}
+
+impl<'src> Cap<'src> {
+  fn parse(message: IrcMessageRef<'src>) -> Option<Self> {
+    if message.command() != Command::Capability {
+      return None;
+    }
+
+    // `CAP * ACK :twitch.tv/commands twitch.tv/tags`
+    // the first param is the client identifier, the second one is the subcommand.
+    let subcommand = match message.params()?.split_whitespace().nth(1)? {
+      "ACK" => CapSubcommand::Ack,
+      "NAK" => CapSubcommand::Nak,
+      "LS" => CapSubcommand::Ls,
+      _ => return None,
+    };
+
+    Some(Cap {
+      subcommand,
+      capabilities: message
+        .text()
+        .map(|text| text.split_whitespace().map(Cow::Borrowed).collect())
+        .unwrap_or_default(),
+    })
+  }
+}
+
+impl<'src> super::FromIrc<'src> for Cap<'src> {
+  #[inline]
+  fn from_irc(message: IrcMessageRef<'src>) -> Result<Self, MessageParseError> {
+    Self::parse(message).ok_or(MessageParseError)
+  }
+}
+
+impl<'src> From<Cap<'src>> for super::Message<'src> {
+  fn from(msg: Cap<'src>) -> Self {
+    super::Message::Cap(msg)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_cap_ack() {
+    assert_irc_snapshot!(
+      Cap,
+      ":tmi.twitch.tv CAP * ACK :twitch.tv/commands twitch.tv/tags"
+    );
+  }
+
+  #[test]
+  fn parse_cap_nak() {
+    assert_irc_snapshot!(Cap, ":tmi.twitch.tv CAP * NAK :twitch.tv/invalid");
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn roundtrip_cap_ack() {
+    assert_irc_roundtrip!(
+      Cap,
+      ":tmi.twitch.tv CAP * ACK :twitch.tv/commands twitch.tv/tags"
+    );
+  }
+}
@@ -0,0 +1,79 @@
+//! Cheers in the text of a message which carries bits.
+//!
+//! A cheer is a word of the form `<prefix><amount>`, e.g. `Cheer100` or `Kappa500`.
+
+use std::ops::Range;
+
+/// A single cheer in a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cheer<'src> {
+  prefix: &'src str,
+  bits: u64,
+  range: Range<usize>,
+}
+
+generate_getters! {
+  <'src> for Cheer<'src> as self {
+    /// The cheermote prefix, e.g. `Cheer`.
+    prefix -> &'src str,
+
+    /// The number of bits cheered.
+    bits -> u64,
+
+    /// Byte range of the cheer in the message text.
+    range -> Range<usize>,
+  }
+}
+
+/// Find the cheers in `text`, in the order in which they appear.
+///
+/// The set of cheermotes depends on the channel, and is only available from the Twitch API.
+/// This yields every word which looks like a cheer: an alphanumeric prefix which does not
+/// end in a digit, followed by a non-zero amount of bits.
+pub fn parse_cheers(text: &str) -> impl Iterator<Item = Cheer<'_>> + '_ {
+  let mut start = 0;
+  text.split(' ').filter_map(move |word| {
+    let range = start..start + word.len();
+    start = range.end + 1;
+
+    let (prefix, amount) = word.split_at(word.trim_end_matches(|c: char| c.is_ascii_digit()).len());
+    if prefix.is_empty() || !prefix.chars().all(char::is_alphanumeric) || amount.starts_with('0') {
+      return None;
+    }
+    Some(Cheer {
+      prefix,
+      bits: amount.parse().ok()?,
+      range,
+    })
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cheers() {
+    let text = "Cheer100 hello  Kappa500 4Head1 Cheer100!";
+    let cheers: Vec<_> = parse_cheers(text).collect();
+    let parts: Vec<_> = cheers.iter().map(|c| (c.prefix(), c.bits())).collect();
+    assert_eq!(parts, [("Cheer", 100), ("Kappa", 500), ("4Head", 1)]);
+    assert_eq!(&text[cheers[1].range()], "Kappa500");
+    assert_eq!(&text[cheers[2].range()], "4Head1");
+  }
+
+  #[test]
+  fn not_cheers() {
+    for text in [
+      "",
+      "Cheer",
+      "100",
+      "Cheer0",
+      "Cheer05",
+      "@Cheer100",
+      "Cheer99999999999999999999",
+    ] {
+      assert_eq!(parse_cheers(text).count(), 0, "{text:?}");
+    }
+  }
+}
@@ -3,6 +3,7 @@
 use super::{parse_duration, parse_timestamp, MessageParseError};
 use crate::common::{ChannelRef, MaybeOwned};
 use crate::irc::{Command, IrcMessageRef, Tag};
+#[cfg(feature = "chrono")]
 use chrono::{DateTime, Utc};
 use std::borrow::Cow;
 use std::time::Duration;
@@ -20,7 +21,7 @@ pub struct ClearChat<'src> {
   #[cfg_attr(feature = "serde", serde(borrow))]
   action: Action<'src>,
 
-  timestamp: DateTime<Utc>,
+  timestamp: super::Timestamp,
 }
 
 generate_getters! {
@@ -35,7 +36,16 @@ generate_getters! {
     action -> &Action<'src> = &self.action,
 
     /// Time at which the [`ClearChat`] was executed on Twitch servers.
+    #[cfg(feature = "chrono")]
     timestamp -> DateTime<Utc>,
+
+    /// The [timestamp][`ClearChat::timestamp`] in milliseconds since the Unix epoch.
+    ///
+    /// This is available without the `chrono` feature.
+    timestamp_millis -> u64 = super::timestamp_millis(&self.timestamp),
+
+    /// The [timestamp][`ClearChat::timestamp`] as a [`SystemTime`][std::time::SystemTime].
+    timestamp_system -> std::time::SystemTime = super::to_system_time(&self.timestamp),
   }
 }
 
@@ -192,16 +202,19 @@ impl<'src> From<ClearChat<'src>> for super::Message<'src> {
 mod tests {
   use super::*;
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_clearchat_timeout() {
     assert_irc_snapshot!(ClearChat, "@ban-duration=1;room-id=11148817;target-user-id=148973258;tmi-sent-ts=1594553828245 :tmi.twitch.tv CLEARCHAT #pajlada :fabzeef");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_clearchat_ban() {
     assert_irc_snapshot!(ClearChat, "@room-id=11148817;target-user-id=70948394;tmi-sent-ts=1594561360331 :tmi.twitch.tv CLEARCHAT #pajlada :weeb123");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_clearchat_clear() {
     assert_irc_snapshot!(
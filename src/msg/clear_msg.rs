@@ -3,6 +3,7 @@
 use super::{parse_message_text, parse_timestamp, MessageParseError};
 use crate::common::{ChannelRef, MaybeOwned};
 use crate::irc::{Command, IrcMessageRef, Tag};
+#[cfg(feature = "chrono")]
 use chrono::{DateTime, Utc};
 use std::borrow::Cow;
 
@@ -27,7 +28,7 @@ pub struct ClearMsg<'src> {
 
   is_action: bool,
 
-  timestamp: DateTime<Utc>,
+  timestamp: super::Timestamp,
 }
 
 generate_getters! {
@@ -51,7 +52,16 @@ generate_getters! {
     is_action -> bool,
 
     /// Time at which the [`ClearMsg`] was executed on Twitch servers.
+    #[cfg(feature = "chrono")]
     timestamp -> DateTime<Utc>,
+
+    /// The [timestamp][`ClearMsg::timestamp`] in milliseconds since the Unix epoch.
+    ///
+    /// This is available without the `chrono` feature.
+    timestamp_millis -> u64 = super::timestamp_millis(&self.timestamp),
+
+    /// The [timestamp][`ClearMsg::timestamp`] as a [`SystemTime`][std::time::SystemTime].
+    timestamp_system -> std::time::SystemTime = super::to_system_time(&self.timestamp),
   }
 }
 
@@ -91,11 +101,13 @@ impl<'src> From<ClearMsg<'src>> for super::Message<'src> {
 mod tests {
   use super::*;
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_clearmsg_basic() {
     assert_irc_snapshot!(ClearMsg, "@login=alazymeme;room-id=;target-msg-id=3c92014f-340a-4dc3-a9c9-e5cf182f4a84;tmi-sent-ts=1594561955611 :tmi.twitch.tv CLEARMSG #pajlada :lole");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_clearmsg_action() {
     assert_irc_snapshot!(ClearMsg, "@login=alazymeme;room-id=;target-msg-id=3c92014f-340a-4dc3-a9c9-e5cf182f4a84;tmi-sent-ts=1594561955611 :tmi.twitch.tv CLEARMSG #pajlada :\u{0001}ACTION lole\u{0001}");
@@ -0,0 +1,176 @@
+//! Emote ranges from the `emotes` tag.
+//!
+//! The tag has the format `<id>:<start>-<end>,<start>-<end>/<id>:<start>-<end>`,
+//! where `start` and `end` are inclusive offsets into the message text.
+
+use std::ops::Range;
+
+/// The unit in which emote offsets are counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OffsetUnit {
+  /// UTF-16 code units, which is what Twitch uses.
+  Utf16,
+
+  /// Unicode scalar values, as returned by [`str::chars`].
+  Codepoint,
+
+  /// Detect the unit from the text and the ranges.
+  ///
+  /// Offsets in both units are resolved, and the unit for which every
+  /// range lands on a whole word in the text is used.
+  /// If both or neither of them do, then [`OffsetUnit::Utf16`] is used.
+  #[default]
+  Auto,
+}
+
+/// A single emote in a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Emote<'src> {
+  id: &'src str,
+  code: &'src str,
+  range: Range<usize>,
+}
+
+generate_getters! {
+  <'src> for Emote<'src> as self {
+    /// ID of the emote.
+    id -> &'src str,
+
+    /// The text of the emote, e.g. `Kappa`.
+    code -> &'src str,
+
+    /// Byte range of the emote in the message text.
+    range -> Range<usize>,
+  }
+}
+
+/// Parse the `raw` value of the `emotes` tag, resolving the offsets in `text` using `unit`.
+///
+/// Ranges which do not fit in `text` are skipped.
+/// The emotes are returned in the order in which they appear in `text`.
+pub fn parse_emotes<'src>(raw: &'src str, text: &'src str, unit: OffsetUnit) -> Vec<Emote<'src>> {
+  let ranges = parse_ranges(raw);
+  let unit = match unit {
+    OffsetUnit::Auto => detect_unit(&ranges, text),
+    unit => unit,
+  };
+
+  let mut emotes: Vec<_> = ranges
+    .into_iter()
+    .filter_map(|(id, start, end)| {
+      let range = resolve(text, start, end, unit)?;
+      Some(Emote {
+        id,
+        code: &text[range.clone()],
+        range,
+      })
+    })
+    .collect();
+  emotes.sort_by_key(|emote| emote.range.start);
+  emotes
+}
+
+fn parse_ranges(raw: &str) -> Vec<(&str, usize, usize)> {
+  raw
+    .split('/')
+    .filter_map(|emote| emote.split_once(':'))
+    .flat_map(|(id, ranges)| {
+      ranges.split(',').filter_map(move |range| {
+        let (start, end) = range.split_once('-')?;
+        let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+        (start <= end).then_some((id, start, end))
+      })
+    })
+    .collect()
+}
+
+/// Resolve the inclusive `start..=end` offsets counted in `unit` into a byte range in `text`.
+pub(super) fn resolve(
+  text: &str,
+  start: usize,
+  end: usize,
+  unit: OffsetUnit,
+) -> Option<Range<usize>> {
+  let mut offset = 0;
+  let mut byte_start = None;
+  for (i, c) in text.char_indices() {
+    if offset == start {
+      byte_start = Some(i);
+    }
+    let width = match unit {
+      OffsetUnit::Codepoint => 1,
+      _ => c.len_utf16(),
+    };
+    if offset <= end && end < offset + width {
+      // `end` must point at the last unit of a character
+      return match end == offset + width - 1 {
+        true => byte_start.map(|start| start..i + c.len_utf8()),
+        false => None,
+      };
+    }
+    offset += width;
+  }
+  None
+}
+
+fn detect_unit(ranges: &[(&str, usize, usize)], text: &str) -> OffsetUnit {
+  let aligned = |unit| {
+    ranges.iter().all(|&(_, start, end)| {
+      resolve(text, start, end, unit).is_some_and(|range| is_word(text, range))
+    })
+  };
+  match (aligned(OffsetUnit::Utf16), aligned(OffsetUnit::Codepoint)) {
+    (false, true) => OffsetUnit::Codepoint,
+    _ => OffsetUnit::Utf16,
+  }
+}
+
+/// Whether `range` in `text` is a whole word, not containing or touching any other characters.
+fn is_word(text: &str, range: Range<usize>) -> bool {
+  let before = text[..range.start].chars().next_back();
+  let after = text[range.end..].chars().next();
+  !text[range].contains(char::is_whitespace)
+    && before.is_none_or(char::is_whitespace)
+    && after.is_none_or(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn ascii_text() {
+    let text = "Kappa hello Keepo Kappa";
+    let emotes = parse_emotes("25:0-4,18-22/1902:12-16", text, OffsetUnit::Auto);
+    let codes: Vec<_> = emotes.iter().map(|e| (e.id(), e.code())).collect();
+    assert_eq!(codes, [("25", "Kappa"), ("1902", "Keepo"), ("25", "Kappa")]);
+    assert_eq!(emotes[1].range(), 12..17);
+  }
+
+  #[test]
+  fn detect_utf16_offsets_with_emoji() {
+    // `👋` is 1 codepoint, but 2 UTF-16 code units
+    let text = "👋 Kappa";
+    let emotes = parse_emotes("25:3-7", text, OffsetUnit::Auto);
+    assert_eq!(emotes.len(), 1);
+    assert_eq!(emotes[0].code(), "Kappa");
+
+    // with codepoint offsets, the range would be misaligned
+    assert!(parse_emotes("25:3-7", text, OffsetUnit::Codepoint).is_empty());
+  }
+
+  #[test]
+  fn detect_codepoint_offsets_with_emoji() {
+    let text = "👋 Kappa";
+    let emotes = parse_emotes("25:2-6", text, OffsetUnit::Auto);
+    assert_eq!(emotes.len(), 1);
+    assert_eq!(emotes[0].code(), "Kappa");
+  }
+
+  #[test]
+  fn out_of_bounds() {
+    assert!(parse_emotes("25:0-100", "Kappa", OffsetUnit::Auto).is_empty());
+    assert!(parse_emotes("25:4-0", "Kappa", OffsetUnit::Auto).is_empty());
+    assert!(parse_emotes("", "Kappa", OffsetUnit::Auto).is_empty());
+  }
+}
@@ -0,0 +1,104 @@
+//! AutoMod flags from the `flags` tag.
+//!
+//! The tag has the format `<start>-<end>:<category>.<score>/<category>.<score>,<start>-<end>:...`,
+//! where `start` and `end` are inclusive offsets into the message text.
+
+use super::emotes::resolve;
+use super::OffsetUnit;
+use std::ops::Range;
+
+/// A part of a message which was flagged by AutoMod.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutomodFlag {
+  range: Range<usize>,
+  category: char,
+  score: u8,
+}
+
+generate_getters! {
+  for AutomodFlag as self {
+    /// Byte range of the flagged text in the message text.
+    range -> Range<usize>,
+
+    /// The category of the flagged text:
+    /// - `A`: aggressive
+    /// - `I`: identity-based hate
+    /// - `P`: profanity
+    /// - `S`: sexual
+    category -> char,
+
+    /// How severe the flagged text is in this category, usually from `0` to `7`.
+    score -> u8,
+  }
+}
+
+/// Parse the `raw` value of the `flags` tag, resolving the offsets in `text`.
+///
+/// A range which is flagged in more than one category yields one flag per category.
+/// Ranges which do not fit in `text` are skipped.
+pub fn parse_automod_flags<'src>(
+  raw: &'src str,
+  text: &'src str,
+) -> impl Iterator<Item = AutomodFlag> + 'src {
+  raw
+    .split(',')
+    .filter_map(move |flag| {
+      let (range, categories) = flag.split_once(':')?;
+      let (start, end) = range.split_once('-')?;
+      let range = resolve(
+        text,
+        start.parse().ok()?,
+        end.parse().ok()?,
+        OffsetUnit::Utf16,
+      )?;
+      Some((range, categories))
+    })
+    .flat_map(|(range, categories)| {
+      categories.split('/').filter_map(move |category| {
+        let (category, score) = category.split_once('.')?;
+        let mut chars = category.chars();
+        let (Some(category), None) = (chars.next(), chars.next()) else {
+          return None;
+        };
+        Some(AutomodFlag {
+          range: range.clone(),
+          category,
+          score: score.parse().ok()?,
+        })
+      })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn single_flag() {
+    let text = "fuck you";
+    let flags: Vec<_> = parse_automod_flags("0-3:P.6", text).collect();
+    assert_eq!(flags.len(), 1);
+    assert_eq!(&text[flags[0].range()], "fuck");
+    assert_eq!(flags[0].category(), 'P');
+    assert_eq!(flags[0].score(), 6);
+  }
+
+  #[test]
+  fn multiple_flags() {
+    let text = "you are a dumb idiot";
+    let flags: Vec<_> = parse_automod_flags("10-13:A.3/P.5,15-19:A.7", text)
+      .map(|flag| (&text[flag.range()], flag.category(), flag.score()))
+      .collect();
+    assert_eq!(
+      flags,
+      [("dumb", 'A', 3), ("dumb", 'P', 5), ("idiot", 'A', 7)]
+    );
+  }
+
+  #[test]
+  fn invalid_flags() {
+    assert_eq!(parse_automod_flags("", "text").count(), 0);
+    assert_eq!(parse_automod_flags("0-100:P.6", "text").count(), 0);
+    assert_eq!(parse_automod_flags("0-3:PP.6/P.x/P.6", "text").count(), 1);
+  }
+}
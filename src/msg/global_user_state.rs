@@ -38,15 +38,30 @@ generate_getters! {
     /// ⚠ This call will allocate and return a String if it needs to be unescaped.
     name -> Cow<'src, str> = maybe_unescape(self.name.clone()),
 
+    /// Display name of the logged in user, with the original tag value escaping intact.
+    name_escaped -> &str = self.name.as_ref(),
+
+    /// ID of the logged in user.
+    ///
+    /// Same as [`GlobalUserState::id`].
+    user_id -> &str = self.id.as_ref(),
+
+    /// Display name of the logged in user.
+    ///
+    /// Same as [`GlobalUserState::name`].
+    ///
+    /// ⚠ This call will allocate and return a String if it needs to be unescaped.
+    display_name -> Cow<'src, str> = maybe_unescape(self.name.clone()),
+
     /// Iterator over global badges.
-    badges -> impl Iterator <Item = &Badge<'src>> + DoubleEndedIterator + ExactSizeIterator
+    badges -> impl DoubleEndedIterator<Item = &Badge<'src>> + ExactSizeIterator
       = self.badges.iter(),
 
     /// Number of global badges.
     num_badges -> usize = self.badges.len(),
 
     /// Iterator over emote sets which are available globally.
-    emote_sets -> impl Iterator<Item = &str> + DoubleEndedIterator + ExactSizeIterator
+    emote_sets -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator
       = self.emote_sets.iter().map(|v| v.as_ref()),
 
     /// Number of emote sets which are available globally.
@@ -0,0 +1,165 @@
+//! Sent when a channel starts or stops hosting another channel.
+//!
+//! Hosting has been removed from Twitch, so this is only relevant for old logs.
+
+use super::MessageParseError;
+use crate::common::{ChannelRef, MaybeOwned};
+use crate::irc::{Command, IrcMessageRef};
+use std::borrow::Cow;
+
+/// Sent when a channel starts or stops hosting another channel.
+///
+/// ```text,ignore
+/// :tmi.twitch.tv HOSTTARGET #hosting_channel :target_channel 10
+/// :tmi.twitch.tv HOSTTARGET #hosting_channel :- 0
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostTarget<'src> {
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  channel: MaybeOwned<'src, ChannelRef>,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  targets: Vec<Cow<'src, str>>,
+
+  viewers: Option<u64>,
+}
+
+generate_getters! {
+  <'src> for HostTarget<'src> as self {
+    /// Name of the hosting channel.
+    channel -> &ChannelRef = self.channel.as_ref(),
+
+    /// Login of the hosted channel.
+    ///
+    /// [`None`] means that the channel stopped hosting.
+    /// If there are multiple targets, this is the first one.
+    target -> Option<&str> = self.targets.first().map(|v| v.as_ref()),
+
+    /// Iterator over the logins of all hosted channels.
+    ///
+    /// Old auto-host messages may contain more than one target.
+    targets -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator
+      = self.targets.iter().map(|v| v.as_ref()),
+
+    /// Number of viewers sent along to the hosted channel.
+    viewers -> Option<u64>,
+  }
+}
+
+impl<'src> HostTarget<'src> {
+  fn parse(message: IrcMessageRef<'src>) -> Option<Self> {
+    if message.command() != Command::HostTarget {
+      return None;
+    }
+
+    // `<target> [<target>...] [<viewers>]`, where `<target>` is `-` when hosting stops.
+    // some sources prefix the target with `#`, like a channel name.
+    // the first token is always a target, even if its login is made up of digits.
+    let mut targets: Vec<_> = message
+      .text()?
+      .split_whitespace()
+      .map(|target| target.strip_prefix('#').unwrap_or(target))
+      .collect();
+    let viewers = match targets.get(1..).and_then(|rest| rest.last()?.parse().ok()) {
+      Some(viewers) => {
+        targets.pop();
+        Some(viewers)
+      }
+      None => None,
+    };
+    targets.retain(|target| *target != "-");
+
+    Some(HostTarget {
+      channel: MaybeOwned::Ref(message.channel()?),
+      targets: targets.into_iter().map(Cow::Borrowed).collect(),
+      viewers,
+    })
+  }
+}
+
+impl<'src> super::FromIrc<'src> for HostTarget<'src> {
+  #[inline]
+  fn from_irc(message: IrcMessageRef<'src>) -> Result<Self, MessageParseError> {
+    Self::parse(message).ok_or(MessageParseError)
+  }
+}
+
+impl<'src> From<HostTarget<'src>> for super::Message<'src> {
+  fn from(msg: HostTarget<'src>) -> Self {
+    super::Message::HostTarget(msg)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const HOSTTARGET_STOP: &str = ":tmi.twitch.tv HOSTTARGET #randers :- 0";
+
+  #[test]
+  fn parse_hosttarget_start() {
+    assert_irc_snapshot!(HostTarget, ":tmi.twitch.tv HOSTTARGET #randers :leebaxd 10");
+  }
+
+  #[test]
+  fn parse_hosttarget_stop() {
+    assert_irc_snapshot!(HostTarget, HOSTTARGET_STOP);
+  }
+
+  #[test]
+  fn parse_hosttarget_multiple_targets() {
+    let msg = crate::msg::macros::_parse_irc::<HostTarget>(
+      ":tmi.twitch.tv HOSTTARGET #randers :leebaxd forsen pajlada 5",
+    );
+    assert_eq!(msg.target(), Some("leebaxd"));
+    assert_eq!(
+      msg.targets().collect::<Vec<_>>(),
+      ["leebaxd", "forsen", "pajlada"]
+    );
+    assert_eq!(msg.viewers(), Some(5));
+
+    let msg = crate::msg::macros::_parse_irc::<HostTarget>(
+      ":tmi.twitch.tv HOSTTARGET #randers :leebaxd forsen",
+    );
+    assert_eq!(msg.targets().len(), 2);
+    assert_eq!(msg.viewers(), None);
+  }
+
+  #[test]
+  fn parse_hosttarget_channel_target() {
+    let msg = crate::msg::macros::_parse_irc::<HostTarget>(
+      ":tmi.twitch.tv HOSTTARGET #randers :#leebaxd 10",
+    );
+    assert_eq!(msg.channel().as_str(), "#randers");
+    assert_eq!(msg.target(), Some("leebaxd"));
+    assert_eq!(msg.viewers(), Some(10));
+
+    let msg = crate::msg::macros::_parse_irc::<HostTarget>(HOSTTARGET_STOP);
+    assert_eq!(msg.target(), None);
+    assert_eq!(msg.viewers(), Some(0));
+
+    let msg =
+      crate::msg::macros::_parse_irc::<HostTarget>(":tmi.twitch.tv HOSTTARGET #randers :12345");
+    assert_eq!(msg.target(), Some("12345"));
+    assert_eq!(msg.viewers(), None);
+
+    let msg =
+      crate::msg::macros::_parse_irc::<HostTarget>(":tmi.twitch.tv HOSTTARGET #randers :12345 10");
+    assert_eq!(msg.target(), Some("12345"));
+    assert_eq!(msg.viewers(), Some(10));
+
+    let msg =
+      crate::msg::Message::parse(":tmi.twitch.tv HOSTTARGET #randers :#leebaxd 10").unwrap();
+    assert!(matches!(msg, crate::msg::Message::HostTarget(v) if v.target() == Some("leebaxd")));
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn roundtrip_hosttarget() {
+    assert_irc_roundtrip!(
+      HostTarget,
+      ":tmi.twitch.tv HOSTTARGET #randers :leebaxd forsen 10"
+    );
+  }
+}
@@ -28,23 +28,25 @@ pub(crate) fn _parse_irc<'src, T: crate::msg::FromIrc<'src>>(input: &'src str) -
   <T as crate::msg::FromIrc>::from_irc(raw).unwrap()
 }
 
+/// Snapshots which contain a timestamp show it as `chrono` formats it,
+/// so the tests for them only run with the `chrono` feature.
 #[cfg(test)]
 macro_rules! assert_irc_snapshot {
-  ($T:ty, $input:literal,) => {
+  ($T:ty, $input:expr,) => {
     assert_irc_snapshot!($T, $input)
   };
-  ($T:ty, $input:literal) => {{
+  ($T:ty, $input:expr) => {{
     let f = $crate::msg::macros::_parse_irc::<$T>;
-    ::insta::assert_debug_snapshot!(f($input))
+    ::insta::assert_debug_snapshot!(f($input));
   }};
 }
 
 #[cfg(all(test, feature = "serde"))]
 macro_rules! assert_irc_roundtrip {
-  ($T:ty, $input:literal,) => {
+  ($T:ty, $input:expr,) => {
     assert_irc_roundtrip!($T, $input)
   };
-  ($T:ty, $input:literal) => {{
+  ($T:ty, $input:expr) => {{
     let original = $crate::msg::macros::_parse_irc::<$T>($input);
     let serialized = ::serde_json::to_string(&original).expect("failed to serialize");
     let deserialized = ::serde_json::from_str(&serialized).expect("failed to deserialize");
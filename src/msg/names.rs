@@ -0,0 +1,163 @@
+//! Sent when joining a channel, listing the users in it.
+//!
+//! This requires the `twitch.tv/membership` capability.
+
+use super::MessageParseError;
+use crate::common::{ChannelRef, MaybeOwned};
+use crate::irc::{Command, IrcMessageRef};
+use std::borrow::Cow;
+
+/// A list of users in a channel, sent after joining it.
+///
+/// Large channels may send more than one of these, followed by a single [`EndOfNames`].
+///
+/// ```text,ignore
+/// :justinfan12345.tmi.twitch.tv 353 justinfan12345 = #forsen :forsen pajlada randers
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Names<'src> {
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  channel: MaybeOwned<'src, ChannelRef>,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  users: Vec<Cow<'src, str>>,
+}
+
+generate_getters! {
+  <'src> for Names<'src> as self {
+    /// Name of the channel.
+    channel -> &ChannelRef = self.channel.as_ref(),
+
+    /// Iterator over the logins of the users in the channel.
+    users -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator
+      = self.users.iter().map(|v| v.as_ref()),
+  }
+}
+
+/// Marks the end of the [`Names`] list of a channel.
+///
+/// ```text,ignore
+/// :justinfan12345.tmi.twitch.tv 366 justinfan12345 #forsen :End of /NAMES list
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EndOfNames<'src> {
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  channel: MaybeOwned<'src, ChannelRef>,
+}
+
+generate_getters! {
+  <'src> for EndOfNames<'src> as self {
+    /// Name of the channel.
+    channel -> &ChannelRef = self.channel.as_ref(),
+  }
+}
+
+/// The channel is the first `#`-prefixed param, e.g. `<nick> = #channel :<names>`.
+fn names_channel<'src>(message: &IrcMessageRef<'src>) -> Option<&'src ChannelRef> {
+  message
+    .params()?
+    .split_whitespace()
+    .take_while(|param| !param.starts_with(':'))
+    .find_map(|param| ChannelRef::parse(param).ok())
+}
+
+impl<'src> Names<'src> {
+  fn parse(message: IrcMessageRef<'src>) -> Option<Self> {
+    if message.command() != Command::RplNames {
+      return None;
+    }
+
+    Some(Names {
+      channel: MaybeOwned::Ref(names_channel(&message)?),
+      users: message
+        .text()
+        .map(|text| text.split_whitespace().map(Cow::Borrowed).collect())
+        .unwrap_or_default(),
+    })
+  }
+}
+
+impl<'src> EndOfNames<'src> {
+  fn parse(message: IrcMessageRef<'src>) -> Option<Self> {
+    if message.command() != Command::RplEndOfNames {
+      return None;
+    }
+
+    Some(EndOfNames {
+      channel: MaybeOwned::Ref(names_channel(&message)?),
+    })
+  }
+}
+
+impl<'src> super::FromIrc<'src> for Names<'src> {
+  #[inline]
+  fn from_irc(message: IrcMessageRef<'src>) -> Result<Self, MessageParseError> {
+    Self::parse(message).ok_or(MessageParseError)
+  }
+}
+
+impl<'src> super::FromIrc<'src> for EndOfNames<'src> {
+  #[inline]
+  fn from_irc(message: IrcMessageRef<'src>) -> Result<Self, MessageParseError> {
+    Self::parse(message).ok_or(MessageParseError)
+  }
+}
+
+impl<'src> From<Names<'src>> for super::Message<'src> {
+  fn from(msg: Names<'src>) -> Self {
+    super::Message::Names(msg)
+  }
+}
+
+impl<'src> From<EndOfNames<'src>> for super::Message<'src> {
+  fn from(msg: EndOfNames<'src>) -> Self {
+    super::Message::EndOfNames(msg)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_names() {
+    assert_irc_snapshot!(
+      Names,
+      ":justinfan12345.tmi.twitch.tv 353 justinfan12345 = #forsen :forsen pajlada randers zneix"
+    );
+  }
+
+  #[test]
+  fn parse_end_of_names() {
+    assert_irc_snapshot!(
+      EndOfNames,
+      ":justinfan12345.tmi.twitch.tv 366 justinfan12345 #forsen :End of /NAMES list"
+    );
+  }
+
+  #[test]
+  fn names_users() {
+    let msg = crate::msg::macros::_parse_irc::<Names>(
+      ":justinfan12345.tmi.twitch.tv 353 justinfan12345 = #forsen :forsen pajlada",
+    );
+    assert_eq!(msg.channel().login(), "forsen");
+    assert_eq!(msg.users().collect::<Vec<_>>(), ["forsen", "pajlada"]);
+
+    let msg = crate::msg::Message::parse(
+      ":justinfan12345.tmi.twitch.tv 366 justinfan12345 #forsen :End of /NAMES list",
+    )
+    .unwrap();
+    assert!(matches!(msg, crate::msg::Message::EndOfNames(v) if v.channel().as_str() == "#forsen"));
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn roundtrip_names() {
+    assert_irc_roundtrip!(
+      Names,
+      ":justinfan12345.tmi.twitch.tv 353 justinfan12345 = #forsen :forsen pajlada randers zneix"
+    );
+  }
+}
@@ -1,10 +1,13 @@
 //! Represents a basic Twitch chat message sent by some user to a specific channel.
 
 use super::{
-  is_not_empty, parse_badges, parse_message_text, parse_timestamp, Badge, MessageParseError, User,
+  is_not_empty, parse_automod_flags, parse_badges, parse_cheers, parse_emotes, parse_message_text,
+  parse_timestamp, AutomodFlag, Badge, Cheer, Emote, KnownBadge, MessageParseError, OffsetUnit,
+  User,
 };
 use crate::common::{maybe_unescape, ChannelRef, MaybeOwned};
 use crate::irc::{Command, IrcMessageRef, Tag};
+#[cfg(feature = "chrono")]
 use chrono::{DateTime, Utc};
 use std::borrow::Cow;
 
@@ -21,6 +24,9 @@ pub struct Privmsg<'src> {
   #[cfg_attr(feature = "serde", serde(borrow))]
   message_id: Cow<'src, str>,
 
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  client_nonce: Option<Cow<'src, str>>,
+
   sender: User<'src>,
 
   #[cfg_attr(feature = "serde", serde(borrow))]
@@ -40,12 +46,30 @@ pub struct Privmsg<'src> {
   #[cfg_attr(feature = "serde", serde(borrow))]
   custom_reward_id: Option<Cow<'src, str>>,
 
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  msg_id: Option<Cow<'src, str>>,
+
   bits: Option<u64>,
 
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  hype_chat: Option<HypeChat<'src>>,
+
   #[cfg_attr(feature = "serde", serde(borrow))]
   emotes: Cow<'src, str>,
 
-  timestamp: DateTime<Utc>,
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  flags: Cow<'src, str>,
+
+  timestamp: super::Timestamp,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  source_channel_id: Option<Cow<'src, str>>,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  source_message_id: Option<Cow<'src, str>>,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  source_badges: Vec<Badge<'src>>,
 }
 
 generate_getters! {
@@ -59,6 +83,11 @@ generate_getters! {
     /// Unique ID of the message.
     message_id -> &str = self.message_id.as_ref(),
 
+    /// The `client-nonce` sent along with the message by the sender's client.
+    ///
+    /// This may be used to match a message you sent with its echo from the server.
+    client_nonce -> Option<&str> = self.client_nonce.as_deref(),
+
     /// Basic info about the user who sent this message.
     sender -> &User<'src> = &self.sender,
 
@@ -74,7 +103,7 @@ generate_getters! {
     is_action -> bool,
 
     /// Iterator over the channel badges enabled by the user in the [channel][`Privmsg::channel`].
-    badges -> impl Iterator<Item = &Badge<'src>> + DoubleEndedIterator + ExactSizeIterator
+    badges -> impl DoubleEndedIterator<Item = &Badge<'src>> + ExactSizeIterator
       = self.badges.iter(),
 
     /// Number of channel badges enabled by the user in the [channel][`Privmsg::channel`].
@@ -92,9 +121,19 @@ generate_getters! {
     /// Note: This is only provided for redeems with a message.
     custom_reward_id -> Option<&str> = self.custom_reward_id.as_deref(),
 
+    /// The `msg-id` tag, which marks messages sent using channel points,
+    /// e.g. `highlighted-message` or `skip-subs-mode-message`.
+    msg_id -> Option<&str> = self.msg_id.as_deref(),
+
+    /// Whether the message was highlighted using channel points.
+    is_highlighted -> bool = self.msg_id.as_deref() == Some("highlighted-message"),
+
     /// The number of bits gifted with this message.
     bits -> Option<u64>,
 
+    /// Info about the payment, if this message is a Hype Chat.
+    hype_chat -> Option<&HypeChat<'src>> = self.hype_chat.as_ref(),
+
     /// The emote raw emote ranges present in this message.
     ///
     /// ⚠ Note: This is _hopelessly broken_ and should **never be used for any purpose whatsoever**,
@@ -102,12 +141,167 @@ generate_getters! {
     /// If for some reason you need it, here you go.
     raw_emotes -> &str = self.emotes.as_ref(),
 
+    /// The emotes present in this message, resolved from the [raw emote ranges][`Privmsg::raw_emotes`].
+    ///
+    /// The unit of the offsets is detected automatically, see [`OffsetUnit::Auto`].
+    /// Use [`Privmsg::emotes_with`] to count them in a specific unit.
+    emotes -> Vec<Emote<'_>> = parse_emotes(&self.emotes, &self.text, OffsetUnit::Auto),
+
+    /// The raw value of the `flags` tag, which contains the parts of the message flagged by AutoMod.
+    raw_flags -> &str = self.flags.as_ref(),
+
+    /// Iterator over the parts of the message flagged by AutoMod.
+    ///
+    /// See [`parse_automod_flags`].
+    automod_flags -> impl Iterator<Item = AutomodFlag> + '_
+      = parse_automod_flags(&self.flags, &self.text),
+
     /// The time at which the message was sent.
+    #[cfg(feature = "chrono")]
     timestamp -> DateTime<Utc>,
+
+    /// The [timestamp][`Privmsg::timestamp`] in milliseconds since the Unix epoch.
+    ///
+    /// This is available without the `chrono` feature.
+    timestamp_millis -> u64 = super::timestamp_millis(&self.timestamp),
+
+    /// The [timestamp][`Privmsg::timestamp`] as a [`SystemTime`][std::time::SystemTime].
+    timestamp_system -> std::time::SystemTime = super::to_system_time(&self.timestamp),
+
+    /// ID of the channel in which this message was originally sent, during a Shared Chat session.
+    ///
+    /// [`None`] means the channel is not in a Shared Chat session.
+    source_channel_id -> Option<&str> = self.source_channel_id.as_deref(),
+
+    /// ID of the message in the channel in which it was originally sent, during a Shared Chat session.
+    source_message_id -> Option<&str> = self.source_message_id.as_deref(),
+
+    /// Iterator over the channel badges enabled by the user in the channel
+    /// in which this message was originally sent, during a Shared Chat session.
+    source_badges -> impl DoubleEndedIterator<Item = &Badge<'src>> + ExactSizeIterator
+      = self.source_badges.iter(),
+
+    /// Whether this message was sent in another channel of the Shared Chat session.
+    ///
+    /// This is `false` for messages sent in the [channel][`Privmsg::channel`] itself.
+    is_from_shared_channel -> bool = self
+      .source_channel_id
+      .as_ref()
+      .is_some_and(|id| *id != self.channel_id),
   }
 }
 
+impl<'src> Privmsg<'src> {
+  /// The emotes present in this message, with offsets counted in `unit`.
+  ///
+  /// See [`OffsetUnit`].
+  pub fn emotes_with(&self, unit: OffsetUnit) -> Vec<Emote<'_>> {
+    parse_emotes(&self.emotes, &self.text, unit)
+  }
+
+  /// Iterator over the cheers in this message, e.g. `Cheer100`.
+  ///
+  /// This is empty unless the message carries [bits][`Privmsg::bits`].
+  /// See [`parse_cheers`].
+  pub fn cheers(&self) -> impl Iterator<Item = Cheer<'_>> + '_ {
+    let text = match self.bits {
+      Some(_) => self.text.as_ref(),
+      None => "",
+    };
+    parse_cheers(text)
+  }
+
+  /// Returns `true` if the user has a badge of the given `kind` enabled
+  /// in the [channel][`Privmsg::channel`], regardless of its version.
+  ///
+  /// ```rust,ignore
+  /// // true for subscribers of any tier or length
+  /// msg.has_badge(KnownBadge::Subscriber)
+  /// ```
+  pub fn has_badge(&self, kind: KnownBadge) -> bool {
+    self.badges.iter().any(|badge| badge.is(kind))
+  }
+
+  /// Find the badge with the given `set_id`, e.g. `subscriber`.
+  pub fn badge(&self, set_id: &str) -> Option<&Badge<'src>> {
+    self.badges.iter().find(|badge| badge.set_id() == set_id)
+  }
+
+  /// Iterator over the `(set_id, version)` of each badge, e.g. `("subscriber", "12")`.
+  ///
+  /// This is what identifies the image of a badge.
+  pub fn badge_versions(
+    &self,
+  ) -> impl DoubleEndedIterator<Item = (&str, &str)> + ExactSizeIterator {
+    self
+      .badges
+      .iter()
+      .map(|badge| (badge.set_id(), badge.version()))
+  }
+}
+
+/// Information about the payment for a Hype Chat message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HypeChat<'src> {
+  amount: u64,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  currency: Cow<'src, str>,
+
+  exponent: u32,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  level: Cow<'src, str>,
+
+  is_system_message: bool,
+}
+
+generate_getters! {
+  <'src> for HypeChat<'src> as self {
+    /// The amount paid, in the smallest unit of the [currency][`HypeChat::currency`].
+    ///
+    /// See [`HypeChat::value`] for the amount in the currency's major unit.
+    amount -> u64,
+
+    /// The ISO 4217 code of the currency, e.g. `USD`.
+    currency -> &str = self.currency.as_ref(),
+
+    /// The number of decimal places of the [currency][`HypeChat::currency`],
+    /// e.g. `2` for `USD`.
+    exponent -> u32,
+
+    /// The level of the Hype Chat, e.g. `ONE` through `TEN`.
+    level -> &str = self.level.as_ref(),
+
+    /// Whether the message was sent by the system, instead of the user who paid.
+    is_system_message -> bool,
+
+    /// The amount paid, in the major unit of the [currency][`HypeChat::currency`].
+    ///
+    /// For example, an [amount][`HypeChat::amount`] of `500` with an
+    /// [exponent][`HypeChat::exponent`] of `2` is a value of `5.0`.
+    value -> f64 = self.amount as f64 / 10f64.powi(self.exponent as i32),
+  }
+}
+
+fn parse_hype_chat<'src>(message: &IrcMessageRef<'src>) -> Option<HypeChat<'src>> {
+  Some(HypeChat {
+    amount: message.tag_u64(Tag::PinnedChatPaidAmount)?,
+    currency: message.tag(Tag::PinnedChatPaidCurrency)?.into(),
+    exponent: message.tag(Tag::PinnedChatPaidExponent)?.parse().ok()?,
+    level: message
+      .tag(Tag::PinnedChatPaidLevel)
+      .unwrap_or_default()
+      .into(),
+    is_system_message: message.tag_bool(Tag::PinnedChatPaidIsSystemMessage) == Some(true),
+  })
+}
+
 /// Information about the reply parent message.
+///
+/// When replying to a message which is itself a reply, the parent is the message
+/// that was replied to directly, and the thread parent is the first message of the thread.
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Reply<'src> {
@@ -129,10 +323,16 @@ pub struct Reply<'src> {
 
 generate_getters! {
   <'src> for Reply<'src> as self {
-    /// Reply thread parent message ID
+    /// ID of the message which started the reply thread,
+    /// from the `reply-thread-parent-msg-id` tag.
+    ///
+    /// This is the same as [`Reply::message_id`] if the parent is not a reply itself.
     thread_message_id -> &str = self.thread_message_id.as_ref(),
 
-    /// Reply thread parent user login
+    /// Login of the sender of the message which started the reply thread,
+    /// from the `reply-thread-parent-user-login` tag.
+    ///
+    /// This is the same as the login of the [`Reply::sender`] if the parent is not a reply itself.
     thread_user_login -> &str = self.thread_user_login.as_ref(),
 
     /// Reply parent message ID
@@ -143,8 +343,21 @@ generate_getters! {
 
     /// Reply parent text
     ///
+    /// This strips the action prefix/suffix bytes if the parent message was sent with `/me`.
+    ///
     /// ⚠ This call will allocate and return a String if it needs to be unescaped.
-    text -> Cow<'src, str> = maybe_unescape(self.text.clone()),
+    text -> Cow<'src, str> = match maybe_unescape(self.text.clone()) {
+      Cow::Borrowed(text) => Cow::Borrowed(parse_message_text(text).0),
+      Cow::Owned(text) => Cow::Owned(parse_message_text(&text).0.to_owned()),
+    },
+
+    /// Reply parent text, with the original tag value escaping intact.
+    ///
+    /// Unlike [`Reply::text`], this does not strip the action prefix/suffix bytes.
+    text_escaped -> &str = self.text.as_ref(),
+
+    /// Whether the reply parent message was sent with `/me`.
+    is_action -> bool = parse_message_text(&maybe_unescape(self.text.as_ref())).1,
   }
 }
 
@@ -173,6 +386,10 @@ impl<'src> Privmsg<'src> {
       channel: MaybeOwned::Ref(message.channel()?),
       channel_id: message.tag(Tag::RoomId)?.into(),
       message_id: message.tag(Tag::Id)?.into(),
+      client_nonce: message
+        .tag(Tag::ClientNonce)
+        .filter(is_not_empty)
+        .map(Cow::Borrowed),
       sender: User {
         id: message.tag(Tag::UserId)?.into(),
         login: message
@@ -197,9 +414,22 @@ impl<'src> Privmsg<'src> {
         .tag(Tag::CustomRewardId)
         .filter(is_not_empty)
         .map(Cow::Borrowed),
-      bits: message.tag(Tag::Bits).and_then(|bits| bits.parse().ok()),
+      msg_id: message
+        .tag(Tag::MsgId)
+        .filter(is_not_empty)
+        .map(Cow::Borrowed),
+      bits: message.tag_u64(Tag::Bits),
+      hype_chat: parse_hype_chat(&message),
       emotes: message.tag(Tag::Emotes).unwrap_or_default().into(),
+      flags: message.tag(Tag::Flags).unwrap_or_default().into(),
       timestamp: message.tag(Tag::TmiSentTs).and_then(parse_timestamp)?,
+      source_channel_id: message.tag(Tag::SourceRoomId).map(Cow::Borrowed),
+      source_message_id: message.tag(Tag::SourceId).map(Cow::Borrowed),
+      source_badges: message
+        .tag(Tag::SourceBadges)
+        .zip(message.tag(Tag::SourceBadgeInfo))
+        .map(|(badges, badge_info)| parse_badges(badges, badge_info))
+        .unwrap_or_default(),
     })
   }
 }
@@ -221,36 +451,65 @@ impl<'src> From<Privmsg<'src>> for super::Message<'src> {
 mod tests {
   use super::*;
 
+  const PRIVMSG_BASIC_EXAMPLE: &str = "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam";
+  const PRIVMSG_ACTION_AND_BADGES: &str = "@badge-info=subscriber/22;badges=moderator/1,subscriber/12;color=#19E6E6;display-name=randers;emotes=;flags=;id=d831d848-b7c7-4559-ae3a-2cb88f4dbfed;mod=1;room-id=11148817;subscriber=1;tmi-sent-ts=1594555275886;turbo=0;user-id=40286300;user-type=mod :randers!randers@randers.tmi.twitch.tv PRIVMSG #pajlada :ACTION -tags";
+  const PRIVMSG_REPLY_PARENT_INCLUDED: &str = "@badge-info=;badges=;client-nonce=cd56193132f934ac71b4d5ac488d4bd6;color=;display-name=LeftSwing;emotes=;first-msg=0;flags=;id=5b4f63a9-776f-4fce-bf3c-d9707f52e32d;mod=0;reply-parent-display-name=Retoon;reply-parent-msg-body=hello;reply-parent-msg-id=6b13e51b-7ecb-43b5-ba5b-2bb5288df696;reply-parent-user-id=37940952;reply-parent-user-login=retoon;reply-thread-parent-msg-id=6b13e51b-7ecb-43b5-ba5b-2bb5288df696;reply-thread-parent-user-login=retoon;returning-chatter=0;room-id=37940952;subscriber=0;tmi-sent-ts=1673925983585;turbo=0;user-id=133651738;user-type= :leftswing!leftswing@leftswing.tmi.twitch.tv PRIVMSG #retoon :@Retoon yes";
+  const PRIVMSG_MESSAGE_WITH_BITS: &str = "@badge-info=;badges=bits/100;bits=1;color=#004B49;display-name=TETYYS;emotes=;flags=;id=d7f03a35-f339-41ca-b4d4-7c0721438570;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594571566672;turbo=0;user-id=36175310;user-type= :tetyys!tetyys@tetyys.tmi.twitch.tv PRIVMSG #pajlada :trihard1";
+  const PRIVMSG_CUSTOM_REWARD_ID: &str = "@badge-info=subscriber/1;badges=broadcaster/1,subscriber/0;color=#8A2BE2;custom-reward-id=be22f712-8fd9-426a-90df-c13eae6cc6dc;display-name=vesdeg;emotes=;first-msg=0;flags=;id=79828352-d979-4e49-bd5e-15c487d275e2;mod=0;returning-chatter=0;room-id=164774298;subscriber=1;tmi-sent-ts=1709298826724;turbo=0;user-id=164774298;user-type= :vesdeg!vesdeg@vesdeg.tmi.twitch.tv PRIVMSG #vesdeg :#00FF00";
+  const PRIVMSG_HIGHLIGHTED_MESSAGE: &str = "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;msg-id=highlighted-message;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam";
+  const PRIVMSG_HYPE_CHAT: &str = "@badge-info=;badges=glhf-pledge/1;color=#FF69B4;display-name=hypeuser;emotes=;first-msg=0;flags=;id=88d01fd6-8bd1-4b67-a5f6-7bb9e7a1e1a3;mod=0;pinned-chat-paid-amount=500;pinned-chat-paid-canonical-amount=500;pinned-chat-paid-currency=USD;pinned-chat-paid-exponent=2;pinned-chat-paid-is-system-message=0;pinned-chat-paid-level=ONE;returning-chatter=0;room-id=11148817;subscriber=0;tmi-sent-ts=1687471125574;turbo=0;user-id=29803735;user-type= :hypeuser!hypeuser@hypeuser.tmi.twitch.tv PRIVMSG #pajlada :hype!";
+  const PRIVMSG_SHARED_CHAT: &str = "@badge-info=;badges=staff/1,raging-wolf-helm/1;color=#DAA520;display-name=lahoooo;emotes=;first-msg=0;flags=;id=01cd601f-bc3f-49d5-ab4b-136fa9d6ec22;mod=0;returning-chatter=0;room-id=12345;source-badge-info=subscriber/4;source-badges=moderator/1,subscriber/3;source-id=4c8a4d8c-8b9d-4f3a-9d2b-3f3a2c1b0e9a;source-room-id=54321;subscriber=0;tmi-sent-ts=1726158218467;turbo=0;user-id=87654321;user-type=staff :lahoooo!lahoooo@lahoooo.tmi.twitch.tv PRIVMSG #ronni :hi";
+  const PRIVMSG_REPLY_IN_THREAD: &str = "@badge-info=;badges=;color=;display-name=LeftSwing;emotes=;first-msg=0;flags=;id=9a0ad5b2-e6a1-4a2c-9f6b-5c1a4c9a7f11;mod=0;reply-parent-display-name=Pajlada;reply-parent-msg-body=@Retoon\\sno;reply-parent-msg-id=2d5f8a3e-19c4-4f3a-a7a2-0e5b3a1f6c2d;reply-parent-user-id=11148817;reply-parent-user-login=pajlada;reply-thread-parent-msg-id=6b13e51b-7ecb-43b5-ba5b-2bb5288df696;reply-thread-parent-user-login=retoon;returning-chatter=0;room-id=37940952;subscriber=0;tmi-sent-ts=1673925993585;turbo=0;user-id=133651738;user-type= :leftswing!leftswing@leftswing.tmi.twitch.tv PRIVMSG #retoon :@Pajlada yes";
+
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_basic_example() {
-    assert_irc_snapshot!(Privmsg, "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam");
+    assert_irc_snapshot!(Privmsg, PRIVMSG_BASIC_EXAMPLE);
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_action_and_badges() {
-    assert_irc_snapshot!(Privmsg, "@badge-info=subscriber/22;badges=moderator/1,subscriber/12;color=#19E6E6;display-name=randers;emotes=;flags=;id=d831d848-b7c7-4559-ae3a-2cb88f4dbfed;mod=1;room-id=11148817;subscriber=1;tmi-sent-ts=1594555275886;turbo=0;user-id=40286300;user-type=mod :randers!randers@randers.tmi.twitch.tv PRIVMSG #pajlada :ACTION -tags");
+    assert_irc_snapshot!(Privmsg, PRIVMSG_ACTION_AND_BADGES);
   }
 
+  #[test]
+  fn privmsg_badge_versions() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_ACTION_AND_BADGES);
+    assert_eq!(
+      msg.badge_versions().collect::<Vec<_>>(),
+      [("moderator", "1"), ("subscriber", "12")]
+    );
+    assert_eq!(msg.badge("subscriber").unwrap().version(), "12");
+    assert_eq!(msg.badge("moderator"), Some(&Badge::Moderator));
+    assert_eq!(msg.badge("vip"), None);
+  }
+
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_reply_parent_included() {
-    assert_irc_snapshot!(Privmsg, "@badge-info=;badges=;client-nonce=cd56193132f934ac71b4d5ac488d4bd6;color=;display-name=LeftSwing;emotes=;first-msg=0;flags=;id=5b4f63a9-776f-4fce-bf3c-d9707f52e32d;mod=0;reply-parent-display-name=Retoon;reply-parent-msg-body=hello;reply-parent-msg-id=6b13e51b-7ecb-43b5-ba5b-2bb5288df696;reply-parent-user-id=37940952;reply-parent-user-login=retoon;reply-thread-parent-msg-id=6b13e51b-7ecb-43b5-ba5b-2bb5288df696;reply-thread-parent-user-login=retoon;returning-chatter=0;room-id=37940952;subscriber=0;tmi-sent-ts=1673925983585;turbo=0;user-id=133651738;user-type= :leftswing!leftswing@leftswing.tmi.twitch.tv PRIVMSG #retoon :@Retoon yes");
+    assert_irc_snapshot!(Privmsg, PRIVMSG_REPLY_PARENT_INCLUDED);
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_display_name_with_trailing_space() {
     assert_irc_snapshot!(Privmsg, "@rm-received-ts=1594554085918;historical=1;badge-info=;badges=;client-nonce=815810609edecdf4537bd9586994182b;color=;display-name=CarvedTaleare\\s;emotes=;flags=;id=c9b941d9-a0ab-4534-9903-971768fcdf10;mod=0;room-id=22484632;subscriber=0;tmi-sent-ts=1594554085753;turbo=0;user-id=467684514;user-type= :carvedtaleare!carvedtaleare@carvedtaleare.tmi.twitch.tv PRIVMSG #forsen :NaM");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_korean_display_name() {
     assert_irc_snapshot!(Privmsg, "@badge-info=subscriber/35;badges=moderator/1,subscriber/3024;color=#FF0000;display-name=테스트계정420;emotes=;flags=;id=bdfa278e-11c4-484f-9491-0a61b16fab60;mod=1;room-id=11148817;subscriber=1;tmi-sent-ts=1593953876927;turbo=0;user-id=117166826;user-type=mod :testaccount_420!testaccount_420@testaccount_420.tmi.twitch.tv PRIVMSG #pajlada :@asd");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_display_name_with_middle_space() {
     assert_irc_snapshot!(Privmsg, "@badge-info=;badges=;color=;display-name=Riot\\sGames;emotes=;flags=;id=bdfa278e-11c4-484f-9491-0a61b16fab60;mod=1;room-id=36029255;subscriber=0;tmi-sent-ts=1593953876927;turbo=0;user-id=36029255;user-type= :riotgames!riotgames@riotgames.tmi.twitch.tv PRIVMSG #riotgames :test fake message");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_emotes_1() {
     assert_irc_snapshot!(
@@ -259,37 +518,263 @@ mod tests {
     );
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_message_with_bits() {
-    assert_irc_snapshot!(Privmsg, "@badge-info=;badges=bits/100;bits=1;color=#004B49;display-name=TETYYS;emotes=;flags=;id=d7f03a35-f339-41ca-b4d4-7c0721438570;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594571566672;turbo=0;user-id=36175310;user-type= :tetyys!tetyys@tetyys.tmi.twitch.tv PRIVMSG #pajlada :trihard1");
+    assert_irc_snapshot!(Privmsg, PRIVMSG_MESSAGE_WITH_BITS);
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_emote_non_numeric_id() {
     assert_irc_snapshot!(Privmsg, "@badge-info=;badges=;client-nonce=245b864d508a69a685e25104204bd31b;color=#FF144A;display-name=AvianArtworks;emote-only=1;emotes=300196486_TK:0-7;flags=;id=21194e0d-f0fa-4a8f-a14f-3cbe89366ad9;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594552113129;turbo=0;user-id=39565465;user-type= :avianartworks!avianartworks@avianartworks.tmi.twitch.tv PRIVMSG #pajlada :pajaM_TK");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_privmsg_custom_reward_id() {
-    assert_irc_snapshot!(Privmsg, "@badge-info=subscriber/1;badges=broadcaster/1,subscriber/0;color=#8A2BE2;custom-reward-id=be22f712-8fd9-426a-90df-c13eae6cc6dc;display-name=vesdeg;emotes=;first-msg=0;flags=;id=79828352-d979-4e49-bd5e-15c487d275e2;mod=0;returning-chatter=0;room-id=164774298;subscriber=1;tmi-sent-ts=1709298826724;turbo=0;user-id=164774298;user-type= :vesdeg!vesdeg@vesdeg.tmi.twitch.tv PRIVMSG #vesdeg :#00FF00");
+    assert_irc_snapshot!(Privmsg, PRIVMSG_CUSTOM_REWARD_ID);
+  }
+
+  #[test]
+  fn privmsg_custom_reward_id() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_CUSTOM_REWARD_ID);
+    assert_eq!(
+      msg.custom_reward_id(),
+      Some("be22f712-8fd9-426a-90df-c13eae6cc6dc")
+    );
+
+    let src = PRIVMSG_BASIC_EXAMPLE.replace(";display-name=", ";custom-reward-id=;display-name=");
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(&src);
+    assert_eq!(msg.custom_reward_id(), None);
+  }
+
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn parse_privmsg_highlighted_message() {
+    assert_irc_snapshot!(Privmsg, PRIVMSG_HIGHLIGHTED_MESSAGE);
+  }
+
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn parse_privmsg_hype_chat() {
+    assert_irc_snapshot!(Privmsg, PRIVMSG_HYPE_CHAT);
+  }
+
+  #[test]
+  fn privmsg_hype_chat() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_HYPE_CHAT);
+    let hype_chat = msg.hype_chat().unwrap();
+    assert_eq!(hype_chat.amount(), 500);
+    assert_eq!(hype_chat.currency(), "USD");
+    assert_eq!(hype_chat.exponent(), 2);
+    assert_eq!(hype_chat.value(), 5.0);
+    assert_eq!(hype_chat.level(), "ONE");
+    assert!(!hype_chat.is_system_message());
+
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>("@badge-info=;badges=;color=;display-name=hypeuser;emotes=;flags=;id=88d01fd6-8bd1-4b67-a5f6-7bb9e7a1e1a3;mod=0;pinned-chat-paid-amount=10000;pinned-chat-paid-currency=JPY;pinned-chat-paid-exponent=0;pinned-chat-paid-is-system-message=1;pinned-chat-paid-level=TWO;room-id=11148817;subscriber=0;tmi-sent-ts=1687471125574;turbo=0;user-id=29803735;user-type= :hypeuser!hypeuser@hypeuser.tmi.twitch.tv PRIVMSG #pajlada :x");
+    let hype_chat = msg.hype_chat().unwrap();
+    assert_eq!(hype_chat.value(), 10000.0);
+    assert!(hype_chat.is_system_message());
+  }
+
+  #[test]
+  fn privmsg_msg_id() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_HIGHLIGHTED_MESSAGE);
+    assert_eq!(msg.msg_id(), Some("highlighted-message"));
+    assert!(msg.is_highlighted());
+
+    let src = PRIVMSG_HIGHLIGHTED_MESSAGE.replace("highlighted-message", "skip-subs-mode-message");
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(&src);
+    assert_eq!(msg.msg_id(), Some("skip-subs-mode-message"));
+    assert!(!msg.is_highlighted());
+
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_BASIC_EXAMPLE);
+    assert_eq!(msg.msg_id(), None);
+    assert!(!msg.is_highlighted());
+  }
+
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn parse_privmsg_shared_chat() {
+    assert_irc_snapshot!(Privmsg, PRIVMSG_SHARED_CHAT);
+  }
+
+  #[test]
+  fn privmsg_shared_chat_source() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_SHARED_CHAT);
+    assert!(msg.is_from_shared_channel());
+    assert_eq!(msg.source_channel_id(), Some("54321"));
+    assert_eq!(
+      msg.source_message_id(),
+      Some("4c8a4d8c-8b9d-4f3a-9d2b-3f3a2c1b0e9a")
+    );
+    assert_eq!(msg.source_badges().len(), 2);
+    assert_eq!(msg.source_badges().next(), Some(&Badge::Moderator));
+
+    // sent in the channel itself, during a shared chat session
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>("@badge-info=;badges=;color=;display-name=lahoooo;emotes=;flags=;id=01cd601f-bc3f-49d5-ab4b-136fa9d6ec22;mod=0;room-id=12345;source-badge-info=;source-badges=;source-id=01cd601f-bc3f-49d5-ab4b-136fa9d6ec22;source-room-id=12345;subscriber=0;tmi-sent-ts=1726158218467;turbo=0;user-id=87654321;user-type= :lahoooo!lahoooo@lahoooo.tmi.twitch.tv PRIVMSG #ronni :hi");
+    assert!(!msg.is_from_shared_channel());
+    assert_eq!(msg.source_channel_id(), Some("12345"));
+
+    // not in a shared chat session
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_BASIC_EXAMPLE);
+    assert!(!msg.is_from_shared_channel());
+    assert_eq!(msg.source_channel_id(), None);
+    assert_eq!(msg.source_message_id(), None);
+    assert_eq!(msg.source_badges().len(), 0);
+  }
+
+  #[test]
+  fn privmsg_automod_flags() {
+    let src = PRIVMSG_BASIC_EXAMPLE
+      .replace("flags=;", "flags=0-4:P.6,11-14:A.3/P.5;")
+      .replace(":dank cam", ":shite dank crap cam");
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(&src);
+    assert_eq!(msg.raw_flags(), "0-4:P.6,11-14:A.3/P.5");
+    let flags: Vec<_> = msg
+      .automod_flags()
+      .map(|flag| (&msg.text()[flag.range()], flag.category(), flag.score()))
+      .collect();
+    assert_eq!(
+      flags,
+      [("shite", 'P', 6), ("crap", 'A', 3), ("crap", 'P', 5)]
+    );
+  }
+
+  #[test]
+  fn privmsg_trailing_carriage_return() {
+    let src = format!("{PRIVMSG_BASIC_EXAMPLE}\r");
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(&src);
+    assert_eq!(msg.text(), "dank cam");
+
+    let src = PRIVMSG_BASIC_EXAMPLE.replace(":dank cam", ":\u{0001}ACTION dank cam\u{0001}\r");
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(&src);
+    assert_eq!(msg.text(), "dank cam");
+    assert!(msg.is_action());
+  }
+
+  #[test]
+  fn privmsg_timestamp() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_BASIC_EXAMPLE);
+    assert_eq!(msg.timestamp_millis(), 1594545155039);
+    assert_eq!(
+      msg.timestamp_system(),
+      std::time::UNIX_EPOCH + std::time::Duration::from_millis(1594545155039)
+    );
+    #[cfg(feature = "chrono")]
+    assert_eq!(
+      std::time::SystemTime::from(msg.timestamp()),
+      msg.timestamp_system()
+    );
+  }
+
+  #[test]
+  fn privmsg_cheers() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_MESSAGE_WITH_BITS);
+    let cheers: Vec<_> = msg.cheers().collect();
+    assert_eq!(cheers.len(), 1);
+    assert_eq!(cheers[0].prefix(), "trihard");
+    assert_eq!(cheers[0].bits(), 1);
+    assert_eq!(cheers[0].range(), 0..8);
+
+    // without bits, there are no cheers
+    let src = PRIVMSG_MESSAGE_WITH_BITS.replace("badges=bits/100;bits=1;", "badges=;");
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(&src);
+    assert_eq!(msg.cheers().count(), 0);
+  }
+
+  #[test]
+  fn privmsg_emotes() {
+    let src = PRIVMSG_BASIC_EXAMPLE
+      .replace("emotes=;", "emotes=25:3-7;")
+      .replace(":dank cam", ":👋 Kappa");
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(&src);
+    let emotes = msg.emotes();
+    assert_eq!(emotes.len(), 1);
+    assert_eq!(emotes[0].id(), "25");
+    assert_eq!(emotes[0].code(), "Kappa");
+    assert!(msg.emotes_with(OffsetUnit::Codepoint).is_empty());
+  }
+
+  #[test]
+  fn privmsg_has_badge() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>("@badge-info=subscriber/22;badges=subscriber/3012,bits/100;color=#19E6E6;display-name=randers;emotes=;flags=;id=d831d848-b7c7-4559-ae3a-2cb88f4dbfed;mod=0;room-id=11148817;subscriber=1;tmi-sent-ts=1594555275886;turbo=0;user-id=40286300;user-type= :randers!randers@randers.tmi.twitch.tv PRIVMSG #pajlada :test");
+    assert!(msg.has_badge(KnownBadge::Subscriber));
+    assert!(msg.has_badge(KnownBadge::Bits));
+    assert!(!msg.has_badge(KnownBadge::Moderator));
+
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>("@badge-info=subscriber/1;badges=subscriber/0;color=;display-name=test;emotes=;flags=;id=d831d848-b7c7-4559-ae3a-2cb88f4dbfed;mod=0;room-id=11148817;subscriber=1;tmi-sent-ts=1594555275886;turbo=0;user-id=40286300;user-type= :test!test@test.tmi.twitch.tv PRIVMSG #pajlada :test");
+    assert!(msg.has_badge(KnownBadge::Subscriber));
+    assert!(!msg.has_badge(KnownBadge::Moderator));
+  }
+
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn parse_privmsg_reply_in_thread() {
+    assert_irc_snapshot!(Privmsg, PRIVMSG_REPLY_IN_THREAD);
+  }
+
+  #[test]
+  fn privmsg_reply_thread_parent() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_REPLY_IN_THREAD);
+    let reply = msg.reply_to().unwrap();
+    assert_eq!(reply.message_id(), "2d5f8a3e-19c4-4f3a-a7a2-0e5b3a1f6c2d");
+    assert_eq!(reply.sender().login(), "pajlada");
+    assert_eq!(
+      reply.thread_message_id(),
+      "6b13e51b-7ecb-43b5-ba5b-2bb5288df696"
+    );
+    assert_eq!(reply.thread_user_login(), "retoon");
+    assert_eq!(msg.client_nonce(), None);
+  }
+
+  #[test]
+  fn parse_privmsg_reply_parent_body_unescaped() {
+    let src =
+      PRIVMSG_REPLY_PARENT_INCLUDED.replace("msg-body=hello;", "msg-body=hello\\sthere,\\sfriend;");
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(&src);
+    assert_eq!(msg.client_nonce(), Some("cd56193132f934ac71b4d5ac488d4bd6"));
+    let reply = msg.reply_to().unwrap();
+    assert_eq!(reply.text(), "hello there, friend");
+    assert_eq!(reply.text_escaped(), "hello\\sthere,\\sfriend");
+    assert!(!reply.is_action());
+  }
+
+  #[test]
+  fn parse_privmsg_reply_parent_body_escaped() {
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(PRIVMSG_REPLY_PARENT_INCLUDED);
+    assert_eq!(msg.reply_to().unwrap().text_escaped(), "hello");
+  }
+
+  #[test]
+  fn parse_privmsg_reply_parent_action() {
+    let src = PRIVMSG_REPLY_PARENT_INCLUDED.replace(
+      "msg-body=hello;",
+      "msg-body=\u{0001}ACTION\\swaves\\sat\\schat\u{0001};",
+    );
+    let msg = crate::msg::macros::_parse_irc::<Privmsg>(&src);
+    let reply = msg.reply_to().unwrap();
+    assert_eq!(reply.text(), "waves at chat");
+    assert!(reply.is_action());
   }
 
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_privmsg_basic_example() {
-    assert_irc_roundtrip!(Privmsg, "@badge-info=;badges=;color=#0000FF;display-name=JuN1oRRRR;emotes=;flags=;id=e9d998c3-36f1-430f-89ec-6b887c28af36;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594545155039;turbo=0;user-id=29803735;user-type= :jun1orrrr!jun1orrrr@jun1orrrr.tmi.twitch.tv PRIVMSG #pajlada :dank cam");
+    assert_irc_roundtrip!(Privmsg, PRIVMSG_BASIC_EXAMPLE);
   }
 
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_privmsg_action_and_badges() {
-    assert_irc_roundtrip!(Privmsg, "@badge-info=subscriber/22;badges=moderator/1,subscriber/12;color=#19E6E6;display-name=randers;emotes=;flags=;id=d831d848-b7c7-4559-ae3a-2cb88f4dbfed;mod=1;room-id=11148817;subscriber=1;tmi-sent-ts=1594555275886;turbo=0;user-id=40286300;user-type=mod :randers!randers@randers.tmi.twitch.tv PRIVMSG #pajlada :ACTION -tags");
+    assert_irc_roundtrip!(Privmsg, PRIVMSG_ACTION_AND_BADGES);
   }
 
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_privmsg_reply_parent_included() {
-    assert_irc_roundtrip!(Privmsg, "@badge-info=;badges=;client-nonce=cd56193132f934ac71b4d5ac488d4bd6;color=;display-name=LeftSwing;emotes=;first-msg=0;flags=;id=5b4f63a9-776f-4fce-bf3c-d9707f52e32d;mod=0;reply-parent-display-name=Retoon;reply-parent-msg-body=hello;reply-parent-msg-id=6b13e51b-7ecb-43b5-ba5b-2bb5288df696;reply-parent-user-id=37940952;reply-parent-user-login=retoon;reply-thread-parent-msg-id=6b13e51b-7ecb-43b5-ba5b-2bb5288df696;reply-thread-parent-user-login=retoon;returning-chatter=0;room-id=37940952;subscriber=0;tmi-sent-ts=1673925983585;turbo=0;user-id=133651738;user-type= :leftswing!leftswing@leftswing.tmi.twitch.tv PRIVMSG #retoon :@Retoon yes");
+    assert_irc_roundtrip!(Privmsg, PRIVMSG_REPLY_PARENT_INCLUDED);
   }
 
   #[cfg(feature = "serde")]
@@ -322,7 +807,7 @@ mod tests {
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_privmsg_message_with_bits() {
-    assert_irc_roundtrip!(Privmsg, "@badge-info=;badges=bits/100;bits=1;color=#004B49;display-name=TETYYS;emotes=;flags=;id=d7f03a35-f339-41ca-b4d4-7c0721438570;mod=0;room-id=11148817;subscriber=0;tmi-sent-ts=1594571566672;turbo=0;user-id=36175310;user-type= :tetyys!tetyys@tetyys.tmi.twitch.tv PRIVMSG #pajlada :trihard1");
+    assert_irc_roundtrip!(Privmsg, PRIVMSG_MESSAGE_WITH_BITS);
   }
 
   #[cfg(feature = "serde")]
@@ -1,6 +1,6 @@
 //! A partial update to the settings of some channel.
 
-use super::{parse_bool, MessageParseError};
+use super::MessageParseError;
 use crate::common::{ChannelRef, MaybeOwned};
 use crate::irc::{Command, IrcMessageRef, Tag};
 use std::borrow::Cow;
@@ -65,6 +65,17 @@ generate_getters! {
     ///
     /// Users may only send messages if they have an active subscription.
     subs_only -> Option<bool>,
+
+    /// Whether this is the full state of the room, rather than an update.
+    ///
+    /// Twitch sends the full state when the channel is joined, and only
+    /// the changed settings afterwards. This is a heuristic which returns
+    /// `true` if all of the settings are present.
+    is_initial -> bool = self.emote_only.is_some()
+      && self.followers_only.is_some()
+      && self.r9k.is_some()
+      && self.slow.is_some()
+      && self.subs_only.is_some(),
   }
 }
 
@@ -99,7 +110,7 @@ impl<'src> RoomState<'src> {
     Some(RoomState {
       channel: MaybeOwned::Ref(message.channel()?),
       channel_id: message.tag(Tag::RoomId)?.into(),
-      emote_only: message.tag(Tag::EmoteOnly).map(parse_bool),
+      emote_only: message.tag_bool(Tag::EmoteOnly),
       followers_only: message
         .tag(Tag::FollowersOnly)
         .and_then(|v| v.parse().ok())
@@ -108,12 +119,9 @@ impl<'src> RoomState<'src> {
           0 => FollowersOnly::Enabled(None),
           _ => FollowersOnly::Disabled,
         }),
-      r9k: message.tag(Tag::R9K).map(parse_bool),
-      slow: message
-        .tag(Tag::Slow)
-        .and_then(|v| v.parse().ok())
-        .map(Duration::from_secs),
-      subs_only: message.tag(Tag::SubsOnly).map(parse_bool),
+      r9k: message.tag_bool(Tag::R9K),
+      slow: message.tag_u64(Tag::Slow).map(Duration::from_secs),
+      subs_only: message.tag_bool(Tag::SubsOnly),
     })
   }
 }
@@ -135,9 +143,22 @@ impl<'src> From<RoomState<'src>> for super::Message<'src> {
 mod tests {
   use super::*;
 
+  const ROOM_STATE_BASIC_FULL: &str = "@emote-only=0;followers-only=-1;r9k=0;rituals=0;room-id=40286300;slow=0;subs-only=0 :tmi.twitch.tv ROOMSTATE #randers";
+
+  #[test]
+  fn room_state_is_initial() {
+    let msg = crate::msg::macros::_parse_irc::<RoomState>(ROOM_STATE_BASIC_FULL);
+    assert!(msg.is_initial());
+
+    let msg = crate::msg::macros::_parse_irc::<RoomState>(
+      "@room-id=40286300;slow=10 :tmi.twitch.tv ROOMSTATE #randers",
+    );
+    assert!(!msg.is_initial());
+  }
+
   #[test]
   fn parse_room_state_basic_full() {
-    assert_irc_snapshot!(RoomState, "@emote-only=0;followers-only=-1;r9k=0;rituals=0;room-id=40286300;slow=0;subs-only=0 :tmi.twitch.tv ROOMSTATE #randers");
+    assert_irc_snapshot!(RoomState, ROOM_STATE_BASIC_FULL);
   }
 
   #[test]
@@ -169,7 +190,7 @@ mod tests {
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_room_state_basic_full() {
-    assert_irc_roundtrip!(RoomState, "@emote-only=0;followers-only=-1;r9k=0;rituals=0;room-id=40286300;slow=0;subs-only=0 :tmi.twitch.tv ROOMSTATE #randers");
+    assert_irc_roundtrip!(RoomState, ROOM_STATE_BASIC_FULL);
   }
 
   #[cfg(feature = "serde")]
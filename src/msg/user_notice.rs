@@ -3,6 +3,7 @@
 use super::{is_not_empty, parse_badges, parse_timestamp, Badge, MessageParseError, User};
 use crate::common::{maybe_unescape, ChannelRef, MaybeOwned};
 use crate::{Command, IrcMessageRef, Tag};
+#[cfg(feature = "chrono")]
 use chrono::{DateTime, Utc};
 use std::borrow::Cow;
 
@@ -45,7 +46,7 @@ pub struct UserNotice<'src> {
   #[cfg_attr(feature = "serde", serde(borrow))]
   message_id: Cow<'src, str>,
 
-  timestamp: DateTime<Utc>,
+  timestamp: super::Timestamp,
 }
 
 generate_getters! {
@@ -73,6 +74,9 @@ generate_getters! {
     /// ⚠ This call will allocate and return a String if it needs to be unescaped.
     system_message -> Option<Cow<'src, str>> = self.system_message.clone().map(maybe_unescape),
 
+    /// Message sent with this user notice, with the original tag value escaping intact.
+    system_message_escaped -> Option<&str> = self.system_message.as_deref(),
+
     /// Event-specific information.
     event -> &Event<'src> = &self.event,
 
@@ -82,7 +86,7 @@ generate_getters! {
     event_id -> &str = self.event_id.as_ref(),
 
     /// Iterator over the channel badges enabled by the user in the [channel][`UserNotice::channel`].
-    badges -> impl Iterator<Item = &Badge<'src>> + DoubleEndedIterator + ExactSizeIterator
+    badges -> impl DoubleEndedIterator<Item = &Badge<'src>> + ExactSizeIterator
       = self.badges.iter(),
 
     /// Number of channel badges enabled by the user in the [channel][`UserNotice::channel`].
@@ -106,7 +110,16 @@ generate_getters! {
     message_id -> &str = self.message_id.as_ref(),
 
     /// The time at which the message was sent.
+    #[cfg(feature = "chrono")]
     timestamp -> DateTime<Utc>,
+
+    /// The [timestamp][`UserNotice::timestamp`] in milliseconds since the Unix epoch.
+    ///
+    /// This is available without the `chrono` feature.
+    timestamp_millis -> u64 = super::timestamp_millis(&self.timestamp),
+
+    /// The [timestamp][`UserNotice::timestamp`] as a [`SystemTime`][std::time::SystemTime].
+    timestamp_system -> std::time::SystemTime = super::to_system_time(&self.timestamp),
   }
 }
 
@@ -208,6 +221,10 @@ generate_getters! {
     ///
     /// ⚠ This call will allocate and return a String if it needs to be unescaped.
     sub_plan_name -> Cow<'src, str> = maybe_unescape(self.sub_plan_name.clone()),
+
+    /// Channel-specific name for this subscription tier/plan,
+    /// with the original tag value escaping intact.
+    sub_plan_name_escaped -> &str = self.sub_plan_name.as_ref(),
   }
 }
 
@@ -244,6 +261,7 @@ pub struct SubGift<'src> {
   sub_plan: Cow<'src, str>,
   sub_plan_name: Cow<'src, str>,
   num_gifted_months: u64,
+  sender_total_gifts: Option<u64>,
 }
 
 generate_getters! {
@@ -267,8 +285,18 @@ generate_getters! {
     /// ⚠ This call will allocate and return a String if it needs to be unescaped.
     sub_plan_name -> Cow<'src, str> = maybe_unescape(self.sub_plan_name.clone()),
 
+    /// Channel-specific name for this subscription tier/plan,
+    /// with the original tag value escaping intact.
+    sub_plan_name_escaped -> &str = self.sub_plan_name.as_ref(),
+
     /// Number of months in a single multi-month gift.
     num_gifted_months -> u64,
+
+    /// Total number of gifts the sender has gifted in this channel.
+    ///
+    /// This is `0` if the sender chose not to share the total,
+    /// and usually [`None`] if the gift was anonymous.
+    sender_total_gifts -> Option<u64>,
   }
 }
 
@@ -419,6 +447,57 @@ generate_getters! {
     ///
     /// Where `PRIMARY` refers to the channel's profile accent color.
     highlight_color -> &str = self.highlight_color.as_ref(),
+
+    /// The color used to highlight the announcement, see [`AnnouncementColor`].
+    color -> AnnouncementColor<'_> = AnnouncementColor::parse(&self.highlight_color),
+  }
+}
+
+/// The color used to highlight an [`Announcement`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AnnouncementColor<'a> {
+  /// `PRIMARY`, the channel's profile accent color.
+  Primary,
+
+  /// `BLUE`
+  Blue,
+
+  /// `GREEN`
+  Green,
+
+  /// `ORANGE`
+  Orange,
+
+  /// `PURPLE`
+  Purple,
+
+  /// Some other color, which is not known to this library.
+  Other(&'a str),
+}
+
+impl<'a> AnnouncementColor<'a> {
+  /// Parse the value of the `msg-param-color` tag.
+  pub fn parse(value: &'a str) -> Self {
+    match value {
+      "PRIMARY" => Self::Primary,
+      "BLUE" => Self::Blue,
+      "GREEN" => Self::Green,
+      "ORANGE" => Self::Orange,
+      "PURPLE" => Self::Purple,
+      other => Self::Other(other),
+    }
+  }
+
+  /// Get the color as it appears in the `msg-param-color` tag.
+  pub fn as_str(&self) -> &'a str {
+    match self {
+      Self::Primary => "PRIMARY",
+      Self::Blue => "BLUE",
+      Self::Green => "GREEN",
+      Self::Orange => "ORANGE",
+      Self::Purple => "PURPLE",
+      Self::Other(other) => other,
+    }
   }
 }
 
@@ -442,9 +521,7 @@ generate_getters! {
 
 fn parse_promotion<'src>(message: &IrcMessageRef<'src>) -> Option<SubGiftPromo<'src>> {
   match (
-    message
-      .tag(Tag::MsgParamPromoGiftTotal)
-      .and_then(|v| v.parse().ok()),
+    message.tag_u64(Tag::MsgParamPromoGiftTotal),
     message.tag(Tag::MsgParamPromoName),
   ) {
     (Some(total_gifts), Some(promo_name)) => Some(SubGiftPromo {
@@ -471,13 +548,14 @@ impl<'src> UserNotice<'src> {
       "sub" | "resub" => (
         Event::SubOrResub(SubOrResub {
           is_resub: event_id == "resub",
-          cumulative_months: message
-            .tag(Tag::MsgParamCumulativeMonths)
-            .and_then(|v| v.parse().ok())?,
-          streak_months: message
-            .tag(Tag::MsgParamStreakMonths)
-            .and_then(|v| v.parse().ok())
-            .and_then(|n| if n > 0 { Some(n) } else { None }),
+          cumulative_months: message.tag_u64(Tag::MsgParamCumulativeMonths)?,
+          streak_months: message.tag_u64(Tag::MsgParamStreakMonths).and_then(|n| {
+            if n > 0 {
+              Some(n)
+            } else {
+              None
+            }
+          }),
           sub_plan: message.tag(Tag::MsgParamSubPlan)?.into(),
           sub_plan_name: message.tag(Tag::MsgParamSubPlanName)?.into(),
         }),
@@ -485,18 +563,14 @@ impl<'src> UserNotice<'src> {
       ),
       "raid" => (
         Event::Raid(Raid {
-          viewer_count: message
-            .tag(Tag::MsgParamViewerCount)
-            .and_then(|v| v.parse().ok())?,
+          viewer_count: message.tag_u64(Tag::MsgParamViewerCount)?,
           profile_image_url: message.tag(Tag::MsgParamProfileImageUrl)?.into(),
         }),
         false,
       ),
       "subgift" | "anonsubgift" => (
         Event::SubGift(SubGift {
-          cumulative_months: message
-            .tag(Tag::MsgParamMonths)
-            .and_then(|v| v.parse().ok())?,
+          cumulative_months: message.tag_u64(Tag::MsgParamMonths)?,
           recipient: User {
             id: message.tag(Tag::MsgParamRecipientId)?.into(),
             login: message.tag(Tag::MsgParamRecipientUserName)?.into(),
@@ -504,38 +578,29 @@ impl<'src> UserNotice<'src> {
           },
           sub_plan: message.tag(Tag::MsgParamSubPlan)?.into(),
           sub_plan_name: message.tag(Tag::MsgParamSubPlanName)?.into(),
-          num_gifted_months: message
-            .tag(Tag::MsgParamGiftMonths)
-            .and_then(|v| v.parse().ok())?,
+          num_gifted_months: message.tag_u64(Tag::MsgParamGiftMonths)?,
+          sender_total_gifts: message.tag_u64(Tag::MsgParamSenderCount),
         }),
         event_id == "anonsubgift" || sender_id == AN_ANONYMOUS_GIFTER,
       ),
       "anonsubmysterygift" => (
         Event::AnonSubMysteryGift(AnonSubMysteryGift {
-          count: message
-            .tag(Tag::MsgParamMassGiftCount)
-            .and_then(|v| v.parse().ok())?,
+          count: message.tag_u64(Tag::MsgParamMassGiftCount)?,
           sub_plan: message.tag(Tag::MsgParamSubPlan)?.into(),
         }),
         true,
       ),
       "submysterygift" if sender_id == AN_ANONYMOUS_GIFTER => (
         Event::AnonSubMysteryGift(AnonSubMysteryGift {
-          count: message
-            .tag(Tag::MsgParamMassGiftCount)
-            .and_then(|v| v.parse().ok())?,
+          count: message.tag_u64(Tag::MsgParamMassGiftCount)?,
           sub_plan: message.tag(Tag::MsgParamSubPlan)?.into(),
         }),
         true,
       ),
       "submysterygift" => (
         Event::SubMysteryGift(SubMysteryGift {
-          count: message
-            .tag(Tag::MsgParamMassGiftCount)
-            .and_then(|v| v.parse().ok())?,
-          sender_total_gifts: message
-            .tag(Tag::MsgParamSenderCount)
-            .and_then(|v| v.parse().ok())?,
+          count: message.tag_u64(Tag::MsgParamMassGiftCount)?,
+          sender_total_gifts: message.tag_u64(Tag::MsgParamSenderCount)?,
           sub_plan: message.tag(Tag::MsgParamSubPlan)?.into(),
         }),
         false,
@@ -562,9 +627,7 @@ impl<'src> UserNotice<'src> {
       ),
       "bitsbadgetier" => (
         Event::BitsBadgeTier(BitsBadgeTier {
-          tier: message
-            .tag(Tag::MsgParamThreshold)
-            .and_then(|v| v.parse().ok())?,
+          tier: message.tag_u64(Tag::MsgParamThreshold)?,
         }),
         false,
       ),
@@ -614,6 +677,132 @@ impl<'src> UserNotice<'src> {
   }
 }
 
+impl<'src> UserNotice<'src> {
+  /// The color used to highlight the announcement.
+  ///
+  /// This is [`None`] if the event is not an [`Event::Announcement`].
+  pub fn announcement_color(&self) -> Option<AnnouncementColor<'_>> {
+    match &self.event {
+      Event::Announcement(announcement) => Some(announcement.color()),
+      _ => None,
+    }
+  }
+}
+
+impl<'src> UserNotice<'src> {
+  /// Describe the event as a human-readable sentence.
+  ///
+  /// The sentence is generated from the typed fields of the [`Event`], so it does not
+  /// depend on the exact wording Twitch uses in [`UserNotice::system_message`].
+  /// For events which are not known to this library, the system message is returned as-is,
+  /// and if it is not available, a generic description based on the [`UserNotice::event_id`].
+  ///
+  /// ⚠ This call will allocate and return a String.
+  pub fn describe(&self) -> String {
+    let sender = match &self.sender {
+      Some(sender) => sender.name(),
+      None => Cow::Borrowed("An anonymous user"),
+    };
+    let channel = self.channel.as_str();
+    let channel = channel.strip_prefix('#').unwrap_or(channel);
+
+    match &self.event {
+      Event::SubOrResub(sub) => {
+        let mut out = format!("{sender} subscribed {}.", describe_plan(sub.sub_plan()));
+        if sub.is_resub() {
+          out += &format!(
+            " They've subscribed for {}",
+            months(sub.cumulative_months())
+          );
+          match sub.streak_months() {
+            Some(streak) => out += &format!(", currently on a {streak} month streak!"),
+            None => out += "!",
+          }
+        }
+        out
+      }
+      Event::Raid(raid) => format!(
+        "{sender} is raiding {channel} with a party of {}.",
+        raid.viewer_count()
+      ),
+      Event::SubGift(gift) => {
+        let mut out = format!(
+          "{sender} gifted a {} sub to {}!",
+          tier(gift.sub_plan()),
+          gift.recipient().name()
+        );
+        if gift.num_gifted_months() > 1 {
+          out += &format!(" It lasts {}.", months(gift.num_gifted_months()));
+        }
+        out
+      }
+      Event::SubMysteryGift(gift) => format!(
+        "{sender} is gifting {} {} to {channel}'s community! They've gifted a total of {} in the channel!",
+        gift.count(),
+        subs(gift.count(), gift.sub_plan()),
+        gift.sender_total_gifts()
+      ),
+      Event::AnonSubMysteryGift(gift) => format!(
+        "An anonymous user is gifting {} {} to {channel}'s community!",
+        gift.count(),
+        subs(gift.count(), gift.sub_plan())
+      ),
+      Event::GiftPaidUpgrade(upgrade) => format!(
+        "{sender} is continuing the Gift Sub they got from {}!",
+        upgrade.gifter_name()
+      ),
+      Event::AnonGiftPaidUpgrade(_) => {
+        format!("{sender} is continuing the Gift Sub they got from an anonymous user!")
+      }
+      Event::Ritual(ritual) if ritual.name() == "new_chatter" => {
+        format!("{sender} is new to {channel}'s chat! Say hello!")
+      }
+      Event::BitsBadgeTier(badge) => {
+        format!("{sender} just earned a new {} Bits badge!", badge.tier())
+      }
+      Event::Announcement(_) => match self.text() {
+        Some(text) => format!("{sender} made an announcement: {text}"),
+        None => format!("{sender} made an announcement."),
+      },
+      _ => match self.system_message() {
+        Some(system_message) => system_message.into_owned(),
+        None => format!("{sender} triggered a `{}` event.", self.event_id()),
+      },
+    }
+  }
+}
+
+fn tier(sub_plan: &str) -> Cow<'_, str> {
+  match sub_plan {
+    "Prime" => Cow::Borrowed("Prime"),
+    "1000" => Cow::Borrowed("Tier 1"),
+    "2000" => Cow::Borrowed("Tier 2"),
+    "3000" => Cow::Borrowed("Tier 3"),
+    other => Cow::Owned(format!("\"{other}\"")),
+  }
+}
+
+fn describe_plan(sub_plan: &str) -> String {
+  match sub_plan {
+    "Prime" => "with Prime".into(),
+    other => format!("at {}", tier(other)),
+  }
+}
+
+fn subs(count: u64, sub_plan: &str) -> String {
+  match count {
+    1 => format!("{} Sub", tier(sub_plan)),
+    _ => format!("{} Subs", tier(sub_plan)),
+  }
+}
+
+fn months(count: u64) -> String {
+  match count {
+    1 => "1 month".into(),
+    n => format!("{n} months"),
+  }
+}
+
 impl<'src> super::FromIrc<'src> for UserNotice<'src> {
   #[inline]
   fn from_irc(message: IrcMessageRef<'src>) -> Result<Self, MessageParseError> {
@@ -631,36 +820,139 @@ impl<'src> From<UserNotice<'src>> for super::Message<'src> {
 mod tests {
   use super::*;
 
+  const USER_NOTICE_ANNOUNCEMENT_BLUE: &str = "@emotes=;login=pajbot;vip=0;tmi-sent-ts=1695554663565;flags=;mod=1;subscriber=1;id=bb1bec25-8f26-4ba3-a084-a6a2ca332f00;badge-info=subscriber/93;system-msg=;user-id=82008718;user-type=mod;room-id=11148817;badges=moderator/1,subscriber/3072;msg-param-color=BLUE;msg-id=announcement;color=#2E8B57;display-name=pajbot :tmi.twitch.tv USERNOTICE #pajlada :$ping xd";
+  const RESUB: &str = "@badge-info=subscriber/2;badges=subscriber/0,battlerite_1/1;color=#0000FF;display-name=Gutrin;emotes=1035663:0-3;flags=;id=e0975c76-054c-4954-8cb0-91b8867ec1ca;login=gutrin;mod=0;msg-id=resub;msg-param-cumulative-months=2;msg-param-months=0;msg-param-should-share-streak=1;msg-param-streak-months=2;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=1000;room-id=71092938;subscriber=1;system-msg=Gutrin\\ssubscribed\\sat\\sTier\\s1.\\sThey've\\ssubscribed\\sfor\\s2\\smonths,\\scurrently\\son\\sa\\s2\\smonth\\sstreak!;tmi-sent-ts=1581713640019;user-id=21156217;user-type= :tmi.twitch.tv USERNOTICE #xqcow :xqcL";
+  const RESUB_NO_SHARE_STREAK: &str = "@badge-info=;badges=premium/1;color=#8A2BE2;display-name=rene_rs;emotes=;flags=;id=ca1f02fb-77ec-487d-a9b3-bc4bfef2fe8b;login=rene_rs;mod=0;msg-id=resub;msg-param-cumulative-months=11;msg-param-months=0;msg-param-should-share-streak=0;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=Prime;room-id=71092938;subscriber=0;system-msg=rene_rs\\ssubscribed\\swith\\sTwitch\\sPrime.\\sThey've\\ssubscribed\\sfor\\s11\\smonths!;tmi-sent-ts=1590628650446;user-id=171356987;user-type= :tmi.twitch.tv USERNOTICE #xqcow";
+  const SUBGIFT: &str = "@badge-info=subscriber/9;badges=subscriber/9,sub-gifter/25;color=#1E90FF;display-name=Stridezgum;emotes=;flags=;id=3c6d3f1e-8f4a-4d4e-9a7e-2f1b6c1d0e5a;login=stridezgum;mod=0;msg-id=subgift;msg-param-gift-months=3;msg-param-months=7;msg-param-origin-id=da\\s39\\sa3\\see\\s5e\\s6b\\s4b\\s0d\\s32\\s55\\sbf\\sef\\s95\\s60\\s18\\s90\\saf\\sd8\\s07\\s09;msg-param-recipient-display-name=CrazyCrackAnimal;msg-param-recipient-id=86082877;msg-param-recipient-user-name=crazycrackanimal;msg-param-sender-count=27;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=1000;room-id=71092938;subscriber=1;system-msg=Stridezgum\\sgifted\\s3\\smonths\\sof\\sTier\\s1\\sto\\sCrazyCrackAnimal.\\sThey've\\sgifted\\s27\\smonths\\sin\\sthe\\schannel!;tmi-sent-ts=1594583782376;user-id=176466364;user-type= :tmi.twitch.tv USERNOTICE #xqcow";
+  const SUBGIFT_ANANONYMOUSGIFTER: &str = "@badge-info=;badges=;color=;display-name=AnAnonymousGifter;emotes=;flags=;id=62c3fd39-84cc-452a-9096-628a5306633a;login=ananonymousgifter;mod=0;msg-id=subgift;msg-param-fun-string=FunStringThree;msg-param-gift-months=1;msg-param-months=13;msg-param-origin-id=da\\s39\\sa3\\see\\s5e\\s6b\\s4b\\s0d\\s32\\s55\\sbf\\sef\\s95\\s60\\s18\\s90\\saf\\sd8\\s07\\s09;msg-param-recipient-display-name=Dot0422;msg-param-recipient-id=151784015;msg-param-recipient-user-name=dot0422;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=1000;room-id=71092938;subscriber=0;system-msg=An\\sanonymous\\suser\\sgifted\\sa\\sTier\\s1\\ssub\\sto\\sDot0422!\\s;tmi-sent-ts=1594495108936;user-id=274598607;user-type= :tmi.twitch.tv USERNOTICE #xqcow";
+  const SUBMYSTERYGIFT: &str = "@badge-info=;badges=sub-gifter/50;color=;display-name=AdamAtReflectStudios;emotes=;flags=;id=049e6371-7023-4fca-8605-7dec60e72e12;login=adamatreflectstudios;mod=0;msg-id=submysterygift;msg-param-mass-gift-count=20;msg-param-origin-id=1f\\sbe\\sbb\\s4a\\s81\\s9a\\s65\\sd1\\s4b\\s77\\sf5\\s23\\s16\\s4a\\sd3\\s13\\s09\\se7\\sbe\\s55;msg-param-sender-count=100;msg-param-sub-plan=1000;room-id=71092938;subscriber=0;system-msg=AdamAtReflectStudios\\sis\\sgifting\\s20\\sTier\\s1\\sSubs\\sto\\sxQcOW's\\scommunity!\\sThey've\\sgifted\\sa\\stotal\\sof\\s100\\sin\\sthe\\schannel!;tmi-sent-ts=1594583777669;user-id=211711554;user-type= :tmi.twitch.tv USERNOTICE #xqcow";
+  const REWARDGIFT: &str = "@badge-info=;badges=sub-gifter/50;color=;display-name=AdamAtReflectStudios;emotes=;flags=;id=7f1336e4-f84a-4510-809d-e57bf50af0cc;login=adamatreflectstudios;mod=0;msg-id=rewardgift;msg-param-domain=pride_megacommerce_2020;msg-param-selected-count=100;msg-param-total-reward-count=100;msg-param-trigger-amount=20;msg-param-trigger-type=SUBGIFT;room-id=71092938;subscriber=0;system-msg=AdamAtReflectStudios's\\sGift\\sshared\\srewards\\sto\\s100\\sothers\\sin\\sChat!;tmi-sent-ts=1594583778756;user-id=211711554;user-type= :tmi.twitch.tv USERNOTICE #xqcow";
+
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_user_notice_announcement() {
     assert_irc_snapshot!(UserNotice, "@emotes=;login=pajbot;vip=0;tmi-sent-ts=1695554663565;flags=;mod=1;subscriber=1;id=bb1bec25-8f26-4ba3-a084-a6a2ca332f00;badge-info=subscriber/93;system-msg=;user-id=82008718;user-type=mod;room-id=11148817;badges=moderator/1,subscriber/3072;msg-param-color=PRIMARY;msg-id=announcement;color=#2E8B57;display-name=pajbot :tmi.twitch.tv USERNOTICE #pajlada :$ping xd");
   }
 
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn parse_user_notice_announcement_blue() {
+    assert_irc_snapshot!(UserNotice, USER_NOTICE_ANNOUNCEMENT_BLUE);
+  }
+
+  #[test]
+  fn user_notice_announcement_color() {
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(USER_NOTICE_ANNOUNCEMENT_BLUE);
+    assert_eq!(msg.announcement_color(), Some(AnnouncementColor::Blue));
+
+    let src = USER_NOTICE_ANNOUNCEMENT_BLUE.replace("color=BLUE", "color=RAINBOW");
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(&src);
+    assert_eq!(
+      msg.announcement_color(),
+      Some(AnnouncementColor::Other("RAINBOW"))
+    );
+    assert_eq!(msg.announcement_color().unwrap().as_str(), "RAINBOW");
+
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(RESUB);
+    assert_eq!(msg.announcement_color(), None);
+
+    for color in ["PRIMARY", "BLUE", "GREEN", "ORANGE", "PURPLE"] {
+      assert_eq!(AnnouncementColor::parse(color).as_str(), color);
+      assert!(!matches!(
+        AnnouncementColor::parse(color),
+        AnnouncementColor::Other(_)
+      ));
+    }
+  }
+
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_sub() {
     assert_irc_snapshot!(UserNotice, "@badge-info=subscriber/0;badges=subscriber/0,premium/1;color=;display-name=fallenseraphhh;emotes=;flags=;id=2a9bea11-a80a-49a0-a498-1642d457f775;login=fallenseraphhh;mod=0;msg-id=sub;msg-param-cumulative-months=1;msg-param-months=0;msg-param-should-share-streak=0;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=Prime;room-id=71092938;subscriber=1;system-msg=fallenseraphhh\\ssubscribed\\swith\\sTwitch\\sPrime.;tmi-sent-ts=1582685713242;user-id=224005980;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_resub() {
     assert_irc_snapshot!(UserNotice, "@badge-info=subscriber/2;badges=subscriber/0,battlerite_1/1;color=#0000FF;display-name=Gutrin;emotes=1035663:0-3;flags=;id=e0975c76-054c-4954-8cb0-91b8867ec1ca;login=gutrin;mod=0;msg-id=resub;msg-param-cumulative-months=2;msg-param-months=0;msg-param-should-share-streak=1;msg-param-streak-months=2;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=1000;room-id=71092938;subscriber=1;system-msg=Gutrin\\ssubscribed\\sat\\sTier\\s1.\\sThey've\\ssubscribed\\sfor\\s2\\smonths,\\scurrently\\son\\sa\\s2\\smonth\\sstreak!;tmi-sent-ts=1581713640019;user-id=21156217;user-type= :tmi.twitch.tv USERNOTICE #xqcow :xqcL");
   }
 
+  #[test]
+  fn resub_msg_param() {
+    let irc = IrcMessageRef::parse(RESUB).unwrap();
+    assert_eq!(irc.msg_param("cumulative-months"), Some("2"));
+    assert_eq!(irc.msg_param("gift-months"), None);
+    assert_eq!(irc.msg_param("msg-param-months"), None);
+    assert_eq!(irc.into_owned().msg_param("should-share-streak"), Some("1"));
+  }
+
+  #[test]
+  fn resub_timestamp() {
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(RESUB);
+    assert_eq!(msg.timestamp_millis(), 1581713640019);
+    #[cfg(feature = "chrono")]
+    assert_eq!(msg.timestamp().timestamp_millis(), 1581713640019);
+    assert_eq!(
+      msg.timestamp_system(),
+      std::time::UNIX_EPOCH + std::time::Duration::from_millis(1581713640019)
+    );
+  }
+
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_resub_no_share_streak() {
     assert_irc_snapshot!(UserNotice, "@badge-info=;badges=premium/1;color=#8A2BE2;display-name=rene_rs;emotes=;flags=;id=ca1f02fb-77ec-487d-a9b3-bc4bfef2fe8b;login=rene_rs;mod=0;msg-id=resub;msg-param-cumulative-months=11;msg-param-months=0;msg-param-should-share-streak=0;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=Prime;room-id=71092938;subscriber=0;system-msg=rene_rs\\ssubscribed\\swith\\sTwitch\\sPrime.\\sThey've\\ssubscribed\\sfor\\s11\\smonths!;tmi-sent-ts=1590628650446;user-id=171356987;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_raid() {
     assert_irc_snapshot!(UserNotice, "@badge-info=;badges=sub-gifter/50;color=;display-name=AdamAtReflectStudios;emotes=;flags=;id=e21409b1-d25d-4a1a-b5cf-ef27d8b7030e;login=adamatreflectstudios;mod=0;msg-id=subgift;msg-param-gift-months=1;msg-param-months=2;msg-param-origin-id=da\\s39\\sa3\\see\\s5e\\s6b\\s4b\\s0d\\s32\\s55\\sbf\\sef\\s95\\s60\\s18\\s90\\saf\\sd8\\s07\\s09;msg-param-recipient-display-name=qatarking24xd;msg-param-recipient-id=236653628;msg-param-recipient-user-name=qatarking24xd;msg-param-sender-count=0;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=1000;room-id=71092938;subscriber=0;system-msg=AdamAtReflectStudios\\sgifted\\sa\\sTier\\s1\\ssub\\sto\\sqatarking24xd!;tmi-sent-ts=1594583782376;user-id=211711554;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn parse_subgift() {
+    assert_irc_snapshot!(UserNotice, SUBGIFT);
+  }
+
+  #[test]
+  fn subgift_details() {
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(SUBGIFT);
+    assert_eq!(msg.sender().unwrap().login(), "stridezgum");
+    let Event::SubGift(gift) = msg.event() else {
+      panic!("expected a subgift, got {:?}", msg.event());
+    };
+    assert_eq!(gift.recipient().id(), "86082877");
+    assert_eq!(gift.recipient().login(), "crazycrackanimal");
+    assert_eq!(gift.recipient().name(), "CrazyCrackAnimal");
+    assert_eq!(gift.cumulative_months(), 7);
+    assert_eq!(gift.num_gifted_months(), 3);
+    assert_eq!(gift.sender_total_gifts(), Some(27));
+
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(SUBGIFT_ANANONYMOUSGIFTER);
+    assert!(msg.sender().is_none());
+    let Event::SubGift(gift) = msg.event() else {
+      panic!("expected a subgift, got {:?}", msg.event());
+    };
+    assert_eq!(gift.recipient().login(), "dot0422");
+    assert_eq!(gift.sender_total_gifts(), None);
+
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(SUBMYSTERYGIFT);
+    let Event::SubMysteryGift(gift) = msg.event() else {
+      panic!("expected a submysterygift, got {:?}", msg.event());
+    };
+    assert_eq!(gift.count(), 20);
+    assert_eq!(gift.sender_total_gifts(), 100);
+  }
+
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_subgift_ananonymousgifter() {
     assert_irc_snapshot!(UserNotice, "@badge-info=;badges=;color=;display-name=AnAnonymousGifter;emotes=;flags=;id=62c3fd39-84cc-452a-9096-628a5306633a;login=ananonymousgifter;mod=0;msg-id=subgift;msg-param-fun-string=FunStringThree;msg-param-gift-months=1;msg-param-months=13;msg-param-origin-id=da\\s39\\sa3\\see\\s5e\\s6b\\s4b\\s0d\\s32\\s55\\sbf\\sef\\s95\\s60\\s18\\s90\\saf\\sd8\\s07\\s09;msg-param-recipient-display-name=Dot0422;msg-param-recipient-id=151784015;msg-param-recipient-user-name=dot0422;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=1000;room-id=71092938;subscriber=0;system-msg=An\\sanonymous\\suser\\sgifted\\sa\\sTier\\s1\\ssub\\sto\\sDot0422!\\s;tmi-sent-ts=1594495108936;user-id=274598607;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_anonsubgift() {
     // note there are no anonsubgift messages being sent on Twitch IRC as of the time of writing this.
@@ -669,16 +961,19 @@ mod tests {
     assert_irc_snapshot!(UserNotice, "@badge-info=;badges=;color=;display-name=xQcOW;emotes=;flags=;id=e21409b1-d25d-4a1a-b5cf-ef27d8b7030e;login=xqcow;mod=0;msg-id=anonsubgift;msg-param-gift-months=1;msg-param-months=2;msg-param-origin-id=da\\s39\\sa3\\see\\s5e\\s6b\\s4b\\s0d\\s32\\s55\\sbf\\sef\\s95\\s60\\s18\\s90\\saf\\sd8\\s07\\s09;msg-param-recipient-display-name=qatarking24xd;msg-param-recipient-id=236653628;msg-param-recipient-user-name=qatarking24xd;msg-param-sender-count=0;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=1000;room-id=71092938;subscriber=0;system-msg=An\\sanonymous\\sgifter\\sgifted\\sa\\sTier\\s1\\ssub\\sto\\sqatarking24xd!;tmi-sent-ts=1594583782376;user-id=71092938;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_submysterygift() {
     assert_irc_snapshot!(UserNotice, "@badge-info=;badges=sub-gifter/50;color=;display-name=AdamAtReflectStudios;emotes=;flags=;id=049e6371-7023-4fca-8605-7dec60e72e12;login=adamatreflectstudios;mod=0;msg-id=submysterygift;msg-param-mass-gift-count=20;msg-param-origin-id=1f\\sbe\\sbb\\s4a\\s81\\s9a\\s65\\sd1\\s4b\\s77\\sf5\\s23\\s16\\s4a\\sd3\\s13\\s09\\se7\\sbe\\s55;msg-param-sender-count=100;msg-param-sub-plan=1000;room-id=71092938;subscriber=0;system-msg=AdamAtReflectStudios\\sis\\sgifting\\s20\\sTier\\s1\\sSubs\\sto\\sxQcOW's\\scommunity!\\sThey've\\sgifted\\sa\\stotal\\sof\\s100\\sin\\sthe\\schannel!;tmi-sent-ts=1594583777669;user-id=211711554;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_submysterygift_ananonymousgifter() {
     assert_irc_snapshot!(UserNotice, "@badge-info=;badges=;color=;display-name=AnAnonymousGifter;emotes=;flags=;id=8db97752-3dee-460b-9001-e925d0e2ba5b;login=ananonymousgifter;mod=0;msg-id=submysterygift;msg-param-mass-gift-count=10;msg-param-origin-id=13\\s33\\sed\\sc0\\sef\\sa0\\s7b\\s9b\\s48\\s59\\scb\\scc\\se4\\s39\\s7b\\s90\\sf9\\s54\\s75\\s66;msg-param-sub-plan=1000;room-id=71092938;subscriber=0;system-msg=An\\sanonymous\\suser\\sis\\sgifting\\s10\\sTier\\s1\\sSubs\\sto\\sxQcOW's\\scommunity!;tmi-sent-ts=1585447099603;user-id=274598607;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_anonsubmysterygift() {
     // again, this is never emitted on IRC currently. So this test case is a made-up
@@ -687,6 +982,7 @@ mod tests {
     assert_irc_snapshot!(UserNotice, "@badge-info=subscriber/2;badges=subscriber/2;color=#00FFF5;display-name=CrazyCrackAnimal;emotes=;flags=;id=7006f242-a45c-4e07-83b3-11f9c6d1ee28;login=crazycrackanimal;mod=0;msg-id=giftpaidupgrade;msg-param-sender-login=stridezgum;msg-param-sender-name=Stridezgum;room-id=71092938;subscriber=1;system-msg=CrazyCrackAnimal\\sis\\scontinuing\\sthe\\sGift\\sSub\\sthey\\sgot\\sfrom\\sStridezgum!;tmi-sent-ts=1594518849459;user-id=86082877;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_giftpaidupgrade_with_promo() {
     // I can't find any real examples for this type of message, so this is a made-up test case
@@ -695,6 +991,7 @@ mod tests {
     assert_irc_snapshot!(UserNotice, "@badge-info=subscriber/1;badges=subscriber/0,premium/1;color=#8A2BE2;display-name=samura1jack_ttv;emotes=;flags=;id=144ee636-0c1d-404e-8b29-35449a045a7e;login=samura1jack_ttv;mod=0;msg-id=anongiftpaidupgrade;room-id=71092938;subscriber=1;system-msg=samura1jack_ttv\\sis\\scontinuing\\sthe\\sGift\\sSub\\sthey\\sgot\\sfrom\\san\\sanonymous\\suser!;tmi-sent-ts=1594327421732;user-id=102707709;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_anongiftpaidupgrade_with_promo() {
     // I can't find any real examples for this type of message, so this is a made-up test case
@@ -703,16 +1000,44 @@ mod tests {
     assert_irc_snapshot!(UserNotice, "@badge-info=subscriber/1;badges=subscriber/0,premium/1;color=#8A2BE2;display-name=samura1jack_ttv;emotes=;flags=;id=144ee636-0c1d-404e-8b29-35449a045a7e;msg-param-promo-name=TestSubtember2020;msg-param-promo-gift-total=4003;login=samura1jack_ttv;mod=0;msg-id=anongiftpaidupgrade;room-id=71092938;subscriber=1;system-msg=samura1jack_ttv\\sis\\scontinuing\\sthe\\sGift\\sSub\\sthey\\sgot\\sfrom\\san\\sanonymous\\suser!\\sbla\\sbla\\sbla\\sstuff\\sabout\\spromo\\shere;tmi-sent-ts=1594327421732;user-id=102707709;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_ritual() {
     assert_irc_snapshot!(UserNotice, "@badge-info=;badges=;color=;display-name=SevenTest1;emotes=30259:0-6;id=37feed0f-b9c7-4c3a-b475-21c6c6d21c3d;login=seventest1;mod=0;msg-id=ritual;msg-param-ritual-name=new_chatter;room-id=6316121;subscriber=0;system-msg=Seventoes\\sis\\snew\\shere!;tmi-sent-ts=1508363903826;turbo=0;user-id=131260580;user-type= :tmi.twitch.tv USERNOTICE #seventoes :HeyGuys");
   }
 
+  #[cfg(feature = "chrono")]
   #[test]
   fn parse_bitsbadgetier() {
     assert_irc_snapshot!(UserNotice, "@badge-info=;badges=sub-gifter/50;color=;display-name=AdamAtReflectStudios;emotes=;flags=;id=7f1336e4-f84a-4510-809d-e57bf50af0cc;login=adamatreflectstudios;mod=0;msg-id=rewardgift;msg-param-domain=pride_megacommerce_2020;msg-param-selected-count=100;msg-param-total-reward-count=100;msg-param-trigger-amount=20;msg-param-trigger-type=SUBGIFT;room-id=71092938;subscriber=0;system-msg=AdamAtReflectStudios's\\sGift\\sshared\\srewards\\sto\\s100\\sothers\\sin\\sChat!;tmi-sent-ts=1594583778756;user-id=211711554;user-type= :tmi.twitch.tv USERNOTICE #xqcow");
   }
 
+  #[test]
+  fn describe_resub() {
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(RESUB);
+    assert_eq!(
+      msg.describe(),
+      "Gutrin subscribed at Tier 1. They've subscribed for 2 months, currently on a 2 month streak!"
+    );
+
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(RESUB_NO_SHARE_STREAK);
+    let description = msg.describe();
+    assert!(description.contains("11 months"), "{description}");
+    assert_eq!(
+      description,
+      "rene_rs subscribed with Prime. They've subscribed for 11 months!"
+    );
+  }
+
+  #[test]
+  fn describe_unknown_falls_back_to_system_message() {
+    let msg = crate::msg::macros::_parse_irc::<UserNotice>(REWARDGIFT);
+    assert_eq!(
+      msg.describe(),
+      "AdamAtReflectStudios's Gift shared rewards to 100 others in Chat!"
+    );
+  }
+
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_user_notice_announcement() {
@@ -5,10 +5,11 @@
 //!
 //! For example, [`UserState::badges`] may be different from [`GlobalUserState::badges`][crate::msg::global_user_state::GlobalUserState::badges].
 
-use super::{is_not_empty, parse_badges, split_comma, Badge, MessageParseError};
-use crate::common::{ChannelRef, MaybeOwned};
+use super::{is_not_empty, parse_badges, split_comma, Badge, KnownBadge, MessageParseError};
+use crate::common::{Channel, ChannelRef, MaybeOwned};
 use crate::irc::{Command, IrcMessageRef, Tag};
 use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
 
 /// Sent upon joining a channel, or upon successfully sending a `PRIVMSG` message to a channel.
 ///
@@ -33,6 +34,8 @@ pub struct UserState<'src> {
 
   #[cfg_attr(feature = "serde", serde(borrow))]
   color: Option<Cow<'src, str>>,
+
+  is_moderator: bool,
 }
 
 generate_getters! {
@@ -44,14 +47,14 @@ generate_getters! {
     user_name -> &str = self.user_name.as_ref(),
 
     /// Iterator over channel-specific badges.
-    badges -> impl Iterator<Item = &Badge<'src>> + DoubleEndedIterator + ExactSizeIterator
+    badges -> impl DoubleEndedIterator<Item = &Badge<'src>> + ExactSizeIterator
       = self.badges.iter(),
 
     /// Number of channel-specific badges.
     num_badges -> usize = self.badges.len(),
 
     /// Iterator over the emote sets which are available in this channel.
-    emote_sets -> impl Iterator<Item = &str> + DoubleEndedIterator + ExactSizeIterator
+    emote_sets -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator
       = self.emote_sets.iter().map(|v| v.as_ref()),
 
     /// Number of emote sets which are avaialble in this channel.
@@ -63,6 +66,14 @@ generate_getters! {
     /// To match the behavior of Twitch, users should be
     /// given a globally-consistent random color.
     color -> Option<&str> = self.color.as_deref(),
+
+    /// Whether the user is a moderator in this channel.
+    ///
+    /// This does not include the broadcaster, see [`UserState::is_broadcaster`].
+    is_moderator -> bool,
+
+    /// Whether the user is the broadcaster of this channel.
+    is_broadcaster -> bool = self.badges.iter().any(|badge| badge.is(KnownBadge::Broadcaster)),
   }
 }
 
@@ -72,14 +83,18 @@ impl<'src> UserState<'src> {
       return None;
     }
 
+    let badges: Vec<_> = message
+      .tag(Tag::Badges)
+      .zip(message.tag(Tag::BadgeInfo))
+      .map(|(badges, badge_info)| parse_badges(badges, badge_info))
+      .unwrap_or_default();
+    let is_moderator = message.tag_bool(Tag::Mod) == Some(true)
+      || badges.iter().any(|badge| badge.is(KnownBadge::Moderator));
+
     Some(UserState {
       channel: MaybeOwned::Ref(message.channel()?),
       user_name: message.tag(Tag::DisplayName)?.into(),
-      badges: message
-        .tag(Tag::Badges)
-        .zip(message.tag(Tag::BadgeInfo))
-        .map(|(badges, badge_info)| parse_badges(badges, badge_info))
-        .unwrap_or_default(),
+      badges,
       emote_sets: message
         .tag(Tag::EmoteSets)
         .map(split_comma)
@@ -90,6 +105,7 @@ impl<'src> UserState<'src> {
         .tag(Tag::Color)
         .filter(is_not_empty)
         .map(|v| v.into()),
+      is_moderator,
     })
   }
 }
@@ -107,13 +123,210 @@ impl<'src> From<UserState<'src>> for super::Message<'src> {
   }
 }
 
+/// A set of emote set IDs, as sent in [`UserState`] and [`GlobalUserState`][crate::msg::global_user_state::GlobalUserState].
+///
+/// This may be used to compare the emote sets of two consecutive states:
+///
+/// ```rust
+/// use tmi::EmoteSets;
+///
+/// let previous = EmoteSets::parse("0,300");
+/// let current = EmoteSets::parse("0,300,793");
+/// let (added, removed) = current.difference(&previous);
+/// assert_eq!(added, ["793"]);
+/// assert!(removed.is_empty());
+/// ```
+///
+/// It may also be collected from [`UserState::emote_sets`]:
+///
+/// ```rust
+/// # fn _test(state: tmi::UserState<'_>) {
+/// let sets: tmi::EmoteSets = state.emote_sets().collect();
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EmoteSets<'src> {
+  sets: BTreeSet<&'src str>,
+}
+
+impl<'src> EmoteSets<'src> {
+  /// Parse the comma-separated value of an `emote-sets` tag.
+  ///
+  /// Empty IDs are skipped, so an empty tag value results in an empty set.
+  pub fn parse(value: &'src str) -> Self {
+    split_comma(value).collect()
+  }
+
+  /// Compare these emote sets to the `previous` ones.
+  ///
+  /// Returns the emote sets which were added and removed, in that order.
+  /// Both lists are sorted.
+  pub fn difference<'a>(&self, previous: &EmoteSets<'a>) -> (Vec<&'src str>, Vec<&'a str>) {
+    let added = self.iter().filter(|id| !previous.contains(id)).collect();
+    let removed = previous.iter().filter(|id| !self.contains(id)).collect();
+    (added, removed)
+  }
+
+  /// Whether the emote set with the given `id` is in this set.
+  pub fn contains(&self, id: &str) -> bool {
+    self.sets.contains(id)
+  }
+
+  /// Iterator over the emote set IDs, in sorted order.
+  pub fn iter(&self) -> impl DoubleEndedIterator<Item = &'src str> + ExactSizeIterator + '_ {
+    self.sets.iter().copied()
+  }
+
+  /// Number of emote sets.
+  pub fn len(&self) -> usize {
+    self.sets.len()
+  }
+
+  /// Whether there are no emote sets.
+  pub fn is_empty(&self) -> bool {
+    self.sets.is_empty()
+  }
+}
+
+impl<'src> FromIterator<&'src str> for EmoteSets<'src> {
+  fn from_iter<T: IntoIterator<Item = &'src str>>(iter: T) -> Self {
+    Self {
+      sets: iter.into_iter().filter(|id| !id.is_empty()).collect(),
+    }
+  }
+}
+
+/// Caches the emote sets from each channel's latest [`UserState`],
+/// in order to detect when they change.
+#[derive(Clone, Debug, Default)]
+pub struct EmoteSetCache {
+  channels: HashMap<Channel, BTreeSet<String>>,
+}
+
+impl EmoteSetCache {
+  /// Create an empty cache.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Update the cached emote sets for the channel of this `state`.
+  ///
+  /// Returns [`None`] if the emote sets did not change,
+  /// or if this is the first [`UserState`] seen for the channel.
+  pub fn update(&mut self, state: &UserState<'_>) -> Option<EmoteSetsChanged> {
+    let current: EmoteSets = state.emote_sets().collect();
+    let channel = state.channel().normalize();
+    let sets = current.iter().map(String::from).collect();
+    let previous = self.channels.insert(channel.clone(), sets)?;
+    let previous: EmoteSets = previous.iter().map(String::as_str).collect();
+
+    let (added, removed) = current.difference(&previous);
+    if added.is_empty() && removed.is_empty() {
+      return None;
+    }
+
+    Some(EmoteSetsChanged {
+      channel,
+      added: added.into_iter().map(String::from).collect(),
+      removed: removed.into_iter().map(String::from).collect(),
+    })
+  }
+
+  /// The cached emote sets for `channel`.
+  pub fn get(&self, channel: &ChannelRef) -> Option<impl ExactSizeIterator<Item = &str> + '_> {
+    self
+      .channels
+      .get(&channel.normalize())
+      .map(|sets| sets.iter().map(|v| v.as_str()))
+  }
+}
+
+/// The emote sets available in a channel have changed.
+///
+/// See [`EmoteSetCache::update`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmoteSetsChanged {
+  channel: Channel,
+  added: Vec<String>,
+  removed: Vec<String>,
+}
+
+generate_getters! {
+  for EmoteSetsChanged as self {
+    /// Name of the channel in which the emote sets changed.
+    channel -> &ChannelRef = self.channel.as_ref(),
+
+    /// Emote sets which became available.
+    added -> &[String] = &self.added,
+
+    /// Emote sets which are no longer available.
+    removed -> &[String] = &self.removed,
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  const USERSTATE: &str = "@badge-info=;badges=;color=#FF0000;display-name=TESTUSER;emote-sets=0;mod=0;subscriber=0;user-type= :tmi.twitch.tv USERSTATE #randers";
+
+  #[test]
+  fn emote_sets_changed() {
+    let mut cache = EmoteSetCache::new();
+
+    let src = USERSTATE.replace("emote-sets=0;", "emote-sets=0,1,2;");
+    let a = crate::msg::macros::_parse_irc::<UserState>(&src);
+    assert_eq!(cache.update(&a), None);
+    assert_eq!(cache.update(&a), None);
+
+    let src = USERSTATE
+      .replace("emote-sets=0;", "emote-sets=0,2,3,4;")
+      .replace("#randers", "#Randers");
+    let b = crate::msg::macros::_parse_irc::<UserState>(&src);
+    let changed = cache.update(&b).unwrap();
+    assert_eq!(changed.channel().as_str(), "#randers");
+    assert_eq!(changed.added(), ["3", "4"]);
+    assert_eq!(changed.removed(), ["1"]);
+    assert_eq!(
+      cache
+        .get(ChannelRef::parse("#randers").unwrap())
+        .unwrap()
+        .collect::<Vec<_>>(),
+      ["0", "2", "3", "4"]
+    );
+  }
+
+  #[test]
+  fn emote_sets_difference() {
+    let previous_src = USERSTATE.replace("emote-sets=0;", "emote-sets=0,300;");
+    let previous = crate::msg::macros::_parse_irc::<UserState>(&previous_src);
+    let current_src = USERSTATE.replace("emote-sets=0;", "emote-sets=0,300,793;");
+    let current = crate::msg::macros::_parse_irc::<UserState>(&current_src);
+    let previous: EmoteSets = previous.emote_sets().collect();
+    let current: EmoteSets = current.emote_sets().collect();
+    assert_eq!(current.len(), 3);
+    assert!(current.contains("793"));
+    assert!(!previous.contains("793"));
+
+    let (added, removed) = current.difference(&previous);
+    assert_eq!(added, ["793"]);
+    assert!(removed.is_empty());
+
+    let (added, removed) = previous.difference(&current);
+    assert!(added.is_empty());
+    assert_eq!(removed, ["793"]);
+
+    let (added, removed) = EmoteSets::parse("0,19194").difference(&EmoteSets::parse("0,300"));
+    assert_eq!(added, ["19194"]);
+    assert_eq!(removed, ["300"]);
+
+    assert!(EmoteSets::parse("").is_empty());
+    assert_eq!(current.iter().collect::<Vec<_>>(), ["0", "300", "793"]);
+  }
+
   #[test]
   fn parse_userstate() {
-    assert_irc_snapshot!(UserState, "@badge-info=;badges=;color=#FF0000;display-name=TESTUSER;emote-sets=0;mod=0;subscriber=0;user-type= :tmi.twitch.tv USERSTATE #randers");
+    assert_irc_snapshot!(UserState, USERSTATE);
   }
 
   #[test]
@@ -121,10 +334,25 @@ mod tests {
     assert_irc_snapshot!(UserState, "@badge-info=;badges=moderator/1;color=#8A2BE2;display-name=TESTUSER;emote-sets=0,75c09c7b-332a-43ec-8be8-1d4571706155;mod=1;subscriber=0;user-type=mod :tmi.twitch.tv USERSTATE #randers");
   }
 
+  #[test]
+  fn userstate_roles() {
+    let msg = crate::msg::macros::_parse_irc::<UserState>("@badge-info=;badges=moderator/1;color=#8A2BE2;display-name=TESTUSER;emote-sets=0;mod=1;subscriber=0;user-type=mod :tmi.twitch.tv USERSTATE #randers");
+    assert!(msg.is_moderator());
+    assert!(!msg.is_broadcaster());
+
+    let msg = crate::msg::macros::_parse_irc::<UserState>("@badge-info=;badges=broadcaster/1;color=#8A2BE2;display-name=TESTUSER;emote-sets=0;mod=0;subscriber=0;user-type= :tmi.twitch.tv USERSTATE #testuser");
+    assert!(!msg.is_moderator());
+    assert!(msg.is_broadcaster());
+
+    let msg = crate::msg::macros::_parse_irc::<UserState>(USERSTATE);
+    assert!(!msg.is_moderator());
+    assert!(!msg.is_broadcaster());
+  }
+
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_userstate() {
-    assert_irc_roundtrip!(UserState, "@badge-info=;badges=;color=#FF0000;display-name=TESTUSER;emote-sets=0;mod=0;subscriber=0;user-type= :tmi.twitch.tv USERSTATE #randers");
+    assert_irc_roundtrip!(UserState, USERSTATE);
   }
 
   #[cfg(feature = "serde")]
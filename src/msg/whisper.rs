@@ -39,7 +39,7 @@ generate_getters! {
     text -> &str = self.text.as_ref(),
 
     /// Iterator over the badges visible in the whisper window.
-    badges -> impl Iterator<Item = &Badge<'src>> + DoubleEndedIterator + ExactSizeIterator
+    badges -> impl DoubleEndedIterator<Item = &Badge<'src>> + ExactSizeIterator
       = self.badges.iter(),
 
     /// Number of badges visible in the whisper window.